@@ -0,0 +1,71 @@
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use json_parser::{json, Document, JsonValue, Number};
+
+fn corpus(records: usize) -> String {
+    let mut out = String::from("{\"records\": [");
+    for i in 0..records {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write!(
+            out,
+            "{{\"id\": {},\"name\": \"user{}\",\"active\": {},\"tags\": [\"a\", \"b\", \"c\"],\"score\": {}}}",
+            i,
+            i,
+            i % 2 == 0,
+            i * 7 % 100
+        )
+        .unwrap();
+    }
+    out.push_str("]}");
+    out
+}
+
+fn sum_tree(value: &JsonValue) -> isize {
+    value.as_number().and_then(Number::as_isize).unwrap_or(0)
+        + value.iter().map(sum_tree).sum::<isize>()
+        + value.members().map(|m| sum_tree(m.value())).sum::<isize>()
+}
+
+fn sum_document(value: json_parser::document::ValueRef<'_, '_>) -> isize {
+    value.as_number().and_then(Number::as_isize).unwrap_or(0)
+        + value.iter().map(sum_document).sum::<isize>()
+        + value.members().map(|(_, v)| sum_document(v)).sum::<isize>()
+}
+
+fn bench(name: &str, bytes: usize, mut f: impl FnMut()) {
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(1) {
+        f();
+        iterations += 1;
+    }
+    let per_iter = start.elapsed() / iterations;
+    println!(
+        "{:<24} {:>10.2?}/iter {:>8.2} MB/s",
+        name,
+        per_iter,
+        bytes as f64 / per_iter.as_secs_f64() / 1_000_000.0
+    );
+}
+
+fn main() {
+    let input = corpus(200);
+    bench("tree parse", input.len(), || {
+        black_box(json(black_box(&input)));
+    });
+    bench("document parse", input.len(), || {
+        black_box(Document::parse(black_box(&input)));
+    });
+    bench("tree parse + walk", input.len(), || {
+        let object = json(&input).unwrap();
+        black_box(object.iter().map(|m| sum_tree(m.value())).sum::<isize>());
+    });
+    bench("document parse + walk", input.len(), || {
+        let document = Document::parse(&input).unwrap();
+        black_box(sum_document(document.root()));
+    });
+}
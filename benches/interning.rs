@@ -0,0 +1,72 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use json_parser::{json_with_options, ParseOptions};
+
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn corpus(records: usize, keys: usize) -> String {
+    let mut out = String::from("{\"records\": [");
+    for i in 0..records {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push('{');
+        for k in 0..keys {
+            if k > 0 {
+                out.push(',');
+            }
+            write!(out, "\"field_number_{}\": {}", k, i * k).unwrap();
+        }
+        out.push('}');
+    }
+    out.push_str("]}");
+    out
+}
+
+fn measure(name: &str, input: &str, options: &ParseOptions) {
+    let live_before = LIVE.load(Ordering::Relaxed);
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let object = json_with_options(input, options).unwrap();
+    // Temporaries of the parse are freed by now, so what is left is the tree itself.
+    let retained = LIVE.load(Ordering::Relaxed) - live_before;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+    println!(
+        "{:<12} {:>10} bytes retained {:>8} allocations during parse",
+        name, retained, allocations
+    );
+    drop(object);
+}
+
+fn main() {
+    let input = corpus(200, 20);
+    println!("input: {} bytes, 200 objects x 20 keys", input.len());
+    measure("owned keys", &input, &ParseOptions::default());
+    measure(
+        "interned",
+        &input,
+        &ParseOptions {
+            intern_keys: true,
+            ..ParseOptions::default()
+        },
+    );
+}
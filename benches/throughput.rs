@@ -0,0 +1,501 @@
+// Throughput and allocations of every way of reading and writing JSON, over generated
+// corpora shaped like real traffic:
+//
+//     cargo bench --bench throughput
+//     cargo bench --bench throughput -- --save target/bench/before.json
+//     cargo bench --bench throughput -- --baseline target/bench/before.json
+//
+// `--save` writes the results as JSON, one object per corpus and path, for scripts to
+// compare; `--baseline` reads such a file back and prints the change against it.
+// `--filter <text>` only runs the corpora or paths containing `text`, and
+// `BENCH_MILLIS` sets how long each one runs (500ms by default).
+//
+// The paths are:
+// - `dom`: `parse_value`, building an owned `JsonValue`,
+// - `borrowed`: `Document::parse`, borrowing strings from the input,
+// - `events`: the grammar with a builder that keeps nothing. There is no streaming
+//   parser yet; this is the cost one would start from,
+// - `serialize`: `to_string` of the parsed value, measured against its output size.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Write;
+use std::fs;
+use std::hint::black_box;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use json_parser::serialize::{to_string, to_string_pretty};
+use json_parser::{
+    json, parse_value, parse_with, Builder, Document, JsonValue, Number, ParseOptions,
+};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED.fetch_add(new_size.saturating_sub(layout.size()), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// A fixed sequence, so that corpora are the same on every run and every commit.
+struct Rng(u64);
+impl Rng {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % n
+    }
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+const WORDS: &[&str] = &[
+    "json",
+    "parser",
+    "rust",
+    "benchmark",
+    "café",
+    "naïve",
+    "東京",
+    "🚀",
+    "release",
+    "the",
+    "quick",
+    "brown",
+    "fox",
+    "jumps",
+    "over",
+    "lazy",
+    "dog",
+    "#rustlang",
+    "@someone",
+    "http",
+];
+
+// Search results from a social network API: wide objects, nested users and entities,
+// big integer ids, and text with non-ASCII characters.
+fn twitter(statuses: usize) -> String {
+    let mut rng = Rng(0x7717);
+    let mut out = String::from("{\"statuses\": [");
+    for i in 0..statuses {
+        if i > 0 {
+            out.push(',');
+        }
+        let text: Vec<_> = (0..12).map(|_| rng.pick(WORDS)).collect();
+        let id = 1_200_000_000_000_000_000u64 + rng.below(1 << 40);
+        write!(
+            out,
+            r#"{{"created_at": "Mon Sep 24 03:35:21 +0000 2012", "id": {id}, "id_str": "{id}",
+"text": "{text}", "truncated": false, "in_reply_to_status_id": null,
+"user": {{"id": {user}, "screen_name": "user_{user}", "name": "{name}",
+"description": "{text}", "followers_count": {followers}, "verified": {verified},
+"profile_image_url": "http:\/\/a0.twimg.com\/profile_images\/{user}\/avatar_normal.png"}},
+"entities": {{"hashtags": [{{"text": "{hashtag}", "indices": [{start}, {end}]}}],
+"urls": [], "user_mentions": []}}, "retweet_count": {retweets}, "favorited": false,
+"lang": "en", "geo": null, "coordinates": null, "place": null}}"#,
+            id = id,
+            text = text.join(" "),
+            user = rng.below(1_000_000_000),
+            name = rng.pick(WORDS),
+            followers = rng.below(100_000),
+            verified = rng.below(2) == 0,
+            hashtag = rng.pick(WORDS),
+            start = rng.below(100),
+            end = 100 + rng.below(40),
+            retweets = rng.below(1000),
+        )
+        .unwrap();
+    }
+    out.push_str(
+        r#"], "search_metadata": {"completed_in": 0.087, "max_id": 250126199840518145,
+"query": "%23json", "count": 100, "since_id": 0}}"#,
+    );
+    out
+}
+
+// Coordinates of polygons: almost nothing but floats.
+fn floats(polygons: usize) -> String {
+    let mut rng = Rng(0xf10a7);
+    let mut out = String::from("{\"type\": \"FeatureCollection\", \"features\": [");
+    for i in 0..polygons {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"type\": \"Polygon\", \"coordinates\": [");
+        for j in 0..64 {
+            if j > 0 {
+                out.push(',');
+            }
+            let longitude = rng.below(360_000_000) as f64 / 1e6 - 180.0;
+            let latitude = rng.below(180_000_000) as f64 / 1e6 - 90.0;
+            write!(out, "[{}, {}]", longitude, latitude).unwrap();
+        }
+        out.push_str("]}");
+    }
+    out.push_str("]}");
+    out
+}
+
+// Configuration nested deep, as pretty printers write it.
+fn nested(sections: usize) -> String {
+    fn section(rng: &mut Rng, depth: usize) -> JsonValue {
+        json!({
+            "enabled": (rng.below(2) == 0),
+            "name": (rng.pick(WORDS)),
+            "retries": (rng.below(10) as i64),
+            "timeout": (rng.below(10_000) as f64 / 100.0),
+            "tags": [(rng.pick(WORDS)), (rng.pick(WORDS))],
+            "child": (match depth {
+                0 => None,
+                _ => Some(section(rng, depth - 1)),
+            })
+        })
+    }
+    let mut rng = Rng(0xc0f19);
+    let sections: Vec<_> = (0..sections).map(|_| section(&mut rng, 48)).collect();
+    to_string_pretty(&json!({ "sections": (sections) }))
+}
+
+// Long strings full of escapes: quotes, control characters and UTF-16 surrogate pairs.
+fn escaped(strings: usize) -> String {
+    let mut rng = Rng(0xe5c);
+    let pieces = [
+        "plain text ",
+        "\\\"quoted\\\" ",
+        "back\\\\slash ",
+        "line\\nbreak ",
+        "\\ttab ",
+        "\\u00e9\\u00e8 ",
+        "\\ud83d\\ude80 ",
+        "\\/ ",
+        "日本語 ",
+    ];
+    let mut out = String::from("{\"log\": [");
+    for i in 0..strings {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push('"');
+        for _ in 0..200 {
+            out.push_str(rng.pick(&pieces));
+        }
+        out.push('"');
+    }
+    out.push_str("]}");
+    out
+}
+
+// Newline-delimited log records, parsed one line at a time.
+fn ndjson(lines: usize) -> String {
+    let mut rng = Rng(0x1095);
+    let levels = ["debug", "info", "info", "info", "warn", "error"];
+    let mut out = String::new();
+    for i in 0..lines {
+        writeln!(
+            out,
+            r#"{{"ts": "2024-01-01T00:{:02}:{:02}.{:03}Z", "level": "{}", "msg": "{} {} {}", "request_id": "{:016x}", "latency_ms": {}, "status": {}}}"#,
+            i / 60 % 60,
+            i % 60,
+            rng.below(1000),
+            rng.pick(&levels),
+            rng.pick(WORDS),
+            rng.pick(WORDS),
+            rng.pick(WORDS),
+            rng.below(u64::MAX),
+            rng.below(100_000) as f64 / 100.0,
+            [200, 200, 201, 404, 500][rng.below(5) as usize],
+        )
+        .unwrap();
+    }
+    out
+}
+
+// Visits every value and keeps nothing.
+#[derive(Default)]
+struct Events(Cell<usize>);
+impl<'a> Builder<'a> for Events {
+    type Value = ();
+    type Member = ();
+    type Object = ();
+
+    fn number(&self, _: Number) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn string(&self, _: &'a str) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn boolean(&self, _: bool) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn null(&self) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn array(&self, _: Vec<()>) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn member(&self, _: &'a str, _: ()) {}
+    fn object(&self, _: Vec<()>) {
+        self.0.set(self.0.get() + 1)
+    }
+    fn object_value(&self, _: ()) {}
+}
+
+struct Corpus {
+    name: &'static str,
+    // NDJSON corpora hold one document per line.
+    documents: Vec<String>,
+}
+impl Corpus {
+    fn single(name: &'static str, input: String) -> Self {
+        Self {
+            name,
+            documents: vec![input],
+        }
+    }
+    fn bytes(&self) -> usize {
+        self.documents.iter().map(|d| d.len()).sum()
+    }
+}
+
+struct Measurement {
+    corpus: &'static str,
+    path: &'static str,
+    bytes: usize,
+    documents: usize,
+    seconds_per_iteration: f64,
+    allocations: usize,
+    allocated: usize,
+}
+impl Measurement {
+    fn megabytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.seconds_per_iteration / 1e6
+    }
+    fn allocations_per_document(&self) -> f64 {
+        self.allocations as f64 / self.documents as f64
+    }
+}
+
+// Runs `f` over the corpus once to count allocations, then for `duration` to time it.
+fn measure(
+    corpus: &Corpus,
+    path: &'static str,
+    bytes: usize,
+    duration: Duration,
+    mut f: impl FnMut(&str),
+) -> Measurement {
+    let (allocations, allocated) = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED.load(Ordering::Relaxed),
+    );
+    for document in &corpus.documents {
+        f(document);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let allocated = ALLOCATED.load(Ordering::Relaxed) - allocated;
+    let mut iterations = 0u32;
+    let start = Instant::now();
+    while start.elapsed() < duration || iterations == 0 {
+        for document in &corpus.documents {
+            f(black_box(document));
+        }
+        iterations += 1;
+    }
+    Measurement {
+        corpus: corpus.name,
+        path,
+        bytes,
+        documents: corpus.documents.len(),
+        seconds_per_iteration: start.elapsed().as_secs_f64() / iterations as f64,
+        allocations,
+        allocated,
+    }
+}
+
+fn run(corpus: &Corpus, filter: &str, duration: Duration) -> Vec<Measurement> {
+    let options = ParseOptions::default();
+    let values: Vec<_> = corpus
+        .documents
+        .iter()
+        .map(|document| parse_value(document, &options).expect("corpora are valid JSON"))
+        .collect();
+    let printed = values.iter().map(|v| to_string(v).len()).sum();
+    let mut results = vec![];
+    let wanted =
+        |path: &str| filter.is_empty() || corpus.name.contains(filter) || path.contains(filter);
+    if wanted("dom") {
+        results.push(measure(corpus, "dom", corpus.bytes(), duration, |input| {
+            black_box(parse_value(input, &options).unwrap());
+        }));
+    }
+    if wanted("borrowed") {
+        results.push(measure(
+            corpus,
+            "borrowed",
+            corpus.bytes(),
+            duration,
+            |input| {
+                black_box(Document::parse(input).unwrap());
+            },
+        ));
+    }
+    if wanted("events") {
+        results.push(measure(
+            corpus,
+            "events",
+            corpus.bytes(),
+            duration,
+            |input| {
+                let events = Events::default();
+                parse_with(&events, input, &options).unwrap();
+                black_box(events.0.get());
+            },
+        ));
+    }
+    if wanted("serialize") {
+        // The closure is handed each document's text; it prints the matching value.
+        let mut next = 0;
+        results.push(measure(corpus, "serialize", printed, duration, |_| {
+            black_box(to_string(&values[next % values.len()]));
+            next += 1;
+        }));
+    }
+    results
+}
+
+fn to_json(results: &[Measurement]) -> JsonValue {
+    JsonValue::Array(
+        results
+            .iter()
+            .map(|m| {
+                json!({
+                    "corpus": (m.corpus),
+                    "path": (m.path),
+                    "bytes": (m.bytes as i64),
+                    "documents": (m.documents as i64),
+                    "seconds_per_iteration": (m.seconds_per_iteration),
+                    "megabytes_per_second": (m.megabytes_per_second()),
+                    "allocations": (m.allocations as i64),
+                    "allocated_bytes": (m.allocated as i64),
+                    "allocations_per_document": (m.allocations_per_document())
+                })
+            })
+            .collect(),
+    )
+}
+
+// Throughput of a previous run, by corpus and path.
+fn baseline(path: &Path) -> Vec<(String, String, f64)> {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    let results = parse_value(&text, &ParseOptions::default())
+        .unwrap_or_else(|e| panic!("{} is not JSON: {}", path.display(), e.message));
+    results
+        .iter()
+        .filter_map(|m| {
+            Some((
+                m.get("corpus")?.as_str()?.to_string(),
+                m.get("path")?.as_str()?.to_string(),
+                m.get("megabytes_per_second")?.as_number()?.as_f64(),
+            ))
+        })
+        .collect()
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (mut save, mut compare, mut filter) = (None, None, String::new());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = args.next(),
+            "--baseline" => compare = args.next(),
+            "--filter" => filter = args.next().unwrap_or_default(),
+            // Passed by `cargo bench`.
+            "--bench" => {}
+            other => filter = other.to_string(),
+        }
+    }
+    let millis = std::env::var("BENCH_MILLIS")
+        .ok()
+        .and_then(|millis| millis.parse().ok())
+        .unwrap_or(500);
+    let duration = Duration::from_millis(millis);
+
+    let corpora = vec![
+        Corpus::single("twitter", twitter(500)),
+        Corpus::single("floats", floats(400)),
+        Corpus::single("nested", nested(40)),
+        Corpus::single("escaped", escaped(300)),
+        Corpus {
+            name: "ndjson",
+            documents: ndjson(5000).lines().map(str::to_string).collect(),
+        },
+    ];
+    let baseline = compare.map(|path| baseline(Path::new(&path)));
+    println!(
+        "{:<10} {:<10} {:>10} {:>10} {:>12} {:>14}{}",
+        "corpus",
+        "path",
+        "KB",
+        "MB/s",
+        "allocs/doc",
+        "allocated KB",
+        if baseline.is_some() {
+            "     change"
+        } else {
+            ""
+        }
+    );
+    let mut results = vec![];
+    for corpus in &corpora {
+        for m in run(corpus, &filter, duration) {
+            let change = baseline.as_ref().and_then(|baseline| {
+                let (_, _, before) = baseline
+                    .iter()
+                    .find(|(c, p, _)| c == m.corpus && p == m.path)?;
+                Some(format!(
+                    " {:>+9.1}%",
+                    (m.megabytes_per_second() / before - 1.0) * 100.0
+                ))
+            });
+            println!(
+                "{:<10} {:<10} {:>10} {:>10.1} {:>12.1} {:>14}{}",
+                m.corpus,
+                m.path,
+                m.bytes / 1000,
+                m.megabytes_per_second(),
+                m.allocations_per_document(),
+                m.allocated / 1000,
+                change.unwrap_or_default()
+            );
+            results.push(m);
+        }
+    }
+    if let Some(path) = save {
+        let path = Path::new(&path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(path, to_string_pretty(&to_json(&results)) + "\n").unwrap();
+        println!("saved to {}", path.display());
+    }
+}
@@ -0,0 +1,280 @@
+use proc_macro::{Delimiter, Group, Span, TokenTree};
+
+use crate::parse::split_commas;
+use crate::Error;
+
+// One `key` or `key = "value"` entry of a `#[json(...)]` attribute.
+pub(crate) struct JsonAttr {
+    key: String,
+    span: Span,
+    value: Option<(String, Span)>,
+}
+
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    pub(crate) rename_all: Option<Case>,
+    pub(crate) tag: Option<String>,
+    pub(crate) content: Option<String>,
+    pub(crate) untagged: bool,
+    pub(crate) deny_unknown_fields: bool,
+    pub(crate) span: Option<Span>,
+}
+
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) default: Option<Default>,
+    pub(crate) skip: bool,
+    pub(crate) flatten: bool,
+    pub(crate) span: Option<Span>,
+}
+
+pub(crate) enum Default {
+    Trait,
+    Path(String),
+}
+
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    pub(crate) rename: Option<String>,
+    pub(crate) rename_all: Option<Case>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Case {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+impl Case {
+    fn parse(name: &str, span: Span) -> Result<Self, Error> {
+        Ok(match name {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "unknown case style `{}`, expected one of lowercase, UPPERCASE, \
+                         PascalCase, camelCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case, \
+                         SCREAMING-KEBAB-CASE",
+                        name
+                    ),
+                ))
+            }
+        })
+    }
+    // Works on both snake_case field names and PascalCase variant names.
+    pub(crate) fn apply(self, name: &str) -> String {
+        let words = words(name);
+        let capitalized = || {
+            words.iter().map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+        };
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Pascal => capitalized().collect(),
+            Self::Camel => {
+                let pascal: String = capitalized().collect();
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_lowercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+            Self::Snake => words.join("_"),
+            Self::ScreamingSnake => words.join("_").to_uppercase(),
+            Self::Kebab => words.join("-"),
+            Self::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(current.split_off(0));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            words.push(current.split_off(0));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+// Returns the entries of `#[json(...)]`, or nothing for any other attribute.
+pub(crate) fn json_attr(group: &Group) -> Vec<JsonAttr> {
+    let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+    let args = match tokens.as_slice() {
+        [TokenTree::Ident(ident), TokenTree::Group(args)]
+            if ident.to_string() == "json" && args.delimiter() == Delimiter::Parenthesis =>
+        {
+            args
+        }
+        [TokenTree::Ident(ident), ..] if ident.to_string() == "json" => {
+            return vec![JsonAttr {
+                key: String::new(),
+                span: ident.span(),
+                value: None,
+            }]
+        }
+        _ => return vec![],
+    };
+    let tokens: Vec<TokenTree> = args.stream().into_iter().collect();
+    split_commas(&tokens)
+        .into_iter()
+        .map(|entry| match entry.as_slice() {
+            [TokenTree::Ident(key)] => JsonAttr {
+                key: key.to_string(),
+                span: key.span(),
+                value: None,
+            },
+            [TokenTree::Ident(key), TokenTree::Punct(eq), TokenTree::Literal(value)]
+                if eq.as_char() == '=' =>
+            {
+                JsonAttr {
+                    key: key.to_string(),
+                    span: key.span(),
+                    value: unquote(&value.to_string()).map(|v| (v, value.span())),
+                }
+            }
+            _ => JsonAttr {
+                key: String::new(),
+                span: entry[0].span(),
+                value: None,
+            },
+        })
+        .collect()
+}
+
+fn unquote(literal: &str) -> Option<String> {
+    let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+    Some(inner.replace("\\\"", "\"").replace("\\\\", "\\"))
+}
+
+fn string_value(attr: &JsonAttr) -> Result<(String, Span), Error> {
+    attr.value
+        .clone()
+        .ok_or_else(|| Error::new(attr.span, format!("expected `{} = \"...\"`", attr.key)))
+}
+
+fn flag(attr: &JsonAttr) -> Result<bool, Error> {
+    match attr.value {
+        None => Ok(true),
+        Some((_, span)) => Err(Error::new(span, format!("`{}` takes no value", attr.key))),
+    }
+}
+
+fn unknown(attr: &JsonAttr, allowed: &str) -> Error {
+    if attr.key.is_empty() {
+        Error::new(
+            attr.span,
+            format!("malformed attribute, expected `#[json({})]`", allowed),
+        )
+    } else {
+        Error::new(
+            attr.span,
+            format!(
+                "unknown attribute `{}`, expected one of {}",
+                attr.key, allowed
+            ),
+        )
+    }
+}
+
+pub(crate) fn container(attrs: &[JsonAttr]) -> Result<ContainerAttrs, Error> {
+    let mut container = ContainerAttrs::default();
+    for attr in attrs {
+        container.span.get_or_insert(attr.span);
+        match attr.key.as_str() {
+            "rename_all" => {
+                let (name, span) = string_value(attr)?;
+                container.rename_all = Some(Case::parse(&name, span)?);
+            }
+            "tag" => container.tag = Some(string_value(attr)?.0),
+            "content" => container.content = Some(string_value(attr)?.0),
+            "untagged" => container.untagged = flag(attr)?,
+            "deny_unknown_fields" => container.deny_unknown_fields = flag(attr)?,
+            _ => {
+                return Err(unknown(
+                    attr,
+                    "rename_all, tag, content, untagged, deny_unknown_fields",
+                ))
+            }
+        }
+    }
+    Ok(container)
+}
+
+pub(crate) fn field(attrs: &[JsonAttr]) -> Result<FieldAttrs, Error> {
+    let mut field = FieldAttrs::default();
+    for attr in attrs {
+        field.span.get_or_insert(attr.span);
+        match attr.key.as_str() {
+            "rename" => field.rename = Some(string_value(attr)?.0),
+            "default" => {
+                field.default = Some(match &attr.value {
+                    Some((path, _)) => Default::Path(path.clone()),
+                    None => Default::Trait,
+                })
+            }
+            "skip" => field.skip = flag(attr)?,
+            "flatten" => field.flatten = flag(attr)?,
+            _ => return Err(unknown(attr, "rename, default, skip, flatten")),
+        }
+    }
+    Ok(field)
+}
+
+pub(crate) fn variant(attrs: &[JsonAttr]) -> Result<VariantAttrs, Error> {
+    let mut variant = VariantAttrs::default();
+    for attr in attrs {
+        match attr.key.as_str() {
+            "rename" => variant.rename = Some(string_value(attr)?.0),
+            "rename_all" => {
+                let (name, span) = string_value(attr)?;
+                variant.rename_all = Some(Case::parse(&name, span)?);
+            }
+            _ => return Err(unknown(attr, "rename, rename_all")),
+        }
+    }
+    Ok(variant)
+}
+
+pub(crate) fn has_json_attr(tokens: &[TokenTree]) -> Option<Span> {
+    tokens.windows(2).find_map(|pair| match pair {
+        [TokenTree::Punct(p), TokenTree::Group(group)] if p.as_char() == '#' => {
+            json_attr(group).first().map(|attr| attr.span)
+        }
+        _ => None,
+    })
+}
@@ -0,0 +1,629 @@
+use proc_macro::{Delimiter, Span, TokenTree};
+
+use crate::attr::{Case, Default};
+use crate::parse::{split_commas, to_string, Body, Field, Fields, Item, Variant};
+use crate::Error;
+
+const RESULT: &str = "::core::result::Result<Self, ::json_parser::FromJsonError>";
+const PRIVATE: &str = "::json_parser::__private";
+
+enum Tagging<'a> {
+    External,
+    Internal(&'a str),
+    Adjacent(&'a str, &'a str),
+    Untagged,
+}
+
+fn tagging(item: &Item) -> Result<Tagging<'_>, Error> {
+    let attrs = &item.attrs;
+    let span = attrs.span.unwrap_or_else(Span::call_site);
+    let is_enum = matches!(item.body, Body::Enum(_));
+    if !is_enum && (attrs.tag.is_some() || attrs.content.is_some() || attrs.untagged) {
+        return Err(Error::new(
+            span,
+            "`tag`, `content` and `untagged` only apply to enums",
+        ));
+    }
+    match (&attrs.tag, &attrs.content, attrs.untagged) {
+        (None, None, false) => Ok(Tagging::External),
+        (Some(tag), None, false) => Ok(Tagging::Internal(tag)),
+        (Some(tag), Some(content), false) => Ok(Tagging::Adjacent(tag, content)),
+        (None, None, true) => Ok(Tagging::Untagged),
+        (None, Some(_), false) => Err(Error::new(span, "`content` requires `tag`")),
+        _ => Err(Error::new(
+            span,
+            "`untagged` cannot be combined with `tag` or `content`",
+        )),
+    }
+}
+
+fn impl_header(item: &Item, trait_name: &str) -> String {
+    let generics = &item.generics;
+    let mut predicates = generics.where_clause.clone();
+    predicates.extend(
+        generics
+            .type_params
+            .iter()
+            .map(|param| format!("{}: ::json_parser::{}", param, trait_name)),
+    );
+    format!(
+        "impl<{}> ::json_parser::{} for {}<{}> where {}",
+        generics.params.join(", "),
+        trait_name,
+        item.name,
+        generics.args.join(", "),
+        predicates.join(", ")
+    )
+}
+
+fn unraw(name: &str) -> &str {
+    name.strip_prefix("r#").unwrap_or(name)
+}
+
+fn field_key(field: &Field, rename_all: Option<Case>) -> String {
+    match (&field.attrs.rename, rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(case)) => case.apply(unraw(&field.name)),
+        (None, None) => unraw(&field.name).to_string(),
+    }
+}
+
+fn variant_key(variant: &Variant, rename_all: Option<Case>) -> String {
+    match (&variant.attrs.rename, rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(case)) => case.apply(&variant.name),
+        (None, None) => variant.name.clone(),
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("{:?}", s)
+}
+
+fn key_list<'a>(keys: impl Iterator<Item = &'a String>) -> String {
+    format!(
+        "&[{}]",
+        keys.map(|key| quote(key)).collect::<Vec<_>>().join(", ")
+    )
+}
+
+// What values of `ty` convert to, when it's plain from its spelling that they don't
+// convert to an object or `null`. Other types, like `JsonValue` or the user's own, are
+// only known once converted.
+fn not_an_object(ty: &[TokenTree]) -> Option<&'static str> {
+    match ty {
+        [TokenTree::Punct(p), rest @ ..] if p.as_char() == '&' => {
+            let rest = match rest {
+                [TokenTree::Punct(q), TokenTree::Ident(_), rest @ ..] if q.as_char() == '\'' => {
+                    rest
+                }
+                _ => rest,
+            };
+            match rest {
+                [TokenTree::Ident(ident), rest @ ..] if ident.to_string() == "mut" => {
+                    not_an_object(rest)
+                }
+                _ => not_an_object(rest),
+            }
+        }
+        [TokenTree::Group(group)] => match group.delimiter() {
+            Delimiter::Bracket | Delimiter::Parenthesis => Some("an array"),
+            _ => None,
+        },
+        _ => {
+            let open = ty
+                .iter()
+                .position(|token| matches!(token, TokenTree::Punct(p) if p.as_char() == '<'));
+            let name = match ty[..open.unwrap_or(ty.len())].last() {
+                Some(TokenTree::Ident(ident)) => ident.to_string(),
+                _ => return None,
+            };
+            let arguments = match open {
+                Some(open) if ty.len() > open + 1 => split_commas(&ty[open + 1..ty.len() - 1]),
+                _ => vec![],
+            };
+            match name.as_str() {
+                "bool" => Some("a boolean"),
+                "char" | "str" | "String" => Some("a string"),
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+                | "u128" | "usize" | "f32" | "f64" => Some("a number"),
+                "Vec" | "VecDeque" | "LinkedList" | "HashSet" | "BTreeSet" | "BinaryHeap" => {
+                    Some("an array")
+                }
+                "Option" | "Box" | "Rc" | "Arc" => match arguments.as_slice() {
+                    [argument] => not_an_object(argument),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+    }
+}
+
+fn check_object(ty: &[TokenTree], span: Span, what: &str) -> Result<(), Error> {
+    match not_an_object(ty) {
+        Some(converted) => Err(Error::new(
+            span,
+            format!(
+                "{} must convert to an object, and `{}` converts to {}",
+                what,
+                to_string(ty),
+                converted
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn check_fields(fields: &[Field], deny_unknown_fields: bool) -> Result<(), Error> {
+    for field in fields {
+        let attrs = &field.attrs;
+        let span = attrs.span.unwrap_or_else(Span::call_site);
+        if attrs.flatten && (attrs.skip || attrs.rename.is_some() || attrs.default.is_some()) {
+            return Err(Error::new(
+                span,
+                "`flatten` cannot be combined with other attributes",
+            ));
+        }
+        if attrs.flatten && deny_unknown_fields {
+            return Err(Error::new(
+                span,
+                "`flatten` cannot be used with `deny_unknown_fields`",
+            ));
+        }
+        if attrs.flatten {
+            check_object(&field.ty, span, "a `#[json(flatten)]` field")?;
+        }
+    }
+    Ok(())
+}
+
+// An expression of type `Result<Self, FromJsonError>` decoding `ctor { .. }` from the
+// object in `value`. `extra_keys` are accepted even with `deny_unknown_fields`.
+fn decode_named(
+    ctor: &str,
+    fields: &[Field],
+    value: &str,
+    rename_all: Option<Case>,
+    deny_unknown_fields: bool,
+    extra_keys: &[String],
+) -> String {
+    let keys: Vec<String> = fields
+        .iter()
+        .filter(|field| !field.attrs.skip && !field.attrs.flatten)
+        .map(|field| field_key(field, rename_all))
+        .chain(extra_keys.iter().cloned())
+        .collect();
+    let mut body = format!("let object = {}::object({})?;", PRIVATE, value);
+    if deny_unknown_fields {
+        body += &format!(
+            "{}::deny_unknown_members(object, {})?;",
+            PRIVATE,
+            key_list(keys.iter())
+        );
+    }
+    let initializers = fields.iter().map(|field| {
+        let key = quote(&field_key(field, rename_all));
+        let default = match &field.attrs.default {
+            Some(Default::Path(path)) => path.clone(),
+            _ => "::core::default::Default::default".to_string(),
+        };
+        let expression = if field.attrs.skip {
+            format!("{}()", default)
+        } else if field.attrs.flatten {
+            format!(
+                "::json_parser::FromJson::from_json(&{}::remaining_members(object, {}))?",
+                PRIVATE,
+                key_list(keys.iter())
+            )
+        } else if field.attrs.default.is_some() {
+            format!("{}::member_or_else(object, {}, {})?", PRIVATE, key, default)
+        } else {
+            format!("{}::member(object, {})?", PRIVATE, key)
+        };
+        format!("{}: {}", field.name, expression)
+    });
+    body += &format!(
+        "::core::result::Result::Ok({} {{ {} }})",
+        ctor,
+        initializers.collect::<Vec<_>>().join(", ")
+    );
+    format!("(|| -> {} {{ {} }})()", RESULT, body)
+}
+
+fn decode_tuple(ctor: &str, count: usize, value: &str) -> String {
+    if count == 1 {
+        return format!(
+            "::json_parser::FromJson::from_json({}).map({})",
+            value, ctor
+        );
+    }
+    let elements = (0..count)
+        .map(|i| format!("{}::element(values, {})?", PRIVATE, i))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "(|| -> {} {{ let values = {}::array({}, {})?; ::core::result::Result::Ok({}({})) }})()",
+        RESULT, PRIVATE, value, count, ctor, elements
+    )
+}
+
+fn decode_unit(ctor: &str, value: &str) -> String {
+    format!(
+        "match {value} {{ ::json_parser::JsonValue::Null => ::core::result::Result::Ok({ctor}), \
+         _ => ::core::result::Result::Err(::json_parser::FromJsonError::invalid_type(\"null\", {value})) }}",
+        value = value,
+        ctor = ctor
+    )
+}
+
+fn decode_fields(
+    ctor: &str,
+    fields: &Fields,
+    value: &str,
+    rename_all: Option<Case>,
+    deny_unknown_fields: bool,
+) -> String {
+    match fields {
+        Fields::Named(fields) => {
+            decode_named(ctor, fields, value, rename_all, deny_unknown_fields, &[])
+        }
+        Fields::Tuple(types) => decode_tuple(ctor, types.len(), value),
+        Fields::Unit => decode_unit(ctor, value),
+    }
+}
+
+pub(crate) fn from_json(item: &Item) -> Result<String, Error> {
+    let tagging = tagging(item)?;
+    let deny = item.attrs.deny_unknown_fields;
+    let body = match &item.body {
+        Body::Struct(fields) => {
+            if let Fields::Named(named) = fields {
+                check_fields(named, deny)?;
+            }
+            decode_fields("Self", fields, "value", item.attrs.rename_all, deny)
+        }
+        Body::Enum(variants) => decode_enum(item, variants, &tagging)?,
+    };
+    Ok(format!(
+        "{} {{ fn from_json(value: &::json_parser::JsonValue) -> {} {{ {} }} }}",
+        impl_header(item, "FromJson"),
+        RESULT,
+        body
+    ))
+}
+
+fn decode_enum(item: &Item, variants: &[Variant], tagging: &Tagging) -> Result<String, Error> {
+    let deny = item.attrs.deny_unknown_fields;
+    let names: Vec<String> = variants
+        .iter()
+        .map(|variant| variant_key(variant, item.attrs.rename_all))
+        .collect();
+    for variant in variants {
+        if let Fields::Named(fields) = &variant.fields {
+            check_fields(fields, deny)?;
+        }
+    }
+    let unknown_variant = |tag: &str| {
+        format!(
+            "other => ::core::result::Result::Err(::json_parser::FromJsonError::unknown_variant(other, {}){})",
+            key_list(names.iter()),
+            tag
+        )
+    };
+    let arms = |decode: &dyn Fn(&Variant, &str) -> Result<String, Error>| {
+        variants
+            .iter()
+            .zip(&names)
+            .map(|(variant, name)| {
+                Ok(format!(
+                    "{} => {},",
+                    quote(name),
+                    decode(variant, &format!("Self::{}", variant.name))?
+                ))
+            })
+            .collect::<Result<String, Error>>()
+    };
+    Ok(match tagging {
+        Tagging::External => {
+            let unit_arms: String = variants
+                .iter()
+                .zip(&names)
+                .filter(|(variant, _)| matches!(variant.fields, Fields::Unit))
+                .map(|(variant, name)| {
+                    format!(
+                        "{} => ::core::result::Result::Ok(Self::{}),",
+                        quote(name),
+                        variant.name
+                    )
+                })
+                .collect();
+            let content_arms = arms(&|variant, ctor| {
+                Ok(match variant.fields {
+                    Fields::Unit => format!("::core::result::Result::Ok({})", ctor),
+                    _ => format!(
+                        "{}.map_err(|error| error.at_member(name))",
+                        decode_fields(
+                            ctor,
+                            &variant.fields,
+                            "content",
+                            variant.attrs.rename_all,
+                            deny
+                        )
+                    ),
+                })
+            })?;
+            format!(
+                "match value {{ \
+                 ::json_parser::JsonValue::String(name) => match name.as_str() {{ {} {} }}, \
+                 ::json_parser::JsonValue::Object(object) if object.len() == 1 => {{ \
+                     let member = object.iter().next().unwrap(); \
+                     let (name, content) = (member.identifier(), member.value()); \
+                     match name {{ {} {} }} \
+                 }} \
+                 _ => ::core::result::Result::Err(::json_parser::FromJsonError::invalid_type(\
+                     \"a string or an object with a single member\", value)), \
+                 }}",
+                unit_arms,
+                unknown_variant(""),
+                content_arms,
+                unknown_variant(".at_key(other)")
+            )
+        }
+        Tagging::Internal(tag) => {
+            let content_arms = arms(&|variant, ctor| match &variant.fields {
+                Fields::Unit => Ok(format!("::core::result::Result::Ok({})", ctor)),
+                Fields::Tuple(types) if types.len() == 1 => {
+                    check_newtype(variant, &types[0])?;
+                    Ok(decode_tuple(ctor, 1, "value"))
+                }
+                Fields::Tuple(_) => Err(Error::new(
+                    variant.span,
+                    "internally tagged enums cannot contain tuple variants",
+                )),
+                Fields::Named(fields) => Ok(decode_named(
+                    ctor,
+                    fields,
+                    "value",
+                    variant.attrs.rename_all,
+                    deny,
+                    &[tag.to_string()],
+                )),
+            })?;
+            format!(
+                "let object = {p}::object(value)?; \
+                 match {p}::tag(object, {tag})? {{ {} {} }}",
+                content_arms,
+                unknown_variant(&format!(".at_member({})", quote(tag))),
+                p = PRIVATE,
+                tag = quote(tag)
+            )
+        }
+        Tagging::Adjacent(tag, content) => {
+            let content_arms = arms(&|variant, ctor| {
+                Ok(match variant.fields {
+                    Fields::Unit => format!("::core::result::Result::Ok({})", ctor),
+                    _ => format!(
+                        "{{ let content = {}::content(object, {})?; {}.map_err(|error| error.at_member({})) }}",
+                        PRIVATE,
+                        quote(content),
+                        decode_fields(ctor, &variant.fields, "content", variant.attrs.rename_all, deny),
+                        quote(content)
+                    ),
+                })
+            })?;
+            let check = if deny {
+                format!(
+                    "{}::deny_unknown_members(object, &[{}, {}])?;",
+                    PRIVATE,
+                    quote(tag),
+                    quote(content)
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                "let object = {p}::object(value)?; {check} \
+                 match {p}::tag(object, {tag})? {{ {} {} }}",
+                content_arms,
+                unknown_variant(&format!(".at_member({})", quote(tag))),
+                p = PRIVATE,
+                check = check,
+                tag = quote(tag)
+            )
+        }
+        Tagging::Untagged => {
+            let attempts: String = variants
+                .iter()
+                .map(|variant| {
+                    format!(
+                        "if let ::core::result::Result::Ok(value) = {} {{ return ::core::result::Result::Ok(value); }}",
+                        decode_fields(
+                            &format!("Self::{}", variant.name),
+                            &variant.fields,
+                            "value",
+                            variant.attrs.rename_all,
+                            deny
+                        )
+                    )
+                })
+                .collect();
+            format!(
+                "{} ::core::result::Result::Err(::json_parser::FromJsonError::new({}))",
+                attempts,
+                quote(&format!(
+                    "data did not match any variant of untagged enum {}",
+                    item.name
+                ))
+            )
+        }
+    })
+}
+
+// The tag of an internally tagged enum goes next to the members of the content.
+fn check_newtype(variant: &Variant, ty: &[TokenTree]) -> Result<(), Error> {
+    check_object(
+        ty,
+        variant.span,
+        "the content of an internally tagged newtype variant",
+    )
+}
+
+// Statements pushing the members of named fields into `members`; `access` turns a
+// field name into a reference to its value.
+fn encode_members(
+    fields: &[Field],
+    rename_all: Option<Case>,
+    access: &dyn Fn(&str) -> String,
+) -> String {
+    fields
+        .iter()
+        .filter(|field| !field.attrs.skip)
+        .map(|field| {
+            let value = format!("::json_parser::ToJson::to_json({})", access(&field.name));
+            if field.attrs.flatten {
+                format!(
+                    "{}::flatten_into(&mut members, {}, {});",
+                    PRIVATE,
+                    quote(&field_key(field, rename_all)),
+                    value
+                )
+            } else {
+                format!(
+                    "members.push(::json_parser::Member::new({}, {}));",
+                    quote(&field_key(field, rename_all)),
+                    value
+                )
+            }
+        })
+        .collect()
+}
+
+fn object(statements: &str) -> String {
+    format!(
+        "{{ let mut members = {}::Vec::new(); {} \
+         ::json_parser::JsonValue::Object(::json_parser::JsonObject::new(members)) }}",
+        PRIVATE, statements
+    )
+}
+
+fn array(count: usize, access: &dyn Fn(usize) -> String) -> String {
+    format!(
+        "::json_parser::JsonValue::Array(::json_parser::__private::vec![{}])",
+        (0..count)
+            .map(|i| format!("::json_parser::ToJson::to_json({})", access(i)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn binding(name: &str) -> String {
+    format!("__{}", unraw(name))
+}
+
+pub(crate) fn to_json(item: &Item) -> Result<String, Error> {
+    let tagging = tagging(item)?;
+    let body = match &item.body {
+        Body::Struct(Fields::Named(fields)) => {
+            check_fields(fields, false)?;
+            object(&encode_members(fields, item.attrs.rename_all, &|name| {
+                format!("&self.{}", name)
+            }))
+        }
+        Body::Struct(Fields::Tuple(types)) if types.len() == 1 => {
+            "::json_parser::ToJson::to_json(&self.0)".to_string()
+        }
+        Body::Struct(Fields::Tuple(types)) => array(types.len(), &|i| format!("&self.{}", i)),
+        Body::Struct(Fields::Unit) => "::json_parser::JsonValue::Null".to_string(),
+        Body::Enum(variants) => {
+            let arms = variants
+                .iter()
+                .map(|variant| encode_variant(item, variant, &tagging))
+                .collect::<Result<String, Error>>()?;
+            format!("match self {{ {} }}", arms)
+        }
+    };
+    Ok(format!(
+        "{} {{ fn to_json(&self) -> ::json_parser::JsonValue {{ {} }} }}",
+        impl_header(item, "ToJson"),
+        body
+    ))
+}
+
+fn encode_variant(item: &Item, variant: &Variant, tagging: &Tagging) -> Result<String, Error> {
+    let name = quote(&variant_key(variant, item.attrs.rename_all));
+    let rename_all = variant.attrs.rename_all;
+    let (pattern, content) = match &variant.fields {
+        Fields::Unit => (String::new(), None),
+        Fields::Tuple(types) => (
+            format!(
+                "({})",
+                (0..types.len())
+                    .map(|i| format!("__{}", i))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Some(if types.len() == 1 {
+                "::json_parser::ToJson::to_json(__0)".to_string()
+            } else {
+                array(types.len(), &|i| format!("__{}", i))
+            }),
+        ),
+        Fields::Named(fields) => {
+            check_fields(fields, false)?;
+            (
+                format!(
+                    "{{ {} }}",
+                    fields
+                        .iter()
+                        .map(|field| format!("{}: {}", field.name, binding(&field.name)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                Some(object(&encode_members(fields, rename_all, &binding))),
+            )
+        }
+    };
+    let single = |key: &str, value: &str| {
+        format!(
+            "::json_parser::JsonValue::Object(::json_parser::JsonObject::new(::json_parser::__private::vec![\
+             ::json_parser::Member::new({}, {})]))",
+            key, value
+        )
+    };
+    let string = format!(
+        "::json_parser::JsonValue::String({}::ToString::to_string({}))",
+        PRIVATE, name
+    );
+    let value = match (tagging, &content) {
+        (Tagging::External, None) => string,
+        (Tagging::External, Some(content)) => single(&name, content),
+        (Tagging::Internal(tag), None) => single(&quote(tag), &string),
+        (Tagging::Internal(tag), Some(content)) => {
+            if let Fields::Tuple(types) = &variant.fields {
+                match types.as_slice() {
+                    [ty] => check_newtype(variant, ty)?,
+                    _ => {
+                        return Err(Error::new(
+                            variant.span,
+                            "internally tagged enums cannot contain tuple variants",
+                        ))
+                    }
+                }
+            }
+            format!("{}::tagged({}, {}, {})", PRIVATE, quote(tag), name, content)
+        }
+        (Tagging::Adjacent(tag, _), None) => single(&quote(tag), &string),
+        (Tagging::Adjacent(tag, content_key), Some(content)) => format!(
+            "::json_parser::JsonValue::Object(::json_parser::JsonObject::new(::json_parser::__private::vec![\
+             ::json_parser::Member::new({}, {}), ::json_parser::Member::new({}, {})]))",
+            quote(tag),
+            string,
+            quote(content_key),
+            content
+        ),
+        (Tagging::Untagged, None) => "::json_parser::JsonValue::Null".to_string(),
+        (Tagging::Untagged, Some(content)) => content.clone(),
+    };
+    Ok(format!("Self::{}{} => {},", variant.name, pattern, value))
+}
@@ -0,0 +1,52 @@
+extern crate proc_macro;
+
+mod attr;
+mod expand;
+mod parse;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    parse::item(input)
+        .and_then(|item| expand::from_json(&item))
+        .map(|code| code.parse().expect("generated FromJson impl is valid rust"))
+        .unwrap_or_else(|error| error.into_compile_error())
+}
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    parse::item(input)
+        .and_then(|item| expand::to_json(&item))
+        .map(|code| code.parse().expect("generated ToJson impl is valid rust"))
+        .unwrap_or_else(|error| error.into_compile_error())
+}
+
+pub(crate) struct Error {
+    span: Span,
+    message: String,
+}
+impl Error {
+    pub(crate) fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+    // Builds `compile_error!("...")` by hand so the error points at the offending tokens.
+    fn into_compile_error(self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+        let mut bang = Punct::new('!', Spacing::Alone);
+        bang.set_span(self.span);
+        let mut body = Group::new(Delimiter::Brace, TokenTree::from(message).into());
+        body.set_span(self.span);
+        vec![
+            TokenTree::from(Ident::new("compile_error", self.span)),
+            bang.into(),
+            body.into(),
+        ]
+        .into_iter()
+        .collect()
+    }
+}
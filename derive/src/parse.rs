@@ -0,0 +1,333 @@
+use proc_macro::{Delimiter, Span, TokenStream, TokenTree};
+
+use crate::attr::{self, ContainerAttrs, FieldAttrs, VariantAttrs};
+use crate::Error;
+
+pub(crate) struct Item {
+    pub(crate) name: String,
+    pub(crate) generics: Generics,
+    pub(crate) attrs: ContainerAttrs,
+    pub(crate) body: Body,
+}
+
+#[derive(Default)]
+pub(crate) struct Generics {
+    // `<...>` of the impl, without defaults.
+    pub(crate) params: Vec<String>,
+    // `<...>` applied to the type.
+    pub(crate) args: Vec<String>,
+    pub(crate) type_params: Vec<String>,
+    pub(crate) where_clause: Vec<String>,
+}
+
+pub(crate) enum Body {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+pub(crate) enum Fields {
+    Named(Vec<Field>),
+    // The type of each field.
+    Tuple(Vec<Vec<TokenTree>>),
+    Unit,
+}
+
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) ty: Vec<TokenTree>,
+    pub(crate) attrs: FieldAttrs,
+}
+
+pub(crate) struct Variant {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+    pub(crate) attrs: VariantAttrs,
+    pub(crate) fields: Fields,
+}
+
+pub(crate) fn item(input: TokenStream) -> Result<Item, Error> {
+    let mut tokens = input.into_iter().peekable();
+    let mut json_attrs = vec![];
+    let kind = loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.next() {
+                    json_attrs.extend(attr::json_attr(&group));
+                }
+            }
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+                if let Some(TokenTree::Group(g)) = tokens.peek() {
+                    if g.delimiter() == Delimiter::Parenthesis {
+                        tokens.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(ident)) => match ident.to_string().as_str() {
+                "struct" | "enum" => break ident,
+                "union" => return Err(Error::new(ident.span(), "unions are not supported")),
+                _ => return Err(Error::new(ident.span(), "expected a struct or an enum")),
+            },
+            Some(other) => return Err(Error::new(other.span(), "expected a struct or an enum")),
+            None => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "expected a struct or an enum",
+                ))
+            }
+        }
+    };
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        other => return Err(Error::new(span_of(other.as_ref()), "expected a type name")),
+    };
+    let mut rest: Vec<TokenTree> = tokens.collect();
+    let mut generics = Generics::default();
+    if matches!(rest.first(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        let close = closing_angle(&rest)
+            .ok_or_else(|| Error::new(rest[0].span(), "unclosed generic parameters"))?;
+        generics = parse_generics(&rest[1..close]);
+        rest.drain(..=close);
+    }
+    let attrs = attr::container(&json_attrs)?;
+    let body = if kind.to_string() == "struct" {
+        Body::Struct(struct_fields(&mut rest, &mut generics)?)
+    } else {
+        let body = take_where_clause(&mut rest, &mut generics);
+        match body {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                Body::Enum(variants(group.stream())?)
+            }
+            other => {
+                return Err(Error::new(
+                    span_of(other.as_ref()),
+                    "expected enum variants",
+                ))
+            }
+        }
+    };
+    Ok(Item {
+        name,
+        generics,
+        attrs,
+        body,
+    })
+}
+
+fn span_of(token: Option<&TokenTree>) -> Span {
+    token.map(TokenTree::span).unwrap_or_else(Span::call_site)
+}
+
+pub(crate) fn to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(p) if p.as_char() == c)
+}
+
+// Index of the `>` matching the `<` at index 0, ignoring the `>` of `->`.
+fn closing_angle(tokens: &[TokenTree]) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        if is_punct(token, '<') {
+            depth += 1;
+        } else if is_punct(token, '>') && !(i > 0 && is_punct(&tokens[i - 1], '-')) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+    }
+    None
+}
+
+// Splits on the commas that are not nested inside `<...>`; groups are already nested.
+pub(crate) fn split_commas(tokens: &[TokenTree]) -> Vec<Vec<TokenTree>> {
+    let mut segments = vec![vec![]];
+    let mut depth = 0i32;
+    for (i, token) in tokens.iter().enumerate() {
+        if is_punct(token, '<') {
+            depth += 1;
+        } else if is_punct(token, '>') && !(i > 0 && is_punct(&tokens[i - 1], '-')) {
+            depth -= 1;
+        } else if is_punct(token, ',') && depth == 0 {
+            segments.push(vec![]);
+            continue;
+        }
+        segments.last_mut().unwrap().push(token.clone());
+    }
+    segments.retain(|segment| !segment.is_empty());
+    segments
+}
+
+fn parse_generics(tokens: &[TokenTree]) -> Generics {
+    let mut generics = Generics::default();
+    for param in split_commas(tokens) {
+        let without_default = match param.iter().position(|t| is_punct(t, '=')) {
+            Some(i) => &param[..i],
+            None => &param[..],
+        };
+        generics.params.push(to_string(without_default));
+        match &param[0] {
+            TokenTree::Punct(p) if p.as_char() == '\'' => {
+                generics.args.push(to_string(&param[..2]));
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "const" => {
+                generics.args.push(param[1].to_string());
+            }
+            other => {
+                generics.args.push(other.to_string());
+                generics.type_params.push(other.to_string());
+            }
+        }
+    }
+    generics
+}
+
+// Removes a leading `where` clause, returning the token that ended it.
+fn take_where_clause(rest: &mut Vec<TokenTree>, generics: &mut Generics) -> Option<TokenTree> {
+    let mut tokens = std::mem::take(rest).into_iter();
+    let first = tokens.next();
+    let end = match &first {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "where" => {
+            let mut predicates = vec![];
+            let mut end = None;
+            for token in tokens.by_ref() {
+                match &token {
+                    TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => {
+                        end = Some(token);
+                        break;
+                    }
+                    TokenTree::Punct(p) if p.as_char() == ';' => {
+                        end = Some(token);
+                        break;
+                    }
+                    _ => predicates.push(token),
+                }
+            }
+            generics.where_clause.extend(
+                split_commas(&predicates)
+                    .iter()
+                    .map(|predicate| to_string(predicate)),
+            );
+            end
+        }
+        _ => first,
+    };
+    rest.extend(tokens);
+    end
+}
+
+fn struct_fields(rest: &mut Vec<TokenTree>, generics: &mut Generics) -> Result<Fields, Error> {
+    if let Some(TokenTree::Group(group)) = rest.first() {
+        if group.delimiter() == Delimiter::Parenthesis {
+            let fields = tuple_fields(group.stream())?;
+            rest.remove(0);
+            take_where_clause(rest, generics);
+            return Ok(fields);
+        }
+    }
+    match take_where_clause(rest, generics) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            named_fields(group.stream())
+        }
+        Some(TokenTree::Punct(p)) if p.as_char() == ';' => Ok(Fields::Unit),
+        other => Err(Error::new(
+            span_of(other.as_ref()),
+            "expected struct fields",
+        )),
+    }
+}
+
+fn named_fields(stream: TokenStream) -> Result<Fields, Error> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut fields = vec![];
+    for segment in split_commas(&tokens) {
+        let (json_attrs, rest) = leading_attrs(&segment);
+        let name = rest
+            .iter()
+            .find_map(|token| match token {
+                TokenTree::Ident(ident) if ident.to_string() != "pub" => Some(ident),
+                _ => None,
+            })
+            .ok_or_else(|| Error::new(segment[0].span(), "expected a field name"))?;
+        let colon = rest
+            .iter()
+            .position(|token| is_punct(token, ':'))
+            .ok_or_else(|| Error::new(name.span(), "expected a field type"))?;
+        fields.push(Field {
+            name: name.to_string(),
+            ty: rest[colon + 1..].to_vec(),
+            attrs: attr::field(&json_attrs)?,
+        });
+    }
+    Ok(Fields::Named(fields))
+}
+
+fn tuple_fields(stream: TokenStream) -> Result<Fields, Error> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let fields = split_commas(&tokens);
+    if let Some(span) = fields.iter().find_map(|field| attr::has_json_attr(field)) {
+        return Err(Error::new(
+            span,
+            "json attributes are only supported on named fields",
+        ));
+    }
+    let types = fields.iter().map(|field| {
+        let (_, rest) = leading_attrs(field);
+        // Skips the visibility, `pub` or `pub(...)`.
+        let start = match rest {
+            [TokenTree::Ident(ident), TokenTree::Group(group), ..]
+                if ident.to_string() == "pub" && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                2
+            }
+            [TokenTree::Ident(ident), ..] if ident.to_string() == "pub" => 1,
+            _ => 0,
+        };
+        rest[start..].to_vec()
+    });
+    Ok(Fields::Tuple(types.collect()))
+}
+
+fn variants(stream: TokenStream) -> Result<Vec<Variant>, Error> {
+    let tokens: Vec<TokenTree> = stream.into_iter().collect();
+    let mut variants = vec![];
+    for segment in split_commas(&tokens) {
+        let (json_attrs, rest) = leading_attrs(&segment);
+        let ident = match rest.first() {
+            Some(TokenTree::Ident(ident)) => ident,
+            other => return Err(Error::new(span_of(other), "expected a variant name")),
+        };
+        let fields = match rest.get(1) {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+                named_fields(group.stream())?
+            }
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+                tuple_fields(group.stream())?
+            }
+            _ => Fields::Unit,
+        };
+        variants.push(Variant {
+            name: ident.to_string(),
+            span: ident.span(),
+            attrs: attr::variant(&json_attrs)?,
+            fields,
+        });
+    }
+    Ok(variants)
+}
+
+// Splits the `#[...]` attributes off the front of a field or variant, keeping the
+// contents of the `#[json(...)]` ones.
+fn leading_attrs(tokens: &[TokenTree]) -> (Vec<attr::JsonAttr>, &[TokenTree]) {
+    let mut json_attrs = vec![];
+    let mut i = 0;
+    while i + 1 < tokens.len() && is_punct(&tokens[i], '#') {
+        if let TokenTree::Group(group) = &tokens[i + 1] {
+            json_attrs.extend(attr::json_attr(group));
+        }
+        i += 2;
+    }
+    (json_attrs, &tokens[i..])
+}
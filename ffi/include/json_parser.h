@@ -0,0 +1,105 @@
+/*
+ * The json parser for C, implemented by libjson_parser_ffi.
+ *
+ * json_parse returns a document, which owns every value in it: the values, keys and
+ * strings read from it are only valid until the document is freed. Documents, errors
+ * and serialized strings are owned by the caller and freed with the matching _free
+ * function. Every function accepts NULL handles and fails on them, returning NULL,
+ * false or 0.
+ *
+ * Strings are UTF-8 with a length, and are not NUL-terminated: json strings may hold
+ * NULs. Error messages and serialized text are NUL-terminated, and hold no other NUL.
+ */
+#ifndef JSON_PARSER_H
+#define JSON_PARSER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct json_document json_document;
+typedef struct json_error json_error;
+typedef struct json_value json_value;
+
+typedef enum json_type {
+    /* A NULL handle. */
+    JSON_NONE,
+    JSON_NULL,
+    JSON_BOOL,
+    JSON_NUMBER,
+    JSON_STRING,
+    JSON_ARRAY,
+    JSON_OBJECT,
+} json_type;
+
+/* Flags for json_parse, combined with |. */
+/* Reject invalid escapes, raw control characters in strings, non-ASCII whitespace
+ * and numbers with leading zeros, which RFC 8259 does not allow. */
+#define JSON_STRICT 1u
+/* Accept JSON5: comments, trailing commas, unquoted keys, single quotes and so on. */
+#define JSON_JSON5 2u
+/* Keep numbers as written rather than rounded to a double, for json_value_int and
+ * json_serialize. */
+#define JSON_EXACT_NUMBERS 4u
+/* Replace malformed UTF-8, UTF-16 or UTF-32 with U+FFFD instead of failing. */
+#define JSON_LOSSY_DECODING 8u
+
+#define JSON_MAX_DEPTH 128
+
+/*
+ * Parses the len bytes at input, as UTF-8, UTF-16 or UTF-32 with or without a byte
+ * order mark. Any json value may be at the top level, and arrays and objects may nest
+ * JSON_MAX_DEPTH deep. Returns NULL on failure, and sets *error, when error is not
+ * NULL, to the error or to NULL on success.
+ */
+json_document *json_parse(const char *input, size_t len, uint32_t flags, json_error **error);
+const json_value *json_document_root(const json_document *document);
+void json_document_free(json_document *document);
+
+/* Lines and columns start at 1, and columns count characters. The offset is in bytes
+ * of the input decoded to UTF-8, without its byte order mark. Errors about the call
+ * itself, like a NULL input, are at line and column 0. */
+const char *json_error_message(const json_error *error);
+size_t json_error_line(const json_error *error);
+size_t json_error_column(const json_error *error);
+size_t json_error_offset(const json_error *error);
+void json_error_free(json_error *error);
+
+json_type json_value_type(const json_value *value);
+/* The number of elements or members; 0 for other values. */
+size_t json_value_len(const json_value *value);
+/* The value of the first member named key in an object. */
+const json_value *json_value_get(const json_value *value, const char *key, size_t key_len);
+/* The element at index in an array. */
+const json_value *json_value_at(const json_value *value, size_t index);
+/* The value an RFC 6901 JSON Pointer like "/records/0/name" points at. */
+const json_value *json_value_pointer(const json_value *value, const char *pointer,
+                                     size_t pointer_len);
+/* The value of the member at index in an object, setting *key and *key_len to its
+ * name when they are not NULL. */
+const json_value *json_value_member(const json_value *value, size_t index, const char **key,
+                                    size_t *key_len);
+
+/* Scalars are read into *out, returning false when the value isn't of the right type.
+ * json_value_int also fails on numbers with a fraction or outside int64_t, while
+ * json_value_double rounds. */
+bool json_value_bool(const json_value *value, bool *out);
+bool json_value_int(const json_value *value, int64_t *out);
+bool json_value_double(const json_value *value, double *out);
+/* The unescaped text of a string, setting *len to its length. */
+const char *json_value_string(const json_value *value, size_t *len);
+
+/* The value as json text, minified or indented by two spaces, setting *len to its
+ * length when len is not NULL. Free it with json_string_free. */
+char *json_serialize(const json_value *value, bool pretty, size_t *len);
+void json_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
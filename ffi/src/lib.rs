@@ -0,0 +1,336 @@
+// The parser for C, as declared in `include/json_parser.h`, which also says what each
+// function expects of its arguments. Documents and errors are handles the caller owns
+// until freeing them; values are borrowed from their document. Null handles are
+// accepted everywhere and make functions fail, and no panic unwinds into C: a function
+// that panics returns as it does on failure.
+#![allow(clippy::missing_safety_doc)]
+
+use std::convert::TryFrom;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::str;
+
+use json_parser::serialize::{to_string, to_string_pretty};
+use json_parser::{parse_bytes, Diagnostic, JsonValue, ParseOptions};
+
+// `json_document`: a parsed value and everything in it.
+pub struct Document {
+    root: JsonValue,
+}
+
+// `json_error`: why `json_parse` failed, and where in the input.
+pub struct Error {
+    message: CString,
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+impl Error {
+    // An error about the call rather than the input, located nowhere.
+    fn new(message: &str) -> Self {
+        Self {
+            message: c_string(message.to_string()),
+            line: 0,
+            column: 0,
+            offset: 0,
+        }
+    }
+}
+impl From<Diagnostic> for Error {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            message: c_string(diagnostic.message),
+            line: diagnostic.start.line,
+            column: diagnostic.start.column,
+            offset: diagnostic.start.offset,
+        }
+    }
+}
+
+// `json_parse` flags.
+pub const JSON_STRICT: u32 = 1;
+pub const JSON_JSON5: u32 = 2;
+pub const JSON_EXACT_NUMBERS: u32 = 4;
+pub const JSON_LOSSY_DECODING: u32 = 8;
+
+// Deeper input would overflow the stack, which aborts rather than unwinds.
+pub const JSON_MAX_DEPTH: usize = json_parser::DEFAULT_MAX_DEPTH;
+
+// `json_type`, with `None` for a null handle.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    None,
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+// Runs `f`, or returns `failed` if it panics.
+fn guard<T>(failed: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(failed)
+}
+
+// C strings end at their first NUL, which messages quoting the input may hold.
+fn c_string(s: String) -> CString {
+    CString::new(s.replace('\0', "\\u0000")).expect("NULs are escaped")
+}
+
+unsafe fn bytes<'a>(ptr: *const c_char, len: usize) -> Option<&'a [u8]> {
+    match ptr.is_null() {
+        true if len == 0 => Some(&[]),
+        true => None,
+        false => Some(slice::from_raw_parts(ptr as *const u8, len)),
+    }
+}
+
+unsafe fn text<'a>(ptr: *const c_char, len: usize) -> Option<&'a str> {
+    str::from_utf8(bytes(ptr, len)?).ok()
+}
+
+fn handle(value: Option<&JsonValue>) -> *const JsonValue {
+    value.map_or(ptr::null(), |value| value)
+}
+
+unsafe fn write<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        *out = value;
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_parse(
+    input: *const c_char,
+    len: usize,
+    flags: u32,
+    error: *mut *mut Error,
+) -> *mut Document {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let input = bytes(input, len).ok_or_else(|| Error::new("The input is null"))?;
+        let options = ParseOptions {
+            strict: flags & JSON_STRICT != 0,
+            json5: flags & JSON_JSON5 != 0,
+            exact_numbers: flags & JSON_EXACT_NUMBERS != 0,
+            lossy_decoding: flags & JSON_LOSSY_DECODING != 0,
+            ..ParseOptions::default()
+        };
+        Ok(parse_bytes(input, &options)?)
+    }))
+    .unwrap_or_else(|_| Err(Error::new("The parser panicked")));
+    match result {
+        Ok(root) => {
+            write(error, ptr::null_mut());
+            Box::into_raw(Box::new(Document { root }))
+        }
+        Err(e) => {
+            write(error, Box::into_raw(Box::new(e)));
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_document_root(document: *const Document) -> *const JsonValue {
+    handle(document.as_ref().map(|document| &document.root))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_document_free(document: *mut Document) {
+    if !document.is_null() {
+        guard((), || drop(Box::from_raw(document)));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_error_message(error: *const Error) -> *const c_char {
+    error
+        .as_ref()
+        .map_or(ptr::null(), |error| error.message.as_ptr())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_error_line(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.line)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_error_column(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.column)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_error_offset(error: *const Error) -> usize {
+    error.as_ref().map_or(0, |error| error.offset)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_error_free(error: *mut Error) {
+    if !error.is_null() {
+        guard((), || drop(Box::from_raw(error)));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_type(value: *const JsonValue) -> Type {
+    match value.as_ref() {
+        None => Type::None,
+        Some(JsonValue::Null) => Type::Null,
+        Some(JsonValue::True) | Some(JsonValue::False) => Type::Bool,
+        Some(JsonValue::Number(_)) => Type::Number,
+        Some(JsonValue::String(_)) => Type::String,
+        Some(JsonValue::Array(_)) => Type::Array,
+        Some(JsonValue::Object(_)) => Type::Object,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_len(value: *const JsonValue) -> usize {
+    value.as_ref().map_or(0, JsonValue::len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_get(
+    value: *const JsonValue,
+    key: *const c_char,
+    key_len: usize,
+) -> *const JsonValue {
+    guard(ptr::null(), || {
+        handle(
+            value
+                .as_ref()
+                .zip(text(key, key_len))
+                .and_then(|(value, key)| value.get(key)),
+        )
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_at(value: *const JsonValue, index: usize) -> *const JsonValue {
+    handle(value.as_ref().and_then(|value| value.get_index(index)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_pointer(
+    value: *const JsonValue,
+    pointer: *const c_char,
+    pointer_len: usize,
+) -> *const JsonValue {
+    guard(ptr::null(), || {
+        let pointer = text(pointer, pointer_len);
+        handle(
+            value
+                .as_ref()
+                .zip(pointer)
+                .and_then(|(value, pointer)| value.pointer(pointer)),
+        )
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_member(
+    value: *const JsonValue,
+    index: usize,
+    key: *mut *const c_char,
+    key_len: *mut usize,
+) -> *const JsonValue {
+    let member = value.as_ref().and_then(|value| value.members().nth(index));
+    let (name, len) = member.map_or((ptr::null(), 0), |member| {
+        let name = member.identifier();
+        (name.as_ptr() as *const c_char, name.len())
+    });
+    write(key, name);
+    write(key_len, len);
+    handle(member.map(|member| member.value()))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_bool(value: *const JsonValue, out: *mut bool) -> bool {
+    match value.as_ref().and_then(JsonValue::as_bool) {
+        Some(b) => {
+            write(out, b);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_int(value: *const JsonValue, out: *mut i64) -> bool {
+    let n = value.as_ref().and_then(JsonValue::as_number);
+    match guard(None, || n.and_then(|n| i64::try_from(n).ok())) {
+        Some(n) => {
+            write(out, n);
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_double(value: *const JsonValue, out: *mut f64) -> bool {
+    match value.as_ref().and_then(JsonValue::as_number) {
+        Some(n) => {
+            write(out, guard(f64::NAN, || n.as_f64()));
+            true
+        }
+        None => false,
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_value_string(
+    value: *const JsonValue,
+    len: *mut usize,
+) -> *const c_char {
+    match value.as_ref().and_then(JsonValue::as_str) {
+        Some(s) => {
+            write(len, s.len());
+            s.as_ptr() as *const c_char
+        }
+        None => {
+            write(len, 0);
+            ptr::null()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_serialize(
+    value: *const JsonValue,
+    pretty: bool,
+    len: *mut usize,
+) -> *mut c_char {
+    let text = value.as_ref().and_then(|value| {
+        guard(None, || {
+            let text = match pretty {
+                true => to_string_pretty(value),
+                false => to_string(value),
+            };
+            // Serialized json escapes NULs, so it holds none.
+            CString::new(text).ok()
+        })
+    });
+    match text {
+        Some(text) => {
+            write(len, text.as_bytes().len());
+            text.into_raw()
+        }
+        None => {
+            write(len, 0);
+            ptr::null_mut()
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn json_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        guard((), || drop(CString::from_raw(s)));
+    }
+}
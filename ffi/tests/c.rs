@@ -0,0 +1,45 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Compiles `c/api.c` against the header and the library cargo built next to this test,
+// with the system's C compiler, and runs it.
+#[test]
+fn c_api() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // `cargo test` builds the library next to the tests, in `target/<profile>/deps`,
+    // and only copies it up to `target/<profile>` on `cargo build`.
+    let exe = env::current_exe().unwrap();
+    let lib_dir = exe.parent().unwrap();
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("c_api");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(&cc)
+        .arg("-std=c99")
+        .args(["-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests/c/api.c"))
+        .arg("-o")
+        .arg(&binary)
+        .arg("-L")
+        .arg(lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-ljson_parser_ffi")
+        .status();
+    match status {
+        Ok(status) => assert!(status.success(), "{} failed to build c/api.c", cc),
+        // No C compiler to build it with.
+        Err(_) => return,
+    }
+    // Cargo points `LD_LIBRARY_PATH` at `target/<profile>` too, whose copy of the
+    // library may be stale, and which would be searched before the `rpath`.
+    let output = Command::new(&binary)
+        .env_remove("LD_LIBRARY_PATH")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,173 @@
+/* Exercises json_parser.h from C; built and run by tests/c.rs. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "json_parser.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                                                 \
+    do {                                                                                 \
+        if (!(condition)) {                                                              \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                                                  \
+        }                                                                                \
+    } while (0)
+
+static json_document *parse(const char *input, uint32_t flags, json_error **error) {
+    return json_parse(input, strlen(input), flags, error);
+}
+
+static const json_value *get(const json_value *value, const char *key) {
+    return json_value_get(value, key, strlen(key));
+}
+
+static const json_value *pointer(const json_value *value, const char *pointer) {
+    return json_value_pointer(value, pointer, strlen(pointer));
+}
+
+static void navigation(void) {
+    json_error *error = (json_error *)1;
+    json_document *document =
+        parse("{\"name\": \"caf\\u00e9\", \"list\": [1, 2.5, null, true], \"nul\": \"a\\u0000b\","
+              " \"a/b\": {\"~\": -7}}",
+              0, &error);
+    CHECK(document != NULL);
+    CHECK(error == NULL);
+    const json_value *root = json_document_root(document);
+    CHECK(json_value_type(root) == JSON_OBJECT);
+    CHECK(json_value_len(root) == 4);
+
+    size_t len = 0;
+    const char *name = json_value_string(get(root, "name"), &len);
+    CHECK(len == 5 && memcmp(name, "caf\xc3\xa9", 5) == 0);
+    const char *nul = json_value_string(get(root, "nul"), &len);
+    CHECK(len == 3 && memcmp(nul, "a\0b", 3) == 0);
+
+    const json_value *list = get(root, "list");
+    CHECK(json_value_type(list) == JSON_ARRAY);
+    CHECK(json_value_len(list) == 4);
+    CHECK(json_value_at(list, 4) == NULL);
+    CHECK(json_value_type(json_value_at(list, 2)) == JSON_NULL);
+    CHECK(json_value_at(list, 1) == pointer(root, "/list/1"));
+    CHECK(get(root, "missing") == NULL);
+    CHECK(get(list, "name") == NULL);
+
+    int64_t i = 0;
+    double d = 0;
+    bool b = false;
+    CHECK(json_value_int(json_value_at(list, 0), &i) && i == 1);
+    CHECK(!json_value_int(json_value_at(list, 1), &i));
+    CHECK(json_value_double(json_value_at(list, 1), &d) && d == 2.5);
+    CHECK(!json_value_double(json_value_at(list, 3), &d));
+    CHECK(json_value_bool(json_value_at(list, 3), &b) && b);
+    CHECK(!json_value_bool(json_value_at(list, 0), &b));
+    CHECK(json_value_string(json_value_at(list, 0), &len) == NULL && len == 0);
+    CHECK(json_value_int(pointer(root, "/a~1b/~0"), &i) && i == -7);
+    CHECK(pointer(root, "list") == NULL);
+
+    const char *key = NULL;
+    const json_value *member = json_value_member(root, 1, &key, &len);
+    CHECK(member == list);
+    CHECK(len == 4 && memcmp(key, "list", 4) == 0);
+    CHECK(json_value_member(root, 4, &key, &len) == NULL && key == NULL && len == 0);
+
+    /* Keys that aren't UTF-8 match nothing. */
+    CHECK(json_value_get(root, "\xff", 1) == NULL);
+    json_document_free(document);
+}
+
+static void serialization(void) {
+    json_document *document = parse("[1, {\"a\": \"\\u0000\"}, 18446744073709551616]",
+                                    JSON_EXACT_NUMBERS, NULL);
+    const json_value *root = json_document_root(document);
+    size_t len = 0;
+    char *text = json_serialize(root, false, &len);
+    CHECK(text != NULL);
+    CHECK(strcmp(text, "[1,{\"a\":\"\\u0000\"},18446744073709551616]") == 0);
+    CHECK(len == strlen(text));
+    json_string_free(text);
+
+    int64_t i = 0;
+    CHECK(!json_value_int(json_value_at(root, 2), &i));
+
+    text = json_serialize(json_value_at(root, 1), true, NULL);
+    CHECK(strcmp(text, "{\n  \"a\": \"\\u0000\"\n}") == 0);
+    json_string_free(text);
+    json_document_free(document);
+}
+
+/* `depth` arrays, each in the one before. */
+static json_document *nested(size_t depth, json_error **error) {
+    char text[2 * (JSON_MAX_DEPTH + 1)];
+    memset(text, '[', depth);
+    memset(text + depth, ']', depth);
+    return json_parse(text, 2 * depth, 0, error);
+}
+
+static void errors(void) {
+    json_error *error = NULL;
+    CHECK(parse("{\n  \"a\": 1,\n  \"b\" 2\n}", 0, &error) == NULL);
+    CHECK(error != NULL);
+    CHECK(strstr(json_error_message(error), "Expected `:`") != NULL);
+    CHECK(json_error_line(error) == 3);
+    CHECK(json_error_column(error) == 7);
+    CHECK(json_error_offset(error) == 18);
+    json_error_free(error);
+
+    CHECK(parse("[1, 2,]", 0, NULL) == NULL);
+    json_document *document = parse("[1, 2,] // JSON5", JSON_JSON5, &error);
+    CHECK(document != NULL && error == NULL);
+    json_document_free(document);
+
+    CHECK(parse("[\"\\q\"]", JSON_STRICT, NULL) == NULL);
+    CHECK(json_parse("[\"\xff\"]", 5, 0, NULL) == NULL);
+    document = json_parse("[\"\xff\"]", 5, JSON_LOSSY_DECODING, NULL);
+    size_t len = 0;
+    const char *s = json_value_string(json_value_at(json_document_root(document), 0), &len);
+    CHECK(len == 3 && memcmp(s, "\xef\xbf\xbd", 3) == 0);
+    json_document_free(document);
+
+    document = nested(JSON_MAX_DEPTH, NULL);
+    CHECK(document != NULL);
+    json_document_free(document);
+    CHECK(nested(JSON_MAX_DEPTH + 1, &error) == NULL);
+    CHECK(strstr(json_error_message(error), "depth") != NULL);
+    json_error_free(error);
+
+    CHECK(json_parse(NULL, 1, 0, &error) == NULL);
+    CHECK(json_error_line(error) == 0 && strlen(json_error_message(error)) > 0);
+    json_error_free(error);
+}
+
+static void null_handles(void) {
+    size_t len = 1;
+    const char *key = "";
+    int64_t i = 0;
+    CHECK(json_document_root(NULL) == NULL);
+    CHECK(json_value_type(NULL) == JSON_NONE);
+    CHECK(json_value_len(NULL) == 0);
+    CHECK(get(NULL, "a") == NULL);
+    CHECK(json_value_at(NULL, 0) == NULL);
+    CHECK(pointer(NULL, "") == NULL);
+    CHECK(json_value_member(NULL, 0, &key, &len) == NULL && key == NULL && len == 0);
+    CHECK(!json_value_int(NULL, &i));
+    CHECK(json_serialize(NULL, false, NULL) == NULL);
+    CHECK(json_error_message(NULL) == NULL);
+    json_document_free(NULL);
+    json_error_free(NULL);
+    json_string_free(NULL);
+}
+
+int main(void) {
+    navigation();
+    serialization();
+    errors();
+    null_handles();
+    if (failures > 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{JsonObject, JsonValue, Member, Number};
+
+// Random `JsonValue` trees for property tests, and shrinking of the ones that fail a
+// property down to a minimal counterexample:
+//
+//     arbitrary::check(&Generator::default(), 1000, |value| match roundtrips(value) {
+//         true => Ok(()),
+//         false => Err("does not round-trip".to_string()),
+//     });
+
+// xorshift64*: deterministic for a seed, and good enough to pick test cases.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Zero is the one state xorshift never leaves.
+        Self(seed.max(1))
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+    // Uniform in `0..n`, or 0 when `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        match n {
+            0 => 0,
+            _ => (self.next_u64() % n as u64) as usize,
+        }
+    }
+}
+
+// The chars strings are drawn from, as ranges of code points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alphabet(pub Vec<RangeInclusive<char>>);
+impl Alphabet {
+    pub fn ascii() -> Self {
+        Self(vec![' '..='~'])
+    }
+    // The chars that have to be escaped: controls, quotes and backslashes.
+    pub fn escapes() -> Self {
+        Self(vec![
+            '\0'..='\u{1f}',
+            '"'..='"',
+            '\\'..='\\',
+            '\u{7f}'..='\u{7f}',
+        ])
+    }
+    // Beyond ASCII: the rest of the basic multilingual plane, which holds U+2028 and
+    // U+2029, and the astral planes, which UTF-16 escapes as surrogate pairs.
+    pub fn unicode() -> Self {
+        Self(vec![
+            '\u{80}'..='\u{7ff}',
+            '\u{800}'..='\u{d7ff}',
+            '\u{e000}'..='\u{ffff}',
+            '\u{10000}'..='\u{10ffff}',
+        ])
+    }
+    pub fn all() -> Self {
+        let mut ranges = Self::ascii().0;
+        ranges.extend(Self::escapes().0);
+        ranges.extend(Self::unicode().0);
+        Self(ranges)
+    }
+    fn sample(&self, rng: &mut Rng) -> char {
+        // Ranges are picked evenly whatever their size, so that the few escapes are
+        // as frequent as the many astral chars.
+        let range = &self.0[rng.below(self.0.len())];
+        let (start, end) = (*range.start() as u32, *range.end() as u32);
+        let code = start + rng.below((end - start + 1) as usize) as u32;
+        char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+// Which numbers to generate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Numbers {
+    pub integers: RangeInclusive<isize>,
+    // Also generate finite floats: small decimals, and any bit pattern.
+    pub floats: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generator {
+    // Containers nest at most this deep; 0 only generates scalars.
+    pub max_depth: usize,
+    // Arrays and objects hold at most this many values.
+    pub max_width: usize,
+    pub max_string_len: usize,
+    pub alphabet: Alphabet,
+    pub numbers: Numbers,
+}
+impl Default for Generator {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_width: 6,
+            max_string_len: 12,
+            alphabet: Alphabet::all(),
+            numbers: Numbers {
+                integers: isize::MIN..=isize::MAX,
+                floats: true,
+            },
+        }
+    }
+}
+impl Generator {
+    pub fn value(&self, rng: &mut Rng) -> JsonValue {
+        self.value_at(rng, self.max_depth)
+    }
+    // An object, as the document-level parsers need.
+    pub fn object(&self, rng: &mut Rng) -> JsonObject {
+        self.object_at(rng, self.max_depth.max(1))
+    }
+    pub fn string(&self, rng: &mut Rng) -> String {
+        let len = rng.below(self.max_string_len + 1);
+        (0..len).map(|_| self.alphabet.sample(rng)).collect()
+    }
+    pub fn number(&self, rng: &mut Rng) -> Number {
+        let (start, end) = (*self.numbers.integers.start(), *self.numbers.integers.end());
+        match rng.below(4) {
+            0 | 1 if self.numbers.floats => match rng.below(2) {
+                // Short decimals like `-12.5`, which print without an exponent.
+                0 => Number::Float((rng.below(200_001) as f64 - 100_000.0) / 100.0),
+                _ => loop {
+                    let f = f64::from_bits(rng.next_u64());
+                    if f.is_finite() {
+                        break Number::Float(f);
+                    }
+                },
+            },
+            // The bounds themselves, and small integers, are where bugs hide.
+            0 => Number::Integer(start),
+            1 => Number::Integer(end),
+            2 if start <= 0 && end >= 0 => {
+                let small = (rng.below(201) as isize - 100).clamp(start, end);
+                Number::Integer(small)
+            }
+            _ => {
+                let span = (end as i128 - start as i128) as u128 + 1;
+                let offset = ((rng.next_u64() as u128) << 64 | rng.next_u64() as u128) % span;
+                Number::Integer((start as i128 + offset as i128) as isize)
+            }
+        }
+    }
+
+    fn value_at(&self, rng: &mut Rng, depth: usize) -> JsonValue {
+        let kinds = if depth == 0 { 4 } else { 6 };
+        match rng.below(kinds) {
+            0 => match rng.below(3) {
+                0 => JsonValue::Null,
+                1 => JsonValue::True,
+                _ => JsonValue::False,
+            },
+            1 => JsonValue::Number(self.number(rng)),
+            2 | 3 => JsonValue::String(self.string(rng)),
+            4 => {
+                let len = rng.below(self.max_width + 1);
+                JsonValue::Array((0..len).map(|_| self.value_at(rng, depth - 1)).collect())
+            }
+            _ => JsonValue::Object(self.object_at(rng, depth)),
+        }
+    }
+    // Keys are unique: documents with duplicate keys have no single meaning.
+    fn object_at(&self, rng: &mut Rng, depth: usize) -> JsonObject {
+        let len = rng.below(self.max_width + 1);
+        let mut keys = HashSet::new();
+        let members = (0..len)
+            .filter_map(|_| {
+                let key = self.string(rng);
+                match keys.insert(key.clone()) {
+                    true => Some(Member::new(key, self.value_at(rng, depth - 1))),
+                    false => None,
+                }
+            })
+            .collect();
+        JsonObject::new(members)
+    }
+}
+
+// Values simpler than `value`, simplest first: fewer elements, shorter strings, numbers
+// closer to zero, and containers replaced by what they hold.
+pub fn shrink(value: &JsonValue) -> Vec<JsonValue> {
+    let mut simpler = vec![];
+    match value {
+        JsonValue::Null => {}
+        JsonValue::True | JsonValue::False => simpler.push(JsonValue::Null),
+        JsonValue::Number(n) => {
+            simpler.push(JsonValue::Null);
+            simpler.extend(shrink_number(n).into_iter().map(JsonValue::Number));
+        }
+        JsonValue::String(s) => {
+            simpler.push(JsonValue::Null);
+            simpler.extend(shrink_string(s).into_iter().map(JsonValue::String));
+        }
+        JsonValue::Array(values) => {
+            simpler.push(JsonValue::Null);
+            simpler.extend(values.iter().cloned());
+            simpler.extend(
+                shrink_list(values, shrink)
+                    .into_iter()
+                    .map(JsonValue::Array),
+            );
+        }
+        JsonValue::Object(object) => {
+            simpler.push(JsonValue::Null);
+            simpler.extend(object.iter().map(|member| member.value().clone()));
+            let members: Vec<_> = object.iter().cloned().collect();
+            let shrunk = shrink_list(&members, |member| {
+                let value = shrink(member.value())
+                    .into_iter()
+                    .map(|value| Member::new(member.identifier(), value));
+                let keys = shrink_string(member.identifier())
+                    .into_iter()
+                    .filter(|key| object.get(key).is_none())
+                    .map(|key| Member::new(key, member.value().clone()));
+                value.chain(keys).collect()
+            });
+            simpler.extend(
+                shrunk
+                    .into_iter()
+                    .map(|members| JsonValue::Object(JsonObject::new(members))),
+            );
+        }
+    }
+    simpler
+}
+fn shrink_number(n: &Number) -> Vec<Number> {
+    let mut simpler = vec![];
+    match *n {
+        Number::Integer(0) => {}
+        Number::Integer(i) => {
+            simpler.push(Number::Integer(0));
+            simpler.push(Number::Integer(i / 2));
+            if i < 0 {
+                simpler.push(Number::Integer(i.saturating_neg()));
+            }
+        }
+        Number::Float(f) if f == 0.0 && f.is_sign_positive() => {}
+        Number::Float(f) => {
+            simpler.push(Number::Integer(0));
+            if f.fract() != 0.0 {
+                simpler.push(Number::Float(f.trunc()));
+            }
+            if f.abs() > 1.0 {
+                simpler.push(Number::Float(f / 2.0));
+            }
+            if f < 0.0 {
+                simpler.push(Number::Float(-f));
+            }
+        }
+        _ => simpler.push(Number::Integer(0)),
+    }
+    simpler
+}
+fn shrink_string(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut simpler = shrink_list(&chars, |c| match *c {
+        'a' => vec![],
+        _ => vec!['a'],
+    });
+    simpler.dedup();
+    simpler
+        .into_iter()
+        .map(|chars| chars.into_iter().collect())
+        .collect()
+}
+// Shorter lists first: empty, halves, one element less; then lists with one element
+// shrunk by `shrink_item`.
+fn shrink_list<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut simpler = vec![];
+    if items.is_empty() {
+        return simpler;
+    }
+    simpler.push(vec![]);
+    if items.len() > 2 {
+        let half = items.len() / 2;
+        simpler.push(items[..half].to_vec());
+        simpler.push(items[half..].to_vec());
+    }
+    if items.len() > 1 {
+        for i in 0..items.len() {
+            let mut fewer = items.to_vec();
+            fewer.remove(i);
+            simpler.push(fewer);
+        }
+    }
+    for (i, item) in items.iter().enumerate() {
+        for shrunk in shrink_item(item) {
+            let mut list = items.to_vec();
+            list[i] = shrunk;
+            simpler.push(list);
+        }
+    }
+    simpler
+}
+
+// Shrinks `value`, which fails `property`, for as long as a simpler value still fails
+// it. Returns the simplest failing value with its failure.
+pub fn minimize(
+    value: JsonValue,
+    failure: String,
+    property: impl Fn(&JsonValue) -> Result<(), String>,
+) -> (JsonValue, String) {
+    let (mut value, mut failure) = (value, failure);
+    'simpler: loop {
+        for candidate in shrink(&value) {
+            if let Err(candidate_failure) = run(&property, &candidate) {
+                value = candidate;
+                failure = candidate_failure;
+                continue 'simpler;
+            }
+        }
+        return (value, failure);
+    }
+}
+// Panics count as failures.
+fn run(
+    property: impl Fn(&JsonValue) -> Result<(), String>,
+    value: &JsonValue,
+) -> Result<(), String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| property(value))) {
+        Ok(result) => result,
+        Err(payload) => Err(format!(
+            "panicked: {}",
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default()
+        )),
+    }
+}
+
+// Runs `property` on `cases` generated values, seeded from `JSON_PROPERTY_SEED` when
+// it is set, and panics with the minimal counterexample if one fails.
+pub fn check(
+    generator: &Generator,
+    cases: usize,
+    property: impl Fn(&JsonValue) -> Result<(), String>,
+) {
+    let seed = std::env::var("JSON_PROPERTY_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0x5eed);
+    check_with(cases, seed, |rng| generator.value(rng), property)
+}
+// Like `check`, with the seed and the way values are drawn given.
+pub fn check_with(
+    cases: usize,
+    seed: u64,
+    draw: impl Fn(&mut Rng) -> JsonValue,
+    property: impl Fn(&JsonValue) -> Result<(), String>,
+) {
+    let mut rng = Rng::new(seed);
+    for case in 0..cases {
+        let value = draw(&mut rng);
+        if let Err(failure) = run(&property, &value) {
+            let (value, failure) = minimize(value, failure, &property);
+            panic!(
+                "property failed on case {} with seed {}\nminimal counterexample: {:?}\n{}",
+                case, seed, value, failure
+            );
+        }
+    }
+}
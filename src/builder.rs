@@ -0,0 +1,120 @@
+use alloc::sync::Arc;
+use core::cell::RefCell;
+use core::ops::Range;
+
+use crate::prelude::*;
+use crate::serialize::unescape;
+use crate::{JsonObject, JsonValue, Member, Number};
+
+// The grammar in `lib.rs` is generic over the representation it produces, so the
+// same `object()`/`array()` parsers can build either the owned tree or a `Document`.
+pub trait Builder<'a> {
+    type Value;
+    type Member;
+    type Object;
+
+    fn number(&self, n: Number) -> Self::Value;
+    // Strings and keys are given as written between their quotes, escapes included.
+    fn string(&self, s: &'a str) -> Self::Value;
+    fn boolean(&self, b: bool) -> Self::Value;
+    fn null(&self) -> Self::Value;
+    fn array(&self, items: Vec<Self::Value>) -> Self::Value;
+    fn member(&self, identifier: &'a str, value: Self::Value) -> Self::Member;
+    fn object(&self, members: Vec<Self::Member>) -> Self::Object;
+    fn object_value(&self, object: Self::Object) -> Self::Value;
+
+    // Called with the byte range each value (and each member's key) was parsed from.
+    fn with_span(&self, value: Self::Value, _span: Range<usize>) -> Self::Value {
+        value
+    }
+    fn member_span(&self, member: Self::Member, _key: Range<usize>) -> Self::Member {
+        member
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TreeBuilder;
+
+impl<'a> Builder<'a> for TreeBuilder {
+    type Value = JsonValue;
+    type Member = Member;
+    type Object = JsonObject;
+
+    fn number(&self, n: Number) -> JsonValue {
+        JsonValue::Number(n)
+    }
+    fn string(&self, s: &'a str) -> JsonValue {
+        JsonValue::String(unescape(s).into_owned())
+    }
+    fn boolean(&self, b: bool) -> JsonValue {
+        if b {
+            JsonValue::True
+        } else {
+            JsonValue::False
+        }
+    }
+    fn null(&self) -> JsonValue {
+        JsonValue::Null
+    }
+    fn array(&self, items: Vec<JsonValue>) -> JsonValue {
+        JsonValue::Array(items)
+    }
+    fn member(&self, identifier: &'a str, value: JsonValue) -> Member {
+        Member::new(unescape(identifier), value)
+    }
+    fn object(&self, members: Vec<Member>) -> JsonObject {
+        JsonObject::new(members)
+    }
+    fn object_value(&self, object: JsonObject) -> JsonValue {
+        JsonValue::Object(object)
+    }
+}
+
+// Builds the same tree as `TreeBuilder`, but keeps a per-document symbol table so
+// that every object key is allocated once, however many objects repeat it.
+#[derive(Debug, Default)]
+pub struct InterningBuilder<'a> {
+    symbols: RefCell<HashMap<&'a str, Arc<str>>>,
+}
+impl<'a> InterningBuilder<'a> {
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.borrow().len()
+    }
+    fn intern(&self, identifier: &'a str) -> Arc<str> {
+        self.symbols
+            .borrow_mut()
+            .entry(identifier)
+            .or_insert_with(|| Arc::from(unescape(identifier)))
+            .clone()
+    }
+}
+impl<'a> Builder<'a> for InterningBuilder<'a> {
+    type Value = JsonValue;
+    type Member = Member;
+    type Object = JsonObject;
+
+    fn number(&self, n: Number) -> JsonValue {
+        TreeBuilder.number(n)
+    }
+    fn string(&self, s: &'a str) -> JsonValue {
+        TreeBuilder.string(s)
+    }
+    fn boolean(&self, b: bool) -> JsonValue {
+        TreeBuilder.boolean(b)
+    }
+    fn null(&self) -> JsonValue {
+        TreeBuilder.null()
+    }
+    fn array(&self, items: Vec<JsonValue>) -> JsonValue {
+        TreeBuilder.array(items)
+    }
+    fn member(&self, identifier: &'a str, value: JsonValue) -> Member {
+        Member::shared(self.intern(identifier), value)
+    }
+    fn object(&self, members: Vec<Member>) -> JsonObject {
+        TreeBuilder.object(members)
+    }
+    fn object_value(&self, object: JsonObject) -> JsonValue {
+        TreeBuilder.object_value(object)
+    }
+}
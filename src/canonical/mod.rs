@@ -0,0 +1,135 @@
+mod sha256;
+
+pub use sha256::{sha256, Sha256};
+
+use core::error::Error;
+use core::fmt;
+
+use crate::number::ecmascript;
+use crate::prelude::*;
+use crate::{escape_pointer_token, JsonObject, JsonValue, Member, Number};
+
+// `value` in the canonical form of RFC 8785 (JCS): no whitespace, object members
+// sorted by the UTF-16 code units of their keys, numbers written as ECMAScript
+// writes them and only the escapes strings can't do without. Equal values always
+// serialize to the same bytes, whatever the order of their members.
+//
+// Numbers are IEEE 754 doubles in JCS, so integers beyond 2^53 and exact decimals
+// round to the nearest one, as they would once parsed by JavaScript. Those that round
+// to an infinity, and floats that are infinite or NaN, have no canonical form: RFC 8785
+// makes them an error.
+pub fn to_canonical_string(value: &JsonValue) -> Result<String, CanonicalError> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+// The SHA-256 of the canonical form of `value`.
+pub fn content_hash(value: &JsonValue) -> Result<ContentHash, CanonicalError> {
+    Ok(ContentHash(sha256(to_canonical_string(value)?.as_bytes())))
+}
+
+// A number that isn't a finite double, and the JSON Pointer of where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalError {
+    pointer: String,
+    number: Number,
+}
+impl CanonicalError {
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+    pub fn number(&self) -> &Number {
+        &self.number
+    }
+    fn at(mut self, token: &str) -> Self {
+        self.pointer = format!("/{}{}", escape_pointer_token(token), self.pointer);
+        self
+    }
+}
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the number {} at `{}` is not a finite double and has no canonical form",
+            self.number, self.pointer
+        )
+    }
+}
+impl Error for CanonicalError {}
+
+// Displays as 64 lowercase hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub [u8; 32]);
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+fn write_value(out: &mut String, value: &JsonValue) -> Result<(), CanonicalError> {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::True => out.push_str("true"),
+        JsonValue::False => out.push_str("false"),
+        JsonValue::Number(n) => match n.as_f64() {
+            x if x.is_finite() => out.push_str(&ecmascript(x)),
+            _ => {
+                return Err(CanonicalError {
+                    pointer: String::new(),
+                    number: n.clone(),
+                })
+            }
+        },
+        JsonValue::String(s) => write_string(out, s),
+        JsonValue::Array(values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, value).map_err(|error| error.at(&i.to_string()))?;
+            }
+            out.push(']');
+        }
+        JsonValue::Object(object) => write_object(out, object)?,
+    }
+    Ok(())
+}
+fn write_object(out: &mut String, object: &JsonObject) -> Result<(), CanonicalError> {
+    let mut members: Vec<&Member> = object.iter().collect();
+    members.sort_by(|a, b| {
+        a.identifier()
+            .encode_utf16()
+            .cmp(b.identifier().encode_utf16())
+    });
+    out.push('{');
+    for (i, member) in members.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        write_string(out, member.identifier());
+        out.push(':');
+        write_value(out, member.value()).map_err(|error| error.at(member.identifier()))?;
+    }
+    out.push('}');
+    Ok(())
+}
+// Unlike `serialize::escape`, leaves DEL as is: JCS only escapes control characters.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,275 @@
+use std::io::{self, IsTerminal};
+
+use json_parser::diff::{diff, to_patch, ArrayDiff, Change, DiffOptions};
+use json_parser::serialize::{to_string, to_string_pretty};
+use json_parser::{parse_value, pointer_tokens, JsonValue, ParseOptions};
+
+use crate::cli::{option_value, read_sources, Source, FAILURE, SUCCESS, USAGE_ERROR};
+
+const USAGE: &str = "\
+usage: json-parser diff [options] <old> <new>
+
+Compares two json documents structurally: object members are matched by key
+whatever their order. Exits with 1 when they differ, and with 2 on trouble.
+
+options:
+    --format <format>    tree (default), patch for an RFC 6902 JSON Patch, or
+                         unified for a text diff of each changed value
+    --arrays <mode>      compare arrays by index, along their longest common
+                         subsequence (lcs, the default), or as sets
+    --ignore <pointer>   leave out the value at this JSON Pointer; `*` matches
+                         any key or index, e.g. `/items/*/updatedAt`. Repeatable
+    --tolerance <x>      treat numbers at most <x> apart as equal
+    --color <when>       auto (default), always or never
+    --json5              accept JSON5 documents
+    --exact-numbers      compare the exact values of numbers instead of doubles";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Tree,
+    Patch,
+    Unified,
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+pub fn run(args: &[String]) -> i32 {
+    let mut options = DiffOptions::default();
+    let mut parse_options = ParseOptions::default();
+    let mut format = Format::Tree;
+    let mut color = io::stdout().is_terminal();
+    let mut files = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return SUCCESS;
+            }
+            "--json5" => {
+                parse_options.json5 = true;
+                Ok(())
+            }
+            "--exact-numbers" => {
+                parse_options.exact_numbers = true;
+                Ok(())
+            }
+            "--" => {
+                files.extend(args.by_ref().cloned());
+                Ok(())
+            }
+            _ => {
+                if let Some(value) = option_value("--format", arg, &mut args) {
+                    value.and_then(|value| {
+                        format = match value {
+                            "tree" => Format::Tree,
+                            "patch" => Format::Patch,
+                            "unified" => Format::Unified,
+                            _ => return Err(format!("unknown format `{}`", value)),
+                        };
+                        Ok(())
+                    })
+                } else if let Some(value) = option_value("--arrays", arg, &mut args) {
+                    value.and_then(|value| {
+                        options.arrays = match value {
+                            "index" => ArrayDiff::Index,
+                            "lcs" => ArrayDiff::Lcs,
+                            "set" => ArrayDiff::Set,
+                            _ => return Err(format!("unknown array mode `{}`", value)),
+                        };
+                        Ok(())
+                    })
+                } else if let Some(value) = option_value("--ignore", arg, &mut args) {
+                    value.and_then(|value| match pointer_tokens(value) {
+                        Some(_) => {
+                            options.ignore.push(value.to_string());
+                            Ok(())
+                        }
+                        None => Err(format!("`{}` is not a JSON Pointer", value)),
+                    })
+                } else if let Some(value) = option_value("--tolerance", arg, &mut args) {
+                    value.and_then(|value| match value.parse::<f64>() {
+                        Ok(tolerance) if tolerance >= 0.0 => {
+                            options.tolerance = tolerance;
+                            Ok(())
+                        }
+                        _ => Err(format!("invalid tolerance `{}`", value)),
+                    })
+                } else if let Some(value) = option_value("--color", arg, &mut args) {
+                    value.and_then(|value| {
+                        color = match value {
+                            "auto" => color,
+                            "always" => true,
+                            "never" => false,
+                            _ => return Err(format!("unknown color mode `{}`", value)),
+                        };
+                        Ok(())
+                    })
+                } else if arg.starts_with("--") {
+                    Err(format!("unknown option `{}`", arg))
+                } else {
+                    files.push(arg.clone());
+                    Ok(())
+                }
+            }
+        };
+        if let Err(message) = parsed {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return USAGE_ERROR;
+        }
+    }
+    if files.len() != 2 {
+        eprintln!("error: expected two files to compare\n\n{}", USAGE);
+        return USAGE_ERROR;
+    }
+    let sources = match read_sources(&files) {
+        Ok(sources) if sources.len() == 2 => sources,
+        Ok(_) => {
+            eprintln!("error: expected two files to compare, not globs");
+            return USAGE_ERROR;
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return USAGE_ERROR;
+        }
+    };
+    let (old, new) = match (
+        document(&sources[0], &parse_options),
+        document(&sources[1], &parse_options),
+    ) {
+        (Some(old), Some(new)) => (old, new),
+        _ => return USAGE_ERROR,
+    };
+    let changes = diff(&old, &new, &options);
+    let painter = Painter { color };
+    match format {
+        Format::Tree => print!("{}", tree(&changes, &old, &new, &painter)),
+        Format::Patch => println!("{}", to_string_pretty(&to_patch(&changes))),
+        Format::Unified if changes.is_empty() => {}
+        Format::Unified => print!(
+            "{}",
+            unified(&changes, &sources[0].name, &sources[1].name, &painter)
+        ),
+    }
+    match changes.is_empty() {
+        true => SUCCESS,
+        false => FAILURE,
+    }
+}
+
+fn document(source: &Source, options: &ParseOptions) -> Option<JsonValue> {
+    let document = match &source.malformed {
+        Some(diagnostic) => Err(diagnostic.clone()),
+        None => parse_value(&source.text, options),
+    };
+    document
+        .map_err(|diagnostic| eprint!("{}", diagnostic.render(&source.name, &source.text)))
+        .ok()
+}
+
+struct Painter {
+    color: bool,
+}
+impl Painter {
+    fn paint(&self, color: &str, text: &str) -> String {
+        match self.color {
+            true => format!("{}{}{}", color, text, RESET),
+            false => text.to_string(),
+        }
+    }
+}
+
+// The changed values under the members and elements holding them:
+//
+//     users
+//       [0]
+//         ~ name: "ann" -> "anna"
+//       + [3]: {"name":"dan"}
+//     - legacy: true
+fn tree(changes: &[Change], old: &JsonValue, new: &JsonValue, painter: &Painter) -> String {
+    let mut out = String::new();
+    // The tokens of the branch printed last, to only print where the next one differs.
+    let mut printed: Vec<String> = vec![];
+    for change in changes {
+        let tokens: Vec<String> = pointer_tokens(change.path())
+            .map(Iterator::collect)
+            .unwrap_or_default();
+        let (parents, last) = match tokens.split_last() {
+            Some((last, parents)) => (parents, Some(last)),
+            None => (&tokens[..], None),
+        };
+        let common = printed
+            .iter()
+            .zip(parents)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for depth in common..parents.len() {
+            let label = label(&parents[..depth], &parents[depth], old, new);
+            out.push_str(&format!("{}{}\n", "  ".repeat(depth), label));
+        }
+        printed = parents.to_vec();
+        let label = match last {
+            Some(last) => format!("{}: ", label(parents, last, old, new)),
+            None => String::new(),
+        };
+        let indent = "  ".repeat(parents.len());
+        let line = match change {
+            Change::Added { value, .. } => {
+                painter.paint(GREEN, &format!("+ {}{}", label, to_string(value)))
+            }
+            Change::Removed { value, .. } => {
+                painter.paint(RED, &format!("- {}{}", label, to_string(value)))
+            }
+            Change::Modified { old, new, .. } => painter.paint(
+                YELLOW,
+                &format!("~ {}{} -> {}", label, to_string(old), to_string(new)),
+            ),
+        };
+        out.push_str(&format!("{}{}\n", indent, line));
+    }
+    out
+}
+
+// `[i]` for array elements and the key for object members.
+fn label(parents: &[String], token: &str, old: &JsonValue, new: &JsonValue) -> String {
+    let pointer: String = parents
+        .iter()
+        .map(|token| format!("/{}", json_parser::escape_pointer_token(token)))
+        .collect();
+    match old.pointer(&pointer).or_else(|| new.pointer(&pointer)) {
+        Some(JsonValue::Array(_)) => format!("[{}]", token),
+        _ => token.to_string(),
+    }
+}
+
+// Each change as a hunk of pretty-printed values, headed by its path.
+fn unified(changes: &[Change], old_name: &str, new_name: &str, painter: &Painter) -> String {
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let lines = |sign: char, color: &str, value: &JsonValue| -> String {
+        to_string_pretty(value)
+            .lines()
+            .map(|line| painter.paint(color, &format!("{}{}", sign, line)) + "\n")
+            .collect()
+    };
+    for change in changes {
+        let path = match change.path() {
+            "" => "/",
+            path => path,
+        };
+        out.push_str(&painter.paint(CYAN, &format!("@@ {} @@", path)));
+        out.push('\n');
+        match change {
+            Change::Added { value, .. } => out.push_str(&lines('+', GREEN, value)),
+            Change::Removed { value, .. } => out.push_str(&lines('-', RED, value)),
+            Change::Modified { old, new, .. } => {
+                out.push_str(&lines('-', RED, old));
+                out.push_str(&lines('+', GREEN, new));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,219 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+use json_parser::serialize::{to_string_commented, Comments, Format};
+use json_parser::{parse_spanned_value, Diagnostic, ParseOptions};
+
+use crate::cli::unified::unified;
+use crate::cli::{option_value, read_sources, Source, FAILURE, SUCCESS, USAGE_ERROR};
+
+const USAGE: &str = "\
+usage: json-parser fmt [options] [files...]
+
+Reformats json documents, printing them to stdout unless `--write` or `--check` is
+given. Exits with 1 when a file is invalid or, with `--check`, not formatted.
+
+options:
+    --indent <n|tab>     indent with <n> spaces (2 by default) or with tabs
+    --minify             write each document on one line, without spaces
+    --sort-keys          order the members of objects by key
+    -w, --write          rewrite the files in place
+    --check              write nothing, print a diff of each file that isn't
+                         formatted
+    --strict             only accept what RFC 8259 allows
+    --json5              accept JSON5; the output is plain json, so comments are
+                         dropped unless `--keep-comments` is given
+    --keep-comments      with `--json5`, keep the comments next to the values they
+                         were written by
+    --exact-numbers      keep every digit of numbers instead of rounding them to
+                         doubles";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Print,
+    Write,
+    Check,
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut options = ParseOptions::default();
+    let mut format = Format::pretty();
+    let mut minify = false;
+    let mut indent = None;
+    let mut keep_comments = false;
+    let mut mode = Mode::Print;
+    let mut files = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return SUCCESS;
+            }
+            "--minify" => {
+                minify = true;
+                Ok(())
+            }
+            "--sort-keys" => {
+                format.sort_keys = true;
+                Ok(())
+            }
+            "-w" | "--write" | "--check" if mode != Mode::Print => {
+                Err("`--write` and `--check` can't be used together".to_string())
+            }
+            "-w" | "--write" => {
+                mode = Mode::Write;
+                Ok(())
+            }
+            "--check" => {
+                mode = Mode::Check;
+                Ok(())
+            }
+            "--strict" => {
+                options.strict = true;
+                Ok(())
+            }
+            "--json5" => {
+                options.json5 = true;
+                Ok(())
+            }
+            "--exact-numbers" => {
+                options.exact_numbers = true;
+                Ok(())
+            }
+            "--keep-comments" => {
+                keep_comments = true;
+                Ok(())
+            }
+            "--" => {
+                files.extend(args.by_ref().cloned());
+                Ok(())
+            }
+            _ => {
+                if let Some(value) = option_value("--indent", arg, &mut args) {
+                    value.and_then(|value| {
+                        indent = Some(match value {
+                            "tab" => "\t".to_string(),
+                            _ => match value.parse() {
+                                Ok(width) => " ".repeat(width),
+                                Err(_) => return Err(format!("invalid indent `{}`", value)),
+                            },
+                        });
+                        Ok(())
+                    })
+                } else if arg.starts_with('-') && arg != "-" {
+                    Err(format!("unknown option `{}`", arg))
+                } else {
+                    files.push(arg.clone());
+                    Ok(())
+                }
+            }
+        };
+        if let Err(message) = parsed {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return USAGE_ERROR;
+        }
+    }
+    let conflict = match () {
+        _ if options.strict && options.json5 => Some("`--strict` and `--json5`"),
+        _ if minify && indent.is_some() => Some("`--minify` and `--indent`"),
+        _ => None,
+    };
+    if let Some(conflict) = conflict {
+        eprintln!("error: {} can't be used together", conflict);
+        return USAGE_ERROR;
+    }
+    if keep_comments && !options.json5 {
+        eprintln!("error: `--keep-comments` needs `--json5`, only JSON5 has comments");
+        return USAGE_ERROR;
+    }
+    format.indent = match minify {
+        true => None,
+        false => indent.or(format.indent),
+    };
+    let sources = match read_sources(&files) {
+        Ok(sources) => sources,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return USAGE_ERROR;
+        }
+    };
+    let mut status = SUCCESS;
+    for source in &sources {
+        let formatted = match reformat(source, &options, &format, keep_comments) {
+            Ok(formatted) => formatted,
+            Err(diagnostic) => {
+                eprint!("{}", diagnostic.render(&source.name, &source.text));
+                status = FAILURE;
+                continue;
+            }
+        };
+        match mode {
+            Mode::Print => print!("{}", formatted),
+            Mode::Write if source.is_stdin() => print!("{}", formatted),
+            Mode::Write if formatted != source.text => {
+                if let Err(error) = write_atomically(Path::new(&source.name), &formatted) {
+                    eprintln!("error: can't write `{}`: {}", source.name, error);
+                    status = FAILURE;
+                }
+            }
+            Mode::Write => {}
+            Mode::Check => {
+                let diff = unified(
+                    &source.name,
+                    &format!("{} (formatted)", source.name),
+                    &source.text,
+                    &formatted,
+                );
+                if !diff.is_empty() {
+                    print!("{}", diff);
+                    status = FAILURE;
+                }
+            }
+        }
+    }
+    status
+}
+
+fn reformat(
+    source: &Source,
+    options: &ParseOptions,
+    format: &Format,
+    keep_comments: bool,
+) -> Result<String, Diagnostic> {
+    if let Some(diagnostic) = &source.malformed {
+        return Err(diagnostic.clone());
+    }
+    let spanned = parse_spanned_value(&source.text, options)?;
+    let comments = match keep_comments {
+        true => Comments::collect(&spanned),
+        false => Comments::default(),
+    };
+    let mut formatted = to_string_commented(&spanned.value, format, &comments);
+    formatted.push('\n');
+    Ok(formatted)
+}
+
+// Replaces the file at `path` without ever leaving it half-written: the new contents
+// go to a temporary file in the same directory, which is then renamed over it.
+fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary = path.with_file_name(format!(".{}.{}.tmp", name, process::id()));
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)?;
+        file.write_all(contents.as_bytes())?;
+        file.set_permissions(fs::metadata(&path)?.permissions())?;
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
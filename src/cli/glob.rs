@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn is_pattern(arg: &str) -> bool {
+    arg.contains(['*', '?'])
+}
+
+// The files matching a path pattern where `*` and `?` match within one component and
+// `**` matches any number of directories. Hidden entries only match patterns that
+// start with a `.`, and `**` doesn't follow symbolic links, which could loop.
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    let root = if pattern.starts_with('/') {
+        Some(PathBuf::from("/"))
+    } else {
+        None
+    };
+    let components: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+    let mut matches = vec![];
+    walk(root.as_deref(), &components, &mut matches);
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+fn walk(dir: Option<&Path>, components: &[&str], matches: &mut Vec<PathBuf>) {
+    let (component, rest) = match components.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let join = |name: &str| match dir {
+        Some(dir) => dir.join(name),
+        None => PathBuf::from(name),
+    };
+    if *component == "**" {
+        walk(dir, rest, matches);
+        for entry in entries(dir) {
+            let path = join(&entry);
+            let real_dir = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+            if real_dir && !entry.starts_with('.') {
+                walk(Some(&path), components, matches);
+            }
+        }
+    } else if is_pattern(component) {
+        for entry in entries(dir) {
+            if entry.starts_with('.') && !component.starts_with('.') {
+                continue;
+            }
+            if wildcard(component, &entry) {
+                visit(join(&entry), rest, matches);
+            }
+        }
+    } else {
+        visit(join(component), rest, matches);
+    }
+}
+
+fn visit(path: PathBuf, rest: &[&str], matches: &mut Vec<PathBuf>) {
+    if rest.is_empty() {
+        if path.is_file() {
+            matches.push(path);
+        }
+    } else if path.is_dir() {
+        walk(Some(&path), rest, matches);
+    }
+}
+
+fn entries(dir: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir.unwrap_or_else(|| Path::new(".")))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Where to resume after the last `*`: its index in the pattern and in the name.
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
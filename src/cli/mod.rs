@@ -0,0 +1,139 @@
+mod diff;
+mod fmt;
+mod glob;
+mod query;
+mod unified;
+mod validate;
+
+use std::fs;
+use std::io::{self, Read};
+
+use json_parser::{encoding, Diagnostic};
+
+const USAGE: &str = "\
+usage: json-parser <command> [options] [files...]
+
+commands:
+    validate    check that files are valid json
+    fmt         pretty-print or minify files, or check that they are formatted
+    query       run a jq filter on files
+    diff        compare two files structurally
+
+Files can be paths or glob patterns (`*`, `?`, `**`); `-` or no file reads stdin.
+Run `json-parser <command> --help` for the options of a command.";
+
+// Exit codes shared by the commands.
+pub const SUCCESS: i32 = 0;
+pub const FAILURE: i32 = 1;
+pub const USAGE_ERROR: i32 = 2;
+
+pub fn run(args: Vec<String>) -> i32 {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            eprintln!("{}", USAGE);
+            return USAGE_ERROR;
+        }
+    };
+    match command {
+        "validate" => validate::run(args),
+        "fmt" => fmt::run(args),
+        "query" => query::run(args),
+        "diff" => diff::run(args),
+        "-h" | "--help" | "help" => {
+            println!("{}", USAGE);
+            SUCCESS
+        }
+        _ => {
+            eprintln!("error: unknown command `{}`\n\n{}", command, USAGE);
+            USAGE_ERROR
+        }
+    }
+}
+
+// One input document, decoded from UTF-8, UTF-16 or UTF-32. `text` is lossily decoded
+// when the input is malformed, in which case `malformed` points at the first bad byte.
+pub struct Source {
+    pub name: String,
+    pub text: String,
+    pub malformed: Option<Diagnostic>,
+}
+impl Source {
+    fn new(name: String, bytes: Vec<u8>) -> Self {
+        let (text, malformed) = match encoding::decode(&bytes, false) {
+            Ok(text) => (text.into_owned(), None),
+            Err(diagnostic) => {
+                let text = encoding::decode(&bytes, true).expect("lossy decoding can't fail");
+                (text.into_owned(), Some(diagnostic))
+            }
+        };
+        Self {
+            name,
+            text,
+            malformed,
+        }
+    }
+    pub fn is_stdin(&self) -> bool {
+        self.name == STDIN
+    }
+}
+
+const STDIN: &str = "<stdin>";
+
+// Reads every file named by `args`, expanding globs; stdin when there are none.
+pub fn read_sources(args: &[String]) -> Result<Vec<Source>, String> {
+    if args.is_empty() {
+        return read_stdin().map(|source| vec![source]);
+    }
+    let mut sources = vec![];
+    for arg in args {
+        if arg == "-" {
+            sources.push(read_stdin()?);
+        } else if glob::is_pattern(arg) {
+            let paths = glob::expand(arg);
+            if paths.is_empty() {
+                return Err(format!("no file matches `{}`", arg));
+            }
+            for path in paths {
+                let name = path.display().to_string();
+                sources.push(read_file(name)?);
+            }
+        } else {
+            sources.push(read_file(arg.clone())?);
+        }
+    }
+    Ok(sources)
+}
+
+fn read_file(name: String) -> Result<Source, String> {
+    fs::read(&name)
+        .map(|bytes| Source::new(name.clone(), bytes))
+        .map_err(|error| format!("can't read `{}`: {}", name, error))
+}
+
+fn read_stdin() -> Result<Source, String> {
+    let mut bytes = vec![];
+    io::stdin()
+        .read_to_end(&mut bytes)
+        .map_err(|error| format!("can't read stdin: {}", error))?;
+    Ok(Source::new(STDIN.to_string(), bytes))
+}
+
+// `--name value` or `--name=value`.
+pub fn option_value<'a>(
+    name: &str,
+    arg: &'a str,
+    rest: &mut impl Iterator<Item = &'a String>,
+) -> Option<Result<&'a str, String>> {
+    if arg == name {
+        Some(
+            rest.next()
+                .map(String::as_str)
+                .ok_or_else(|| format!("`{}` needs a value", name)),
+        )
+    } else {
+        arg.strip_prefix(name)
+            .and_then(|value| value.strip_prefix('='))
+            .map(Ok)
+    }
+}
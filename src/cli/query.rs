@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+
+use json_parser::query::{Filter, QueryError};
+use json_parser::serialize::{to_string_with, Format};
+use json_parser::{parse_value, JsonValue, ParseOptions};
+
+use crate::cli::{option_value, read_sources, FAILURE, SUCCESS, USAGE_ERROR};
+
+const USAGE: &str = "\
+usage: json-parser query [options] <filter> [files...]
+
+Runs a jq filter on each file and prints every output as soon as it is produced.
+Exits with 1 when a file is invalid or the filter fails on it.
+
+The supported subset of jq: `.`, `.a.b`, `.\"a b\"`, `.[0]`, `.[]`, `.[2:5]`, `..`,
+`?`, `|`, `,`, `[...]`, `{...}`, `+ - * / %`, `== != < <= > >=`, `and`, `or`,
+`//`, `select(f)`, `map(f)`, `has(k)`, `keys`, `length`, `add`, `type`, `not`
+and `empty`.
+
+options:
+    -r, --raw-output     print strings without quotes
+    -c, --compact        print each output on one line
+    --indent <n>         indent pretty-printed outputs with <n> spaces
+    --json5              accept JSON5 files
+    --exact-numbers      keep every digit of numbers; `+`, `-` and `*` on them
+                         are then exact";
+
+pub fn run(args: &[String]) -> i32 {
+    let mut options = ParseOptions::default();
+    let mut format = Format::pretty();
+    let mut raw = false;
+    let mut filter = None;
+    let mut files = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return SUCCESS;
+            }
+            "-r" | "--raw-output" => {
+                raw = true;
+                Ok(())
+            }
+            "-c" | "--compact" => {
+                format.indent = None;
+                Ok(())
+            }
+            "--json5" => {
+                options.json5 = true;
+                Ok(())
+            }
+            "--exact-numbers" => {
+                options.exact_numbers = true;
+                Ok(())
+            }
+            "--" => {
+                if filter.is_none() {
+                    filter = args.next().cloned();
+                }
+                files.extend(args.by_ref().cloned());
+                Ok(())
+            }
+            _ => {
+                if let Some(value) = option_value("--indent", arg, &mut args) {
+                    value.and_then(|value| {
+                        value
+                            .parse()
+                            .map(|width| format.indent = Some(" ".repeat(width)))
+                            .map_err(|_| format!("invalid indent `{}`", value))
+                    })
+                } else if arg.starts_with('-') && arg != "-" {
+                    Err(format!("unknown option `{}`", arg))
+                } else if filter.is_none() {
+                    filter = Some(arg.clone());
+                    Ok(())
+                } else {
+                    files.push(arg.clone());
+                    Ok(())
+                }
+            }
+        };
+        if let Err(message) = parsed {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return USAGE_ERROR;
+        }
+    }
+    let source = match filter {
+        Some(source) => source,
+        None => {
+            eprintln!("error: missing the filter\n\n{}", USAGE);
+            return USAGE_ERROR;
+        }
+    };
+    let filter = match Filter::parse(&source) {
+        Ok(filter) => filter,
+        Err(diagnostic) => {
+            eprint!("{}", diagnostic.render("<filter>", &source));
+            return USAGE_ERROR;
+        }
+    };
+    let sources = match read_sources(&files) {
+        Ok(sources) => sources,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return USAGE_ERROR;
+        }
+    };
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut status = SUCCESS;
+    // Set when whatever reads the output, like `head`, has stopped.
+    let mut closed = false;
+    for source in &sources {
+        let document = match &source.malformed {
+            Some(diagnostic) => Err(diagnostic.clone()),
+            None => parse_value(&source.text, &options),
+        };
+        let document = match document {
+            Ok(document) => document,
+            Err(diagnostic) => {
+                eprint!("{}", diagnostic.render(&source.name, &source.text));
+                status = FAILURE;
+                continue;
+            }
+        };
+        let result = filter.run(&document, |value| {
+            let text = match value {
+                JsonValue::String(s) if raw => s,
+                value => to_string_with(&value, &format),
+            };
+            writeln!(out, "{}", text).map_err(|error| {
+                closed = error.kind() == io::ErrorKind::BrokenPipe;
+                QueryError::new(format!("can't write the output: {}", error))
+            })
+        });
+        if closed {
+            break;
+        }
+        if let Err(error) = result {
+            eprintln!("error: {}: {}", source.name, error);
+            status = FAILURE;
+        }
+    }
+    status
+}
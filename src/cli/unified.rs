@@ -0,0 +1,165 @@
+// Line diffs in the unified format of `diff -u`, for `fmt --check`.
+
+// Past this many differing lines a change is shown as one block instead of searching
+// for the shortest edit, which takes memory quadratic in the number of differences.
+const MAX_EDITS: usize = 1024;
+const CONTEXT: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Keep,
+    Delete,
+    Insert,
+}
+
+// The unified diff turning `old` into `new`, empty when they are the same.
+pub fn unified(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = edits(&a, &b);
+    if ops.iter().all(|op| *op == Op::Keep) {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    // The line of `a` and of `b` each op starts at.
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut i, mut j) = (0, 0);
+    for op in &ops {
+        positions.push((i, j));
+        match op {
+            Op::Keep => (i, j) = (i + 1, j + 1),
+            Op::Delete => i += 1,
+            Op::Insert => j += 1,
+        }
+    }
+    positions.push((i, j));
+    let mut start = 0;
+    while let Some(first) = (start..ops.len()).find(|&k| ops[k] != Op::Keep) {
+        // Extend the hunk while the next change is close enough to share context.
+        let mut last = first;
+        while let Some(next) = (last + 1..ops.len()).find(|&k| ops[k] != Op::Keep) {
+            if next - last - 1 > 2 * CONTEXT {
+                break;
+            }
+            last = next;
+        }
+        let from = first.saturating_sub(CONTEXT);
+        let to = (last + 1 + CONTEXT).min(ops.len());
+        let (a_start, b_start) = positions[from];
+        let (a_end, b_end) = positions[to];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(a_start, a_end - a_start),
+            range(b_start, b_end - b_start)
+        ));
+        for (op, &(i, j)) in ops[from..to].iter().zip(&positions[from..to]) {
+            let (sign, line) = match op {
+                Op::Keep => (' ', a[i]),
+                Op::Delete => ('-', a[i]),
+                Op::Insert => ('+', b[j]),
+            };
+            out.push(sign);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        start = to;
+    }
+    out
+}
+
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+// The shortest edit script, found with Myers' algorithm once the common prefix and
+// suffix are set aside.
+fn edits(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    let mut ops = vec![Op::Keep; prefix];
+    ops.extend(myers(a_mid, b_mid).unwrap_or_else(|| {
+        let mut ops = vec![Op::Delete; a_mid.len()];
+        ops.extend(vec![Op::Insert; b_mid.len()]);
+        ops
+    }));
+    ops.extend(vec![Op::Keep; suffix]);
+    ops
+}
+
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m).min(MAX_EDITS as isize);
+    let at = |k: isize| (k + max + 1) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // Round `d` reads diagonals `-d - 1..=d + 1`; only those are kept for backtracking.
+    let mut trace = vec![];
+    let mut found = false;
+    for d in 0..=max {
+        trace.push(v[at(-d - 1)..=at(d + 1)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                found = true;
+                break;
+            }
+        }
+        if found {
+            break;
+        }
+    }
+    if !found {
+        return None;
+    }
+    let mut ops = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| (k + d + 1) as usize;
+        let k = x - y;
+        let previous = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = v[at(previous)];
+        let previous_y = previous_x - previous;
+        while x > previous_x && y > previous_y {
+            ops.push(Op::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if x == previous_x {
+                Op::Insert
+            } else {
+                Op::Delete
+            });
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    ops.reverse();
+    Some(ops)
+}
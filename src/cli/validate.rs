@@ -0,0 +1,228 @@
+use json_parser::serialize::escape as quote;
+use json_parser::{parse_value, Diagnostic, ParseOptions};
+
+use crate::cli::{option_value, read_sources, Source, FAILURE, SUCCESS, USAGE_ERROR};
+
+const USAGE: &str = "\
+usage: json-parser validate [options] [files...]
+
+Checks that each file is a valid json document. Exits with 1 when one is not,
+and with 2 when a file can't be read.
+
+options:
+    --strict             reject what RFC 8259 forbids but is accepted by default:
+                         invalid escapes, raw control characters in strings,
+                         non-ASCII whitespace and leading zeros
+    --json5              accept comments, trailing commas, unquoted keys,
+                         single-quoted strings, hex numbers and a leading `+`
+    --max-depth <n>      reject arrays and objects nested deeper than <n>
+                         (default 128)
+    --format <format>    human (default), json, sarif or gcc";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Human,
+    Json,
+    Sarif,
+    Gcc,
+}
+
+struct Report<'s> {
+    source: &'s Source,
+    diagnostic: Option<Diagnostic>,
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut options = ParseOptions::default();
+    let mut format = Format::Human;
+    let mut files = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let parsed = match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return SUCCESS;
+            }
+            "--strict" => {
+                options.strict = true;
+                Ok(())
+            }
+            "--json5" => {
+                options.json5 = true;
+                Ok(())
+            }
+            "--" => {
+                files.extend(args.by_ref().cloned());
+                Ok(())
+            }
+            _ => {
+                if let Some(value) = option_value("--max-depth", arg, &mut args) {
+                    value.and_then(|value| {
+                        value
+                            .parse()
+                            .map(|depth| options.max_depth = Some(depth))
+                            .map_err(|_| format!("invalid depth `{}`", value))
+                    })
+                } else if let Some(value) = option_value("--format", arg, &mut args) {
+                    value.and_then(|value| {
+                        format = match value {
+                            "human" => Format::Human,
+                            "json" => Format::Json,
+                            "sarif" => Format::Sarif,
+                            "gcc" => Format::Gcc,
+                            _ => return Err(format!("unknown format `{}`", value)),
+                        };
+                        Ok(())
+                    })
+                } else if arg.starts_with("--") {
+                    Err(format!("unknown option `{}`", arg))
+                } else {
+                    files.push(arg.clone());
+                    Ok(())
+                }
+            }
+        };
+        if let Err(message) = parsed {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            return USAGE_ERROR;
+        }
+    }
+    if options.strict && options.json5 {
+        eprintln!("error: `--strict` and `--json5` can't be used together");
+        return USAGE_ERROR;
+    }
+    let sources = match read_sources(&files) {
+        Ok(sources) => sources,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            return USAGE_ERROR;
+        }
+    };
+    let reports: Vec<Report> = sources
+        .iter()
+        .map(|source| Report {
+            source,
+            diagnostic: check(source, &options),
+        })
+        .collect();
+    match format {
+        Format::Human => {
+            for report in &reports {
+                if let Some(diagnostic) = &report.diagnostic {
+                    eprint!(
+                        "{}",
+                        diagnostic.render(&report.source.name, &report.source.text)
+                    );
+                }
+            }
+        }
+        Format::Gcc => {
+            for report in &reports {
+                if let Some(diagnostic) = &report.diagnostic {
+                    print!("{}", gcc(&report.source.name, diagnostic));
+                }
+            }
+        }
+        Format::Json => println!("{}", json(&reports)),
+        Format::Sarif => println!("{}", sarif(&reports)),
+    }
+    if reports.iter().any(|report| report.diagnostic.is_some()) {
+        FAILURE
+    } else {
+        SUCCESS
+    }
+}
+
+fn check(source: &Source, options: &ParseOptions) -> Option<Diagnostic> {
+    if let Some(diagnostic) = &source.malformed {
+        return Some(diagnostic.clone());
+    }
+    parse_value(&source.text, options).err()
+}
+
+fn gcc(name: &str, diagnostic: &Diagnostic) -> String {
+    let position = format!(
+        "{}:{}:{}",
+        name, diagnostic.start.line, diagnostic.start.column
+    );
+    let mut out = format!("{}: error: {}\n", position, diagnostic.message);
+    for help in &diagnostic.help {
+        out.push_str(&format!("{}: note: {}\n", position, help));
+    }
+    out
+}
+
+fn json(reports: &[Report]) -> String {
+    let files: Vec<String> = reports
+        .iter()
+        .map(|report| {
+            let diagnostics = match &report.diagnostic {
+                Some(d) => format!(
+                    "[{{\"message\": {}, \"help\": [{}], \"line\": {}, \"column\": {}, \
+                     \"endLine\": {}, \"endColumn\": {}, \"offset\": {}, \"length\": {}}}]",
+                    quote(&d.message),
+                    d.help
+                        .iter()
+                        .map(|h| quote(h))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    d.start.line,
+                    d.start.column,
+                    d.end.line,
+                    d.end.column,
+                    d.range.start,
+                    d.range.len()
+                ),
+                None => "[]".to_string(),
+            };
+            format!(
+                "{{\"file\": {}, \"valid\": {}, \"diagnostics\": {}}}",
+                quote(&report.source.name),
+                report.diagnostic.is_none(),
+                diagnostics
+            )
+        })
+        .collect();
+    format!("[{}]", files.join(", "))
+}
+
+// SARIF 2.1.0, the format code scanning services read.
+fn sarif(reports: &[Report]) -> String {
+    let results: Vec<String> = reports
+        .iter()
+        .filter_map(|report| {
+            let d = report.diagnostic.as_ref()?;
+            let mut text = d.message.clone();
+            for help in &d.help {
+                text.push_str(&format!("\nhelp: {}", help));
+            }
+            Some(format!(
+                "{{\"ruleId\": \"invalid-json\", \"level\": \"error\", \"message\": {{\"text\": {}}}, \
+                 \"locations\": [{{\"physicalLocation\": {{\"artifactLocation\": {{\"uri\": {}}}, \
+                 \"region\": {{\"startLine\": {}, \"startColumn\": {}, \"endLine\": {}, \
+                 \"endColumn\": {}, \"byteOffset\": {}, \"byteLength\": {}}}}}}}]}}",
+                quote(&text),
+                quote(&report.source.name.replace('\\', "/")),
+                d.start.line,
+                d.start.column,
+                d.end.line,
+                match d.range.is_empty() {
+                    true => d.start.column + 1,
+                    false => d.end.column,
+                },
+                d.range.start,
+                d.range.len()
+            ))
+        })
+        .collect();
+    format!(
+        "{{\"version\": \"2.1.0\", \
+         \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\", \
+         \"runs\": [{{\"tool\": {{\"driver\": {{\"name\": \"json-parser\", \"version\": {}, \
+         \"rules\": [{{\"id\": \"invalid-json\", \
+         \"shortDescription\": {{\"text\": \"The file is not valid json\"}}}}]}}}}, \
+         \"columnKind\": \"unicodeCodePoints\", \"results\": [{}]}}]}}",
+        quote(env!("CARGO_PKG_VERSION")),
+        results.join(", ")
+    )
+}
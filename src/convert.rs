@@ -0,0 +1,517 @@
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use core::convert::TryFrom;
+use core::error::Error;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::hash::BuildHasher;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::prelude::*;
+use crate::{
+    escape_pointer_token, BigInt, ConversionError, JsonObject, JsonValue, Location, Member, Number,
+};
+
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError>;
+}
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    // Reference tokens from the failing node up to the root; errors collect them while
+    // they bubble out of nested `from_json` calls.
+    path: Vec<String>,
+    reason: String,
+    key: bool,
+    location: Option<Location>,
+}
+impl FromJsonError {
+    pub fn new(reason: impl Into<String>) -> Self {
+        Self {
+            path: vec![],
+            reason: reason.into(),
+            key: false,
+            location: None,
+        }
+    }
+    pub fn invalid_type(expected: &str, found: &JsonValue) -> Self {
+        Self::new(format!("expected {}, found {}", expected, describe(found)))
+    }
+    pub fn missing_member(identifier: &str) -> Self {
+        Self::new(format!("missing member `{}`", identifier))
+    }
+    pub fn unknown_member(identifier: &str) -> Self {
+        Self::new(format!("unknown member `{}`", identifier)).at_key(identifier)
+    }
+    pub fn unknown_variant(name: &str, expected: &[&str]) -> Self {
+        Self::new(format!(
+            "unknown variant `{}`, expected one of {}",
+            name,
+            expected
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+    pub fn at_member(mut self, identifier: &str) -> Self {
+        self.path.push(identifier.to_string());
+        self
+    }
+    // Like `at_member`, but the error is about the key itself rather than its value.
+    pub fn at_key(mut self, identifier: &str) -> Self {
+        self.key = self.path.is_empty();
+        self.at_member(identifier)
+    }
+    pub fn at_index(mut self, index: usize) -> Self {
+        self.path.push(index.to_string());
+        self
+    }
+    pub fn with_location(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+    // The JSON Pointer of the node that failed to convert, `""` for the root.
+    pub fn pointer(&self) -> String {
+        self.path
+            .iter()
+            .rev()
+            .map(|token| format!("/{}", escape_pointer_token(token)))
+            .collect()
+    }
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+    pub fn location(&self) -> Option<Location> {
+        self.location
+    }
+    pub fn is_key_error(&self) -> bool {
+        self.key
+    }
+}
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at `{}`", self.reason, self.pointer())?;
+        if let Some(location) = self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+impl Error for FromJsonError {}
+
+fn describe(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::String(_) => "a string",
+        JsonValue::Number(_) => "a number",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+        JsonValue::True | JsonValue::False => "a boolean",
+        JsonValue::Null => "null",
+    }
+}
+
+macro_rules! integer {
+    ($($t:ty),*) => {$(
+        impl FromJson for $t {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                match value {
+                    JsonValue::Number(n) => <$t>::try_from(n)
+                        .map_err(|error| FromJsonError::new(error.to_string())),
+                    _ => Err(FromJsonError::invalid_type("an integer", value)),
+                }
+            }
+        }
+    )*};
+}
+integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! integer_to_json {
+    ($($t:ty),*) => {$(
+        impl ToJson for $t {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Number(Number::Integer(*self as isize))
+            }
+        }
+    )*};
+}
+// Only the types that always fit in the `isize` of `Number::Integer`.
+integer_to_json!(i8, i16, i32, isize, u8, u16);
+
+macro_rules! big_integer_to_json {
+    ($($t:ty),*) => {$(
+        impl ToJson for $t {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Number(match isize::try_from(*self) {
+                    Ok(n) => Number::Integer(n),
+                    Err(_) => Number::from(BigInt::from(*self)),
+                })
+            }
+        }
+    )*};
+}
+// A `Number::BigInt` only for the values beyond an `isize`.
+big_integer_to_json!(i64, i128, u32, u64, u128, usize);
+
+macro_rules! float {
+    ($($t:ty),*) => {$(
+        impl FromJson for $t {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                match value {
+                    // Numbers too large for the type round to infinity instead.
+                    JsonValue::Number(n) => match n.as_f64() as $t {
+                        x if x.is_infinite() => Err(FromJsonError::new(
+                            ConversionError::out_of_range(n, stringify!($t)).to_string(),
+                        )),
+                        x => Ok(x),
+                    },
+                    _ => Err(FromJsonError::invalid_type("a number", value)),
+                }
+            }
+        }
+        // Json has no NaN nor infinities: like `JSON.stringify`, they become `null`.
+        impl ToJson for $t {
+            fn to_json(&self) -> JsonValue {
+                match self.is_finite() {
+                    true => JsonValue::Number(Number::Float(*self as f64)),
+                    false => JsonValue::Null,
+                }
+            }
+        }
+    )*};
+}
+float!(f32, f64);
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value
+            .as_bool()
+            .ok_or_else(|| FromJsonError::invalid_type("a boolean", value))
+    }
+}
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue {
+        if *self {
+            JsonValue::True
+        } else {
+            JsonValue::False
+        }
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| FromJsonError::invalid_type("a string", value))
+    }
+}
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.clone())
+    }
+}
+impl ToJson for str {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::String(self.to_string())
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+impl ToJson for JsonValue {
+    fn to_json(&self) -> JsonValue {
+        self.clone()
+    }
+}
+impl FromJson for JsonObject {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Object(object) => Ok(object.clone()),
+            _ => Err(FromJsonError::invalid_type("an object", value)),
+        }
+    }
+}
+impl ToJson for JsonObject {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Object(self.clone())
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+}
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        match self {
+            Some(value) => value.to_json(),
+            None => JsonValue::Null,
+        }
+    }
+}
+
+fn elements<T: FromJson>(value: &JsonValue) -> Result<Vec<T>, FromJsonError> {
+    match value {
+        JsonValue::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| T::from_json(value).map_err(|error| error.at_index(i)))
+            .collect(),
+        _ => Err(FromJsonError::invalid_type("an array", value)),
+    }
+}
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        elements(value)
+    }
+}
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+impl<T: FromJson, const N: usize> FromJson for [T; N] {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let values = elements(value)?;
+        let len = values.len();
+        <[T; N]>::try_from(values).map_err(|_| {
+            FromJsonError::new(format!(
+                "expected an array of {} elements, found {}",
+                N, len
+            ))
+        })
+    }
+}
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> JsonValue {
+        self.as_slice().to_json()
+    }
+}
+
+macro_rules! tuple {
+    ($len:expr => $($name:ident $index:tt),+) => {
+        impl<$($name: FromJson),+> FromJson for ($($name,)+) {
+            fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+                match value {
+                    JsonValue::Array(values) if values.len() == $len => Ok(($(
+                        $name::from_json(&values[$index])
+                            .map_err(|error| error.at_index($index))?,
+                    )+)),
+                    JsonValue::Array(values) => Err(FromJsonError::new(format!(
+                        "expected an array of {} elements, found {}",
+                        $len,
+                        values.len()
+                    ))),
+                    _ => Err(FromJsonError::invalid_type("an array", value)),
+                }
+            }
+        }
+        impl<$($name: ToJson),+> ToJson for ($($name,)+) {
+            fn to_json(&self) -> JsonValue {
+                JsonValue::Array(vec![$(self.$index.to_json()),+])
+            }
+        }
+    };
+}
+tuple!(1 => A 0);
+tuple!(2 => A 0, B 1);
+tuple!(3 => A 0, B 1, C 2);
+tuple!(4 => A 0, B 1, C 2, D 3);
+tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+fn members<T: FromJson>(
+    value: &JsonValue,
+) -> Result<impl Iterator<Item = Result<(String, T), FromJsonError>> + '_, FromJsonError> {
+    match value {
+        JsonValue::Object(object) => Ok(object.iter().map(|member| {
+            T::from_json(member.value())
+                .map(|value| (member.identifier().to_string(), value))
+                .map_err(|error| error.at_member(member.identifier()))
+        })),
+        _ => Err(FromJsonError::invalid_type("an object", value)),
+    }
+}
+fn object<'a, T: ToJson + 'a>(entries: impl Iterator<Item = (&'a String, &'a T)>) -> JsonValue {
+    JsonValue::Object(JsonObject::new(
+        entries
+            .map(|(identifier, value)| Member::new(identifier.as_str(), value.to_json()))
+            .collect(),
+    ))
+}
+#[cfg(feature = "std")]
+impl<T: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, T, S> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        members(value)?.collect()
+    }
+}
+#[cfg(feature = "std")]
+impl<T: ToJson, S: BuildHasher> ToJson for HashMap<String, T, S> {
+    fn to_json(&self) -> JsonValue {
+        object(self.iter())
+    }
+}
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        members(value)?.collect()
+    }
+}
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        object(self.iter())
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Box::new)
+    }
+}
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+impl<T: FromJson> FromJson for Rc<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        T::from_json(value).map(Rc::new)
+    }
+}
+impl<T: ToJson + ?Sized> ToJson for Rc<T> {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> JsonValue {
+        (**self).to_json()
+    }
+}
+
+// Support code for `#[derive(FromJson, ToJson)]` and `json!`, not part of the public API.
+#[doc(hidden)]
+pub mod derive {
+    use super::*;
+
+    pub use alloc::string::ToString;
+    pub use alloc::vec;
+    pub use alloc::vec::Vec;
+
+    pub fn key<K: ToString + ?Sized>(key: &K) -> String {
+        key.to_string()
+    }
+
+    pub fn object(value: &JsonValue) -> Result<&JsonObject, FromJsonError> {
+        match value {
+            JsonValue::Object(object) => Ok(object),
+            _ => Err(FromJsonError::invalid_type("an object", value)),
+        }
+    }
+    pub fn array(value: &JsonValue, len: usize) -> Result<&[JsonValue], FromJsonError> {
+        match value {
+            JsonValue::Array(values) if values.len() == len => Ok(values),
+            JsonValue::Array(values) => Err(FromJsonError::new(format!(
+                "expected an array of {} elements, found {}",
+                len,
+                values.len()
+            ))),
+            _ => Err(FromJsonError::invalid_type("an array", value)),
+        }
+    }
+    pub fn element<T: FromJson>(values: &[JsonValue], index: usize) -> Result<T, FromJsonError> {
+        T::from_json(&values[index]).map_err(|error| error.at_index(index))
+    }
+    // A missing member decodes like `null`, so that `Option` fields may be left out.
+    pub fn member<T: FromJson>(object: &JsonObject, identifier: &str) -> Result<T, FromJsonError> {
+        match object.get(identifier) {
+            Some(value) => T::from_json(value).map_err(|error| error.at_member(identifier)),
+            None => T::from_json(&JsonValue::Null)
+                .map_err(|_| FromJsonError::missing_member(identifier)),
+        }
+    }
+    pub fn member_or_else<T: FromJson>(
+        object: &JsonObject,
+        identifier: &str,
+        default: impl FnOnce() -> T,
+    ) -> Result<T, FromJsonError> {
+        match object.get(identifier) {
+            Some(value) => T::from_json(value).map_err(|error| error.at_member(identifier)),
+            None => Ok(default()),
+        }
+    }
+    pub fn deny_unknown_members(object: &JsonObject, known: &[&str]) -> Result<(), FromJsonError> {
+        match object.iter().find(|m| !known.contains(&m.identifier())) {
+            Some(member) => Err(FromJsonError::unknown_member(member.identifier())),
+            None => Ok(()),
+        }
+    }
+    pub fn remaining_members(object: &JsonObject, known: &[&str]) -> JsonValue {
+        JsonValue::Object(JsonObject::new(
+            object
+                .iter()
+                .filter(|m| !known.contains(&m.identifier()))
+                .cloned()
+                .collect(),
+        ))
+    }
+    pub fn tag<'a>(object: &'a JsonObject, tag: &str) -> Result<&'a str, FromJsonError> {
+        match object.get(tag) {
+            Some(JsonValue::String(name)) => Ok(name),
+            Some(value) => Err(FromJsonError::invalid_type("a string", value).at_member(tag)),
+            None => Err(FromJsonError::missing_member(tag)),
+        }
+    }
+    pub fn content<'a>(
+        object: &'a JsonObject,
+        content: &str,
+    ) -> Result<&'a JsonValue, FromJsonError> {
+        object
+            .get(content)
+            .ok_or_else(|| FromJsonError::missing_member(content))
+    }
+    // The derive rejects the field types it can tell don't convert to an object. Values
+    // of other types that don't are kept under `key` rather than lost.
+    pub fn flatten_into(members: &mut Vec<Member>, key: &str, value: JsonValue) {
+        match value {
+            JsonValue::Object(object) => members.extend(object.members),
+            JsonValue::Null => {}
+            value => members.push(Member::new(key, value)),
+        }
+    }
+    // Decoding reads the content from the members next to the tag, so content that
+    // isn't an object, `null` included, would not read back. Like serde, this panics
+    // on what the derive could not rule out from the variant's type.
+    pub fn tagged(tag: &str, name: &str, content: JsonValue) -> JsonValue {
+        let mut members = vec![Member::new(tag, JsonValue::String(name.to_string()))];
+        match content {
+            JsonValue::Object(object) => members.extend(object.members),
+            content => panic!(
+                "the content of the internally tagged variant `{}` must convert to an \
+                 object, and converted to {}",
+                name,
+                describe(&content)
+            ),
+        }
+        JsonValue::Object(JsonObject::new(members))
+    }
+}
@@ -0,0 +1,102 @@
+use core::fmt;
+use core::ops::Range;
+
+use crate::parsec::JsonError;
+use crate::prelude::*;
+use crate::span::Location;
+
+// A parse error resolved against its source, ready to be shown to a user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub help: Vec<String>,
+    // The bytes of the source the error points at; may be empty.
+    pub range: Range<usize>,
+    pub start: Location,
+    pub end: Location,
+}
+impl Diagnostic {
+    // `message` may carry `Help: ...` lines, as the grammar's errors do.
+    pub fn new(source: &str, range: Range<usize>, message: &str) -> Self {
+        let boundary = |mut offset: usize| {
+            offset = offset.min(source.len());
+            while !source.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        };
+        let range = boundary(range.start)..boundary(range.end.max(range.start));
+        let (help, message): (Vec<&str>, Vec<&str>) = message
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .partition(|line| line.starts_with("Help:"));
+        Self {
+            message: message.join(" "),
+            help: help
+                .into_iter()
+                .map(|line| line["Help:".len()..].trim().to_string())
+                .collect(),
+            start: Location::from_offset(source, range.start),
+            end: Location::from_offset(source, range.end),
+            range,
+        }
+    }
+    pub fn from_error(source: &str, error: &JsonError<'_>) -> Self {
+        let pos = error.pos();
+        let range = match error.error().pos_range() {
+            Some(range) => pos + range.start..pos + range.end,
+            None => pos..pos,
+        };
+        Self::new(source, range, &error.error().message())
+    }
+    // The error as rustc shows its own, with the offending line of `source` underlined:
+    //
+    //     error: Expected `:` found `1`
+    //      --> data.json:1:6
+    //       |
+    //     1 | {"a" 1}
+    //       |      ^
+    pub fn render(&self, name: &str, source: &str) -> String {
+        let line = source.lines().nth(self.start.line - 1).unwrap_or("");
+        let gutter = " ".repeat(self.start.line.to_string().len());
+        // Keep tabs so that the carets line up with the source line.
+        let indent: String = line
+            .chars()
+            .take(self.start.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = if self.end.line == self.start.line {
+            self.end.column.saturating_sub(self.start.column)
+        } else {
+            line.chars().count() + 1 - self.start.column
+        };
+        let mut out = format!(
+            "error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.message,
+            gutter,
+            name,
+            self.start.line,
+            self.start.column,
+            gutter,
+            self.start.line,
+            line,
+            gutter,
+            indent,
+            "^".repeat(width.max(1)),
+        );
+        for help in &self.help {
+            out.push_str(&format!("{} = help: {}\n", gutter, help));
+        }
+        out
+    }
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.start.line, self.start.column, self.message
+        )
+    }
+}
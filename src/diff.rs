@@ -0,0 +1,341 @@
+use crate::prelude::*;
+use crate::{escape_pointer_token, pointer_tokens, JsonObject, JsonValue, Member, Number};
+
+// Past this many pairs of elements, the middle of two arrays that differ is compared
+// by index instead of along their longest common subsequence.
+const MAX_LCS_CELLS: usize = 1 << 20;
+
+// How `diff` pairs up the elements of two arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayDiff {
+    // Element `i` of one against element `i` of the other.
+    Index,
+    // Along their longest common subsequence, so that inserting one element in the
+    // middle shows as one addition rather than a change of everything after it.
+    #[default]
+    Lcs,
+    // As multisets: elements are matched wherever they are, and order is ignored.
+    Set,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    pub arrays: ArrayDiff,
+    // JSON Pointers of values to leave out, with everything they hold. A `*` token
+    // matches any key or index, e.g. `/items/*/updatedAt`.
+    pub ignore: Vec<String>,
+    // Numbers at most this far apart are equal.
+    pub tolerance: f64,
+}
+
+// One difference, at the JSON Pointer of the value it concerns. Array indices are
+// those of JSON Patch: where the change applies once the changes before it are made.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: JsonValue,
+    },
+    Removed {
+        path: String,
+        value: JsonValue,
+    },
+    Modified {
+        path: String,
+        old: JsonValue,
+        new: JsonValue,
+    },
+}
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added { path, .. } | Self::Removed { path, .. } | Self::Modified { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+// The changes that turn `old` into `new`. Object members are matched by key whatever
+// their order.
+pub fn diff(old: &JsonValue, new: &JsonValue, options: &DiffOptions) -> Vec<Change> {
+    let mut differ = Differ {
+        options,
+        ignore: options
+            .ignore
+            .iter()
+            .filter_map(|pointer| Some(pointer_tokens(pointer)?.collect()))
+            .collect(),
+        path: vec![],
+        changes: vec![],
+    };
+    if !differ.ignored() {
+        differ.value(old, new);
+    }
+    differ.changes
+}
+
+// `changes` as an RFC 6902 JSON Patch document.
+pub fn to_patch(changes: &[Change]) -> JsonValue {
+    let operation = |op: &str, path: &str, value: Option<&JsonValue>| {
+        let mut members = vec![
+            Member::new("op", JsonValue::String(op.to_string())),
+            Member::new("path", JsonValue::String(path.to_string())),
+        ];
+        members.extend(value.map(|value| Member::new("value", value.clone())));
+        JsonValue::Object(JsonObject::new(members))
+    };
+    JsonValue::Array(
+        changes
+            .iter()
+            .map(|change| match change {
+                Change::Added { path, value } => operation("add", path, Some(value)),
+                Change::Removed { path, .. } => operation("remove", path, None),
+                Change::Modified { path, new, .. } => operation("replace", path, Some(new)),
+            })
+            .collect(),
+    )
+}
+
+struct Differ<'o> {
+    options: &'o DiffOptions,
+    ignore: Vec<Vec<String>>,
+    // The unescaped tokens of the pointer of the values being compared.
+    path: Vec<String>,
+    changes: Vec<Change>,
+}
+impl<'o> Differ<'o> {
+    fn pointer(&self) -> String {
+        self.path
+            .iter()
+            .map(|token| format!("/{}", escape_pointer_token(token)))
+            .collect()
+    }
+    fn ignored(&self) -> bool {
+        self.ignore.iter().any(|pattern| {
+            pattern.len() == self.path.len()
+                && pattern
+                    .iter()
+                    .zip(&self.path)
+                    .all(|(pattern, token)| pattern == "*" || pattern == token)
+        })
+    }
+    // Runs `f` one level down, unless that path is ignored.
+    fn at(&mut self, token: impl ToString, f: impl FnOnce(&mut Self)) {
+        self.path.push(token.to_string());
+        if !self.ignored() {
+            f(self);
+        }
+        self.path.pop();
+    }
+    // Whether `old` and `new`, one level down, are equal or ignored. When there is no
+    // `new` they are only equal if ignored.
+    fn equal_at(&mut self, token: impl ToString, old: &JsonValue, new: Option<&JsonValue>) -> bool {
+        self.path.push(token.to_string());
+        let equal = self.ignored() || new.is_some_and(|new| self.equal(old, new));
+        self.path.pop();
+        equal
+    }
+    fn added(&mut self, value: &JsonValue) {
+        let path = self.pointer();
+        self.changes.push(Change::Added {
+            path,
+            value: value.clone(),
+        });
+    }
+    fn removed(&mut self, value: &JsonValue) {
+        let path = self.pointer();
+        self.changes.push(Change::Removed {
+            path,
+            value: value.clone(),
+        });
+    }
+
+    fn value(&mut self, old: &JsonValue, new: &JsonValue) {
+        match (old, new) {
+            (JsonValue::Object(old), JsonValue::Object(new)) => self.object(old, new),
+            (JsonValue::Array(old), JsonValue::Array(new)) => match self.options.arrays {
+                ArrayDiff::Index => self.by_index(old, new, 0),
+                ArrayDiff::Lcs => self.by_lcs(old, new),
+                ArrayDiff::Set => self.as_set(old, new),
+            },
+            _ if self.equal(old, new) => {}
+            _ => {
+                let path = self.pointer();
+                self.changes.push(Change::Modified {
+                    path,
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+    fn object(&mut self, old: &JsonObject, new: &JsonObject) {
+        for member in old.iter() {
+            match new.get(member.identifier()) {
+                Some(value) => self.at(member.identifier(), |d| d.value(member.value(), value)),
+                None => self.at(member.identifier(), |d| d.removed(member.value())),
+            }
+        }
+        for member in new.iter() {
+            if old.get(member.identifier()).is_none() {
+                self.at(member.identifier(), |d| d.added(member.value()));
+            }
+        }
+    }
+    // Pairs `old[i]` with `new[i]`; `start` is the index both slices begin at.
+    fn by_index(&mut self, old: &[JsonValue], new: &[JsonValue], start: usize) {
+        for (i, (old, new)) in old.iter().zip(new).enumerate() {
+            self.at(start + i, |d| d.value(old, new));
+        }
+        // From the end, so that the indices of the removals stay valid.
+        for i in (new.len()..old.len()).rev() {
+            self.at(start + i, |d| d.removed(&old[i]));
+        }
+        for (i, value) in new.iter().enumerate().skip(old.len()) {
+            self.at(start + i, |d| d.added(value));
+        }
+    }
+    fn by_lcs(&mut self, old: &[JsonValue], new: &[JsonValue]) {
+        let prefix = (0..old.len().min(new.len()))
+            .take_while(|&i| self.equal_at(i, &old[i], Some(&new[i])))
+            .count();
+        let suffix = (1..=(old.len() - prefix).min(new.len() - prefix))
+            .take_while(|&i| {
+                self.equal_at(
+                    old.len() - i,
+                    &old[old.len() - i],
+                    Some(&new[new.len() - i]),
+                )
+            })
+            .count();
+        let (old, new) = (
+            &old[prefix..old.len() - suffix],
+            &new[prefix..new.len() - suffix],
+        );
+        if old.len() * new.len() > MAX_LCS_CELLS {
+            return self.by_index(old, new, prefix);
+        }
+        let width = new.len() + 1;
+        let mut equal = vec![false; old.len() * new.len()];
+        for (i, a) in old.iter().enumerate() {
+            for (j, b) in new.iter().enumerate() {
+                equal[i * new.len() + j] = self.equal_at(prefix + i, a, Some(b));
+            }
+        }
+        // `longest[i * width + j]`: the length of the LCS of `old[i..]` and `new[j..]`.
+        let mut longest = vec![0u32; (old.len() + 1) * width];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                longest[i * width + j] = match equal[i * new.len() + j] {
+                    true => longest[(i + 1) * width + j + 1] + 1,
+                    false => longest[(i + 1) * width + j].max(longest[i * width + j + 1]),
+                };
+            }
+        }
+        let (mut i, mut j, mut position) = (0, 0, prefix);
+        while i < old.len() || j < new.len() {
+            if i < old.len() && j < new.len() && equal[i * new.len() + j] {
+                i += 1;
+                j += 1;
+                position += 1;
+                continue;
+            }
+            // A run of removals and additions between two common elements; they are
+            // paired up first, so that a changed element shows as a modification.
+            let (start_i, start_j) = (i, j);
+            while (i < old.len() || j < new.len())
+                && !(i < old.len() && j < new.len() && equal[i * new.len() + j])
+            {
+                if j == new.len()
+                    || (i < old.len() && longest[(i + 1) * width + j] >= longest[i * width + j + 1])
+                {
+                    i += 1;
+                } else {
+                    j += 1;
+                }
+            }
+            let (removed, added) = (&old[start_i..i], &new[start_j..j]);
+            let paired = removed.len().min(added.len());
+            for (old, new) in removed.iter().zip(added) {
+                self.at(position, |d| d.value(old, new));
+                position += 1;
+            }
+            for value in &removed[paired..] {
+                self.at(position, |d| d.removed(value));
+            }
+            for value in &added[paired..] {
+                self.at(position, |d| d.added(value));
+                position += 1;
+            }
+        }
+    }
+    fn as_set(&mut self, old: &[JsonValue], new: &[JsonValue]) {
+        let mut matched = vec![false; old.len()];
+        let mut unmatched = vec![];
+        for value in new {
+            let found =
+                (0..old.len()).find(|&i| !matched[i] && self.equal_at(i, &old[i], Some(value)));
+            match found {
+                Some(i) => matched[i] = true,
+                None => unmatched.push(value),
+            }
+        }
+        for i in (0..old.len()).rev().filter(|&i| !matched[i]) {
+            self.at(i, |d| d.removed(&old[i]));
+        }
+        let kept = matched.iter().filter(|&&matched| matched).count();
+        for (i, value) in unmatched.into_iter().enumerate() {
+            self.at(kept + i, |d| d.added(value));
+        }
+    }
+
+    // Equality under the options: ignored paths, the numeric tolerance and, for sets,
+    // the order of array elements are left out.
+    fn equal(&mut self, old: &JsonValue, new: &JsonValue) -> bool {
+        match (old, new) {
+            (JsonValue::Number(Number::Integer(a)), JsonValue::Number(Number::Integer(b))) => {
+                (*a as i128 - *b as i128).unsigned_abs() as f64 <= self.options.tolerance
+            }
+            (JsonValue::Number(a), JsonValue::Number(b)) => {
+                // Through doubles, which would blur big integers and decimals that
+                // are only equal once rounded.
+                a == b
+                    || (self.options.tolerance > 0.0
+                        && (a.as_f64() - b.as_f64()).abs() <= self.options.tolerance)
+            }
+            (JsonValue::Object(old), JsonValue::Object(new)) => {
+                old.iter().all(|member| {
+                    self.equal_at(
+                        member.identifier(),
+                        member.value(),
+                        new.get(member.identifier()),
+                    )
+                }) && new.iter().all(|member| {
+                    old.get(member.identifier()).is_some()
+                        || self.equal_at(member.identifier(), member.value(), None)
+                })
+            }
+            (JsonValue::Array(old), JsonValue::Array(new))
+                if self.options.arrays == ArrayDiff::Set =>
+            {
+                let mut matched = vec![false; new.len()];
+                old.len() == new.len()
+                    && old.iter().enumerate().all(|(i, value)| {
+                        let found = (0..new.len())
+                            .find(|&j| !matched[j] && self.equal_at(i, value, Some(&new[j])));
+                        found.map(|j| matched[j] = true).is_some()
+                    })
+            }
+            (JsonValue::Array(old), JsonValue::Array(new)) => {
+                old.len() == new.len()
+                    && old
+                        .iter()
+                        .zip(new)
+                        .enumerate()
+                        .all(|(i, (old, new))| self.equal_at(i, old, Some(new)))
+            }
+            _ => old == new,
+        }
+    }
+}
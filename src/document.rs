@@ -0,0 +1,320 @@
+use alloc::borrow::Cow;
+use core::cell::{Cell, RefCell};
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter;
+
+use crate::footprint::Footprint;
+use crate::prelude::*;
+use crate::serialize::unescape;
+use crate::{
+    json_with, parse_with, Builder, Diagnostic, JsonObject, JsonValue, Member, Number, ParseOptions,
+};
+
+// Every node of a `Document` lives in a single vector. Containers point at the
+// contiguous run of slots holding their children; object children alternate between
+// `Key` and value slots. Strings and keys are borrowed straight from the parsed input,
+// escapes and all, and decoded when read. Numbers sit in a vector of their own,
+// keeping every slot small, which limits a document to `u32::MAX` slots and numbers.
+// Dropping a document frees those two vectors whatever its size, and the digits of
+// each `BigInt` or `Decimal` when `exact_numbers` kept any.
+#[derive(Debug, Clone, Copy)]
+enum Node<'a> {
+    Null,
+    True,
+    False,
+    Number(u32),
+    String(&'a str),
+    Key(&'a str),
+    Array { start: u32, len: u32 },
+    Object { start: u32, len: u32 },
+}
+
+pub struct Document<'a> {
+    nodes: Vec<Node<'a>>,
+    numbers: Vec<Number>,
+}
+impl<'a> Document<'a> {
+    pub fn parse(input: &'a str) -> Option<Self> {
+        let builder = DocumentBuilder::default();
+        let root = json_with(&builder, input)?;
+        Self::new(builder, root).ok()
+    }
+    // Like `parse`, with options, returning what went wrong instead of printing it.
+    pub fn try_parse(input: &'a str, options: &ParseOptions) -> Result<Self, Diagnostic> {
+        let builder = DocumentBuilder::default();
+        let root = parse_with(&builder, input, options)?;
+        Self::new(builder, root).map_err(|message| Diagnostic::new(input, 0..0, message))
+    }
+    fn new(builder: DocumentBuilder<'a>, root: Node<'a>) -> Result<Self, &'static str> {
+        if builder.overflowed.get() {
+            return Err("The document has too many values to index with 32 bits");
+        }
+        let mut nodes = builder.nodes.into_inner();
+        nodes.push(root);
+        Ok(Self {
+            nodes,
+            numbers: builder.numbers.into_inner(),
+        })
+    }
+    pub fn root(&self) -> ValueRef<'_, 'a> {
+        ValueRef {
+            doc: self,
+            node: self.nodes.last().expect("a document always has a root"),
+        }
+    }
+    // Number of arena slots used by the document, keys included.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn get(&self, identifier: &str) -> Option<ValueRef<'_, 'a>> {
+        self.root().get(identifier)
+    }
+    // The heap memory held by the document. Strings and keys borrow the input, which
+    // isn't counted.
+    pub fn footprint(&self) -> Footprint {
+        let mut footprint = Footprint::default();
+        footprint.nodes = footprint.vec::<Node<'a>>(self.nodes.len(), self.nodes.capacity());
+        footprint.numbers = footprint.vec::<Number>(self.numbers.len(), self.numbers.capacity());
+        for number in &self.numbers {
+            footprint.number(number.heap_size());
+        }
+        footprint
+    }
+    pub fn to_value(&self) -> JsonValue {
+        self.root().to_value()
+    }
+}
+impl<'a> fmt::Debug for Document<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.root(), f)
+    }
+}
+
+#[derive(Default)]
+struct DocumentBuilder<'a> {
+    nodes: RefCell<Vec<Node<'a>>>,
+    numbers: RefCell<Vec<Number>>,
+    // Set when an index or length didn't fit a `u32`, failing the parse at its end.
+    overflowed: Cell<bool>,
+}
+impl<'a> DocumentBuilder<'a> {
+    fn index(&self, n: usize) -> u32 {
+        u32::try_from(n).unwrap_or_else(|_| {
+            self.overflowed.set(true);
+            0
+        })
+    }
+    fn push_run(&self, run: impl IntoIterator<Item = Node<'a>>) -> u32 {
+        let mut nodes = self.nodes.borrow_mut();
+        let start = self.index(nodes.len());
+        nodes.extend(run);
+        // The whole run must be addressable too.
+        self.index(nodes.len());
+        start
+    }
+}
+impl<'a> Builder<'a> for DocumentBuilder<'a> {
+    type Value = Node<'a>;
+    type Member = (&'a str, Node<'a>);
+    type Object = Node<'a>;
+
+    fn number(&self, n: Number) -> Node<'a> {
+        let mut numbers = self.numbers.borrow_mut();
+        numbers.push(n);
+        Node::Number(self.index(numbers.len() - 1))
+    }
+    fn string(&self, s: &'a str) -> Node<'a> {
+        Node::String(s)
+    }
+    fn boolean(&self, b: bool) -> Node<'a> {
+        if b {
+            Node::True
+        } else {
+            Node::False
+        }
+    }
+    fn null(&self) -> Node<'a> {
+        Node::Null
+    }
+    fn array(&self, items: Vec<Node<'a>>) -> Node<'a> {
+        let len = self.index(items.len());
+        let start = self.push_run(items);
+        Node::Array { start, len }
+    }
+    fn member(&self, identifier: &'a str, value: Node<'a>) -> (&'a str, Node<'a>) {
+        (identifier, value)
+    }
+    fn object(&self, members: Vec<(&'a str, Node<'a>)>) -> Node<'a> {
+        let len = self.index(members.len());
+        let start = self.push_run(members.into_iter().flat_map(|(identifier, value)| {
+            iter::once(Node::Key(identifier)).chain(iter::once(value))
+        }));
+        Node::Object { start, len }
+    }
+    fn object_value(&self, object: Node<'a>) -> Node<'a> {
+        object
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ValueRef<'d, 'a> {
+    doc: &'d Document<'a>,
+    node: &'d Node<'a>,
+}
+impl<'d, 'a> ValueRef<'d, 'a> {
+    fn at(&self, slot: u32) -> ValueRef<'d, 'a> {
+        ValueRef {
+            doc: self.doc,
+            node: &self.doc.nodes[slot as usize],
+        }
+    }
+    // Finds a member by its decoded key, as `JsonValue::get` does.
+    pub fn get(&self, identifier: &str) -> Option<ValueRef<'d, 'a>> {
+        self.members()
+            .find(|(key, _)| *key == identifier)
+            .map(|(_, value)| value)
+    }
+    pub fn get_index(&self, index: usize) -> Option<ValueRef<'d, 'a>> {
+        match *self.node {
+            Node::Array { start, len } if index < len as usize => {
+                Some(self.at(start + index as u32))
+            }
+            _ => None,
+        }
+    }
+    // Iterates over the elements of an array, or nothing for any other value.
+    pub fn iter(&self) -> Elements<'d, 'a> {
+        let (start, len) = match *self.node {
+            Node::Array { start, len } => (start, len),
+            _ => (0, 0),
+        };
+        Elements {
+            value: *self,
+            next: start,
+            end: start + len,
+        }
+    }
+    // Iterates over the members of an object, or nothing for any other value.
+    pub fn members(&self) -> Members<'d, 'a> {
+        let (start, len) = match *self.node {
+            Node::Object { start, len } => (start, len),
+            _ => (0, 0),
+        };
+        Members {
+            value: *self,
+            next: start,
+            end: start + 2 * len,
+        }
+    }
+    pub fn len(&self) -> usize {
+        match *self.node {
+            Node::Array { len, .. } | Node::Object { len, .. } => len as usize,
+            _ => 0,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    // The decoded string, borrowed from the input unless it holds escapes.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match *self.node {
+            Node::String(s) => Some(unescape(s)),
+            _ => None,
+        }
+    }
+    // The string as written in the input, escapes and all.
+    pub fn as_raw_str(&self) -> Option<&'a str> {
+        match *self.node {
+            Node::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_number(&self) -> Option<&'d Number> {
+        match *self.node {
+            Node::Number(i) => Some(&self.doc.numbers[i as usize]),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self.node {
+            Node::True => Some(true),
+            Node::False => Some(false),
+            _ => None,
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self.node, Node::Null)
+    }
+    pub fn is_array(&self) -> bool {
+        matches!(self.node, Node::Array { .. })
+    }
+    pub fn is_object(&self) -> bool {
+        matches!(self.node, Node::Object { .. })
+    }
+    pub fn to_value(&self) -> JsonValue {
+        match *self.node {
+            Node::Null => JsonValue::Null,
+            Node::True => JsonValue::True,
+            Node::False => JsonValue::False,
+            Node::Number(i) => JsonValue::Number(self.doc.numbers[i as usize].clone()),
+            Node::String(s) => JsonValue::String(unescape(s).into_owned()),
+            Node::Array { .. } => JsonValue::Array(self.iter().map(|v| v.to_value()).collect()),
+            Node::Object { .. } => JsonValue::Object(JsonObject::new(
+                self.members()
+                    .map(|(key, value)| Member::new(&*key, value.to_value()))
+                    .collect(),
+            )),
+            Node::Key(_) => unreachable!("keys are only reachable through `members()`"),
+        }
+    }
+}
+impl<'d, 'a> fmt::Debug for ValueRef<'d, 'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self.node {
+            Node::Array { .. } => f.debug_list().entries(self.iter()).finish(),
+            Node::Object { .. } => f.debug_map().entries(self.members()).finish(),
+            Node::Number(i) => write!(f, "{}", self.doc.numbers[i as usize]),
+            Node::String(s) | Node::Key(s) => write!(f, "{:?}", unescape(s)),
+            Node::True => write!(f, "true"),
+            Node::False => write!(f, "false"),
+            Node::Null => write!(f, "null"),
+        }
+    }
+}
+
+pub struct Elements<'d, 'a> {
+    value: ValueRef<'d, 'a>,
+    next: u32,
+    end: u32,
+}
+impl<'d, 'a> Iterator for Elements<'d, 'a> {
+    type Item = ValueRef<'d, 'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 1;
+        Some(self.value.at(self.next - 1))
+    }
+}
+
+pub struct Members<'d, 'a> {
+    value: ValueRef<'d, 'a>,
+    next: u32,
+    end: u32,
+}
+impl<'d, 'a> Iterator for Members<'d, 'a> {
+    // Keys are decoded, and borrowed from the input unless they hold escapes.
+    type Item = (Cow<'a, str>, ValueRef<'d, 'a>);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.end {
+            return None;
+        }
+        self.next += 2;
+        match self.value.at(self.next - 2).node {
+            Node::Key(key) => Some((unescape(key), self.value.at(self.next - 1))),
+            _ => unreachable!("object slots always start with a key"),
+        }
+    }
+}
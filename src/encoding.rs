@@ -0,0 +1,139 @@
+use alloc::borrow::Cow;
+use core::char::decode_utf16;
+use core::fmt;
+
+use crate::prelude::*;
+use crate::Diagnostic;
+
+// The encodings RFC 8259 section 8.1 and its predecessors allow for json text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16LE",
+            Self::Utf16Be => "UTF-16BE",
+            Self::Utf32Le => "UTF-32LE",
+            Self::Utf32Be => "UTF-32BE",
+        })
+    }
+}
+
+// The encoding of `bytes` and the length of its byte order mark. Without a mark, the
+// encoding is told by where the zero bytes of the first ASCII characters fall, as
+// RFC 4627 section 3 describes: `00 00 00 7b` is UTF-32BE, `7b 00` UTF-16LE, and so on.
+pub fn detect(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+        [0x00, 0x00, 0xfe, 0xff, ..] => (Encoding::Utf32Be, 4),
+        [0xff, 0xfe, 0x00, 0x00, ..] => (Encoding::Utf32Le, 4),
+        [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+        [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+        [0, 0, 0, b, ..] if *b != 0 => (Encoding::Utf32Be, 0),
+        [a, 0, 0, 0, ..] if *a != 0 => (Encoding::Utf32Le, 0),
+        [0, b, ..] if *b != 0 => (Encoding::Utf16Be, 0),
+        [a, 0, ..] if *a != 0 => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+// `bytes` as text, without its byte order mark. Valid UTF-8 without a mark is
+// borrowed. Malformed input is an error pointing at its first bad byte, or, when
+// `lossy`, has each bad sequence replaced by U+FFFD.
+pub fn decode(bytes: &[u8], lossy: bool) -> Result<Cow<'_, str>, Diagnostic> {
+    let (encoding, bom) = detect(bytes);
+    let (text, error) = match encoding {
+        Encoding::Utf8 => decode_utf8(&bytes[bom..]),
+        Encoding::Utf16Le => decode_units(&bytes[bom..], 2, |b| {
+            u16::from_le_bytes([b[0], b[1]]) as u32
+        }),
+        Encoding::Utf16Be => decode_units(&bytes[bom..], 2, |b| {
+            u16::from_be_bytes([b[0], b[1]]) as u32
+        }),
+        Encoding::Utf32Le => decode_units(&bytes[bom..], 4, |b| {
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        }),
+        Encoding::Utf32Be => decode_units(&bytes[bom..], 4, |b| {
+            u32::from_be_bytes([b[0], b[1], b[2], b[3]])
+        }),
+    };
+    match error {
+        Some(error) if !lossy => Err(Diagnostic::new(
+            &text,
+            error.at..error.at + char::REPLACEMENT_CHARACTER.len_utf8(),
+            &format!("Invalid {} at byte {}", encoding, bom + error.offset),
+        )),
+        _ => Ok(text),
+    }
+}
+
+// Where decoding first went wrong: `offset` in the input, `at` in the decoded text.
+struct Malformed {
+    offset: usize,
+    at: usize,
+}
+
+fn decode_utf8(bytes: &[u8]) -> (Cow<'_, str>, Option<Malformed>) {
+    match core::str::from_utf8(bytes) {
+        Ok(text) => (Cow::Borrowed(text), None),
+        Err(error) => {
+            let offset = error.valid_up_to();
+            let error = Malformed { offset, at: offset };
+            (String::from_utf8_lossy(bytes), Some(error))
+        }
+    }
+}
+
+// Decodes code units of `width` bytes, UTF-16 surrogate pairs included.
+fn decode_units(
+    bytes: &[u8],
+    width: usize,
+    unit: impl Fn(&[u8]) -> u32,
+) -> (Cow<'static, str>, Option<Malformed>) {
+    let mut text = String::with_capacity(bytes.len() / width);
+    let mut error = None;
+    let mut malformed = |text: &mut String, offset: usize| {
+        error.get_or_insert(Malformed {
+            offset,
+            at: text.len(),
+        });
+        text.push(char::REPLACEMENT_CHARACTER);
+    };
+    let units = bytes.chunks_exact(width);
+    let trailing = units.remainder().len();
+    let mut offset = 0;
+    if width == 2 {
+        for c in decode_utf16(units.map(|b| unit(b) as u16)) {
+            match c {
+                Ok(c) => {
+                    offset += width * c.len_utf16();
+                    text.push(c);
+                }
+                Err(_) => {
+                    malformed(&mut text, offset);
+                    offset += width;
+                }
+            }
+        }
+    } else {
+        for c in units.map(|b| char::from_u32(unit(b))) {
+            match c {
+                Some(c) => text.push(c),
+                None => malformed(&mut text, offset),
+            }
+            offset += width;
+        }
+    }
+    // A code unit cut short at the end.
+    if trailing > 0 {
+        malformed(&mut text, offset);
+    }
+    (Cow::Owned(text), error)
+}
@@ -0,0 +1,240 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::slice;
+
+use crate::document::Document;
+use crate::{encoding, parse_value, Diagnostic, JsonValue, ParseOptions};
+
+// A json file, read into memory by `open`, or memory-mapped by `map` when it is a
+// regular file on a 64-bit unix system, where `off_t` is 64 bits wide. Values borrowed
+// from the file, like those of `document`, can't outlive it, so they can't outlive the
+// mapping either:
+//
+//     let file = unsafe { JsonFile::map("big.json", &ParseOptions::default())? };
+//     let document = file.document(&ParseOptions::default())?;
+pub struct JsonFile {
+    contents: Contents,
+    text: Text,
+}
+
+enum Contents {
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    Mapped(mmap::Mapping),
+    Read(Vec<u8>),
+}
+
+// Where the text of the file is: in the contents after a UTF-8 byte order mark, or
+// decoded from another encoding.
+enum Text {
+    Contents { start: usize },
+    Decoded(String),
+}
+
+impl JsonFile {
+    // Reads `path` and checks that it holds UTF-8, UTF-16 or UTF-32 text, decoding
+    // the latter two and repairing malformed text with `options.lossy_decoding`.
+    pub fn open(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self, FileError> {
+        Self::new(Contents::read(path.as_ref())?, options)
+    }
+    /// Like `open`, memory-mapping the file when it can, and reading it when it can't:
+    /// pipes, devices, files that report no size, like those of `/proc`, and files that
+    /// can't be mapped, like those of `/sys`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not change while the `JsonFile` lives. The mapping is private,
+    /// but pages of it that nothing wrote to still show what other processes write to
+    /// the file, so `text` could stop being UTF-8 after `map` checked it, and a file
+    /// truncated meanwhile makes reading past its new end fail with `SIGBUS`.
+    pub unsafe fn map(path: impl AsRef<Path>, options: &ParseOptions) -> Result<Self, FileError> {
+        Self::new(Contents::map(path.as_ref())?, options)
+    }
+    fn new(contents: Contents, options: &ParseOptions) -> Result<Self, FileError> {
+        let bytes = contents.bytes();
+        let text = match encoding::decode(bytes, options.lossy_decoding)? {
+            Cow::Borrowed(text) => Text::Contents {
+                start: text.as_ptr() as usize - bytes.as_ptr() as usize,
+            },
+            Cow::Owned(text) => Text::Decoded(text),
+        };
+        Ok(Self { contents, text })
+    }
+    // Whether the file is memory-mapped rather than read into memory.
+    pub fn is_mapped(&self) -> bool {
+        match self.contents {
+            #[cfg(all(unix, target_pointer_width = "64"))]
+            Contents::Mapped(_) => true,
+            Contents::Read(_) => false,
+        }
+    }
+    pub fn bytes(&self) -> &[u8] {
+        self.contents.bytes()
+    }
+    pub fn text(&self) -> &str {
+        match &self.text {
+            Text::Contents { start } => {
+                let bytes = &self.contents.bytes()[*start..];
+                // `new` checked that these bytes are UTF-8 before borrowing them, and
+                // the caller of `map` keeps a mapping from changing since.
+                unsafe { std::str::from_utf8_unchecked(bytes) }
+            }
+            Text::Decoded(text) => text,
+        }
+    }
+    // The file as a `Document`, whose strings without escapes point into the mapping.
+    pub fn document(&self, options: &ParseOptions) -> Result<Document<'_>, Diagnostic> {
+        Document::try_parse(self.text(), options)
+    }
+    pub fn value(&self, options: &ParseOptions) -> Result<JsonValue, Diagnostic> {
+        parse_value(self.text(), options)
+    }
+}
+
+impl Contents {
+    fn read(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        Self::read_from(file)
+    }
+    fn read_from(mut file: File) -> io::Result<Self> {
+        // Only a hint: files of `/proc` report no size, and those of `/sys` 4096 bytes.
+        let metadata = file.metadata()?;
+        let mut bytes = Vec::with_capacity(match metadata.is_file() {
+            true => metadata.len() as usize,
+            false => 0,
+        });
+        file.read_to_end(&mut bytes)?;
+        Ok(Contents::Read(bytes))
+    }
+    // The caller of `JsonFile::map` keeps the file from changing.
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    fn map(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        // Files reporting no size can't be mapped whole, and special files that report
+        // one may not be mappable at all, failing with `ENODEV`.
+        if metadata.is_file() && metadata.len() > 0 {
+            if let Ok(mapping) = mmap::Mapping::new(&file, metadata.len()) {
+                return Ok(Contents::Mapped(mapping));
+            }
+        }
+        Self::read_from(file)
+    }
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    fn map(path: &Path) -> io::Result<Self> {
+        Self::read(path)
+    }
+    fn bytes(&self) -> &[u8] {
+        match self {
+            #[cfg(all(unix, target_pointer_width = "64"))]
+            Contents::Mapped(mapping) => mapping.bytes(),
+            Contents::Read(bytes) => bytes,
+        }
+    }
+}
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod mmap {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::io;
+    use std::os::raw::c_int;
+    use std::os::unix::io::AsRawFd;
+    use std::ptr;
+
+    use super::slice;
+
+    // The same on Linux, the BSDs and macOS.
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    pub(super) struct Mapping {
+        ptr: *mut c_void,
+        len: usize,
+    }
+    impl Mapping {
+        pub(super) fn new(file: &File, len: u64) -> io::Result<Self> {
+            let len = len as usize;
+            let ptr = unsafe {
+                mmap(
+                    ptr::null_mut(),
+                    len,
+                    PROT_READ,
+                    MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            // `MAP_FAILED`.
+            if ptr as isize == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { ptr, len })
+        }
+        pub(super) fn bytes(&self) -> &[u8] {
+            unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            unsafe { munmap(self.ptr, self.len) };
+        }
+    }
+    // The mapping is read-only and owned by one `Mapping`; `JsonFile::map`'s caller
+    // keeps the file under it from changing.
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+}
+
+// Reads and parses `path`. `JsonFile::map` maps it instead, for files too large to
+// copy into memory.
+pub fn parse_file(path: impl AsRef<Path>, options: &ParseOptions) -> Result<JsonValue, FileError> {
+    Ok(JsonFile::open(path, options)?.value(options)?)
+}
+
+#[derive(Debug)]
+pub enum FileError {
+    Io(io::Error),
+    Invalid(Diagnostic),
+}
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Invalid(diagnostic) => write!(f, "{}", diagnostic),
+        }
+    }
+}
+impl Error for FileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Invalid(_) => None,
+        }
+    }
+}
+impl From<io::Error> for FileError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+impl From<Diagnostic> for FileError {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self::Invalid(diagnostic)
+    }
+}
@@ -0,0 +1,203 @@
+use alloc::sync::Arc;
+use core::alloc::Layout;
+use core::mem;
+use core::ops::Add;
+
+use crate::prelude::*;
+use crate::{JsonValue, Key, Member};
+
+// Heap bytes held by a parsed value, by what holds them. Sizes are the ones asked of
+// the allocator, so they include spare capacity, which is also totalled in `overhead`;
+// the allocator's own bookkeeping is not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Footprint {
+    // String values.
+    pub strings: usize,
+    // Object keys, with the reference counts of shared keys, which count once.
+    pub keys: usize,
+    // The elements of arrays.
+    pub arrays: usize,
+    // The members of objects.
+    pub members: usize,
+    // The limbs of `BigInt` and `Decimal` numbers.
+    pub numbers: usize,
+    // The slots of a `Document`.
+    pub nodes: usize,
+    // Spare capacity and reference counts, already included above.
+    pub overhead: usize,
+    // Heap blocks holding all of the above.
+    pub allocations: usize,
+}
+impl Footprint {
+    pub fn total(&self) -> usize {
+        self.strings + self.keys + self.arrays + self.members + self.numbers + self.nodes
+    }
+    pub(crate) fn of_value(value: &JsonValue) -> Self {
+        let mut footprint = Self::default();
+        footprint.add_value(value, &mut HashSet::new());
+        footprint
+    }
+    pub(crate) fn vec<T>(&mut self, len: usize, capacity: usize) -> usize {
+        self.overhead += (capacity - len) * mem::size_of::<T>();
+        self.allocations += (capacity > 0) as usize;
+        capacity * mem::size_of::<T>()
+    }
+    pub(crate) fn number(&mut self, heap_size: usize) {
+        self.numbers += heap_size;
+        self.allocations += (heap_size > 0) as usize;
+    }
+
+    fn add_value(&mut self, value: &JsonValue, keys: &mut HashSet<*const u8>) {
+        match value {
+            JsonValue::String(s) => self.strings += self.vec::<u8>(s.len(), s.capacity()),
+            JsonValue::Number(n) => self.number(n.heap_size()),
+            JsonValue::Array(values) => {
+                self.arrays += self.vec::<JsonValue>(values.len(), values.capacity());
+                for value in values {
+                    self.add_value(value, keys);
+                }
+            }
+            JsonValue::Object(object) => {
+                self.members += self.vec::<Member>(object.len(), object.capacity());
+                for member in object.iter() {
+                    match &member.identifier {
+                        Key::Owned(key) => self.keys += self.vec::<u8>(key.len(), key.capacity()),
+                        Key::Shared(key) => self.add_shared_key(key, keys),
+                    }
+                    self.add_value(member.value(), keys);
+                }
+            }
+            JsonValue::True | JsonValue::False | JsonValue::Null => {}
+        }
+    }
+    fn add_shared_key(&mut self, key: &Arc<str>, keys: &mut HashSet<*const u8>) {
+        if !keys.insert(key.as_ptr()) {
+            return;
+        }
+        // An `Arc` allocation starts with its strong and weak counts.
+        let counts = 2 * mem::size_of::<usize>();
+        let size = Layout::from_size_align(counts + key.len(), mem::align_of::<usize>())
+            .expect("keys are smaller than the address space")
+            .pad_to_align()
+            .size();
+        self.keys += size;
+        self.overhead += size - key.len();
+        self.allocations += 1;
+    }
+}
+impl Add for Footprint {
+    type Output = Footprint;
+    fn add(self, other: Footprint) -> Footprint {
+        Footprint {
+            strings: self.strings + other.strings,
+            keys: self.keys + other.keys,
+            arrays: self.arrays + other.arrays,
+            members: self.members + other.members,
+            numbers: self.numbers + other.numbers,
+            nodes: self.nodes + other.nodes,
+            overhead: self.overhead + other.overhead,
+            allocations: self.allocations + other.allocations,
+        }
+    }
+}
+
+// What the allocator was asked for while `count` ran its closure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Allocations {
+    // Calls to `alloc` and `realloc`.
+    pub count: usize,
+    // Bytes allocated, growth by `realloc` included.
+    pub bytes: usize,
+    // Bytes allocated and not freed by the end, negative if more was freed.
+    pub retained: isize,
+    // The most bytes held at once, above what was held at the start.
+    pub peak: usize,
+}
+
+#[cfg(feature = "std")]
+pub use counting::{count, Counting};
+
+// Counting needs thread-local storage, and an allocator to wrap.
+#[cfg(feature = "std")]
+mod counting {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    use super::Allocations;
+
+    thread_local! {
+        // `None` unless `count` is running on this thread.
+        static COUNTING: Cell<Option<Allocations>> = const { Cell::new(None) };
+    }
+
+    fn record(f: impl FnOnce(&mut Allocations)) {
+        // Allocations while the thread shuts down find no counters, and aren't counted.
+        let _ = COUNTING.try_with(|counting| {
+            if let Some(mut allocations) = counting.get() {
+                f(&mut allocations);
+                allocations.peak = allocations.peak.max(allocations.retained.max(0) as usize);
+                counting.set(Some(allocations));
+            }
+        });
+    }
+
+    // Wraps an allocator to count what `count` asks of it. Install it in a test binary:
+    //
+    //     #[global_allocator]
+    //     static ALLOCATOR: Counting = Counting(System);
+    //
+    // Only allocations of the thread running `count` are counted, so that tests running
+    // next to each other don't add up.
+    #[derive(Debug, Default)]
+    pub struct Counting<A = System>(pub A);
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for Counting<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            record(|a| {
+                a.count += 1;
+                a.bytes += layout.size();
+                a.retained += layout.size() as isize;
+            });
+            unsafe { self.0.alloc(layout) }
+        }
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            record(|a| {
+                a.count += 1;
+                a.bytes += layout.size();
+                a.retained += layout.size() as isize;
+            });
+            unsafe { self.0.alloc_zeroed(layout) }
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            record(|a| a.retained -= layout.size() as isize);
+            unsafe { self.0.dealloc(ptr, layout) }
+        }
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            record(|a| {
+                a.count += 1;
+                a.bytes += new_size.saturating_sub(layout.size());
+                a.retained += new_size as isize - layout.size() as isize;
+            });
+            unsafe { self.0.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    // Runs `f`, counting its allocations on this thread. Counts are only kept when
+    // `Counting` is the global allocator, and are all zero otherwise.
+    pub fn count<R>(f: impl FnOnce() -> R) -> (R, Allocations) {
+        let outer = COUNTING.with(|counting| counting.replace(Some(Allocations::default())));
+        let result = f();
+        let allocations = COUNTING.with(|counting| counting.replace(outer));
+        let allocations = allocations.unwrap_or_default();
+        // Nested counts also count towards the outer one.
+        if outer.is_some() {
+            record(|a| {
+                a.peak = a.peak.max((a.retained.max(0) as usize) + allocations.peak);
+                a.count += allocations.count;
+                a.bytes += allocations.bytes;
+                a.retained += allocations.retained;
+            });
+        }
+        (result, allocations)
+    }
+}
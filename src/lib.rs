@@ -0,0 +1,817 @@
+// Without the `std` feature, only the parser, its values and what works on them build,
+// on `core` and `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+#![feature(trait_alias)]
+extern crate alloc;
+extern crate self as json_parser;
+
+#[macro_use]
+mod macros;
+mod prelude;
+
+#[cfg(feature = "std")]
+pub mod arbitrary;
+pub mod builder;
+pub mod canonical;
+pub mod convert;
+pub mod diagnostic;
+pub mod diff;
+pub mod document;
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod file;
+pub mod footprint;
+pub mod number;
+pub mod parsec;
+pub mod query;
+pub mod serialize;
+pub mod span;
+
+pub use builder::{Builder, InterningBuilder, TreeBuilder};
+pub use convert::{FromJson, FromJsonError, ToJson};
+pub use diagnostic::Diagnostic;
+pub use document::Document;
+#[cfg(feature = "std")]
+pub use file::{parse_file, FileError, JsonFile};
+pub use footprint::Footprint;
+pub use number::{BigInt, ConversionError, Decimal, Number};
+pub use span::{json_spanned, parse_spanned, parse_spanned_value, Location, SpanTree, Spanned};
+
+#[cfg(feature = "derive")]
+pub use json_parser_derive::{FromJson, ToJson};
+
+#[doc(hidden)]
+pub use convert::derive as __private;
+
+use alloc::sync::Arc;
+use core::cell::Cell;
+use core::fmt;
+use core::ops::Range;
+
+use crate::prelude::*;
+
+use parsec::{
+    basic::{cut, expected, map, opt, recognize, recursive, Recursive},
+    branch::alt,
+    character::{digit, label, one_of, quoted, string, take_while, take_while1},
+    multi::{many, many1, sep_by, Trailing},
+    sequence::{between, pair, preceded},
+    trace::trace,
+    whitespace::ws,
+    JsonError, Parser, ParserError, Remaining,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonObject {
+    members: Vec<Member>,
+}
+impl JsonObject {
+    pub fn new(members: Vec<Member>) -> Self {
+        Self { members }
+    }
+    pub fn get(&self, identifier: &str) -> Option<&JsonValue> {
+        self.members
+            .iter()
+            .find(|member| member.identifier() == identifier)
+            .map(|member| &member.value)
+    }
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+    pub(crate) fn capacity(&self) -> usize {
+        self.members.capacity()
+    }
+    pub fn iter(&self) -> core::slice::Iter<'_, Member> {
+        self.members.iter()
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    identifier: Key,
+    value: JsonValue,
+}
+impl Member {
+    pub fn new(identifier: impl Into<String>, value: JsonValue) -> Self {
+        Self {
+            identifier: Key::Owned(identifier.into()),
+            value,
+        }
+    }
+    // A member whose key may be shared with others, as `intern_keys` shares them.
+    pub fn shared(identifier: Arc<str>, value: JsonValue) -> Self {
+        Self {
+            identifier: Key::Shared(identifier),
+            value,
+        }
+    }
+    pub fn identifier(&self) -> &str {
+        self.identifier.as_str()
+    }
+    // The shared handle behind `identifier()`, for members made by `shared`; with
+    // `intern_keys` every member spelled the same way in a document points at the
+    // same allocation.
+    pub fn shared_identifier(&self) -> Option<&Arc<str>> {
+        match &self.identifier {
+            Key::Owned(_) => None,
+            Key::Shared(key) => Some(key),
+        }
+    }
+    pub fn value(&self) -> &JsonValue {
+        &self.value
+    }
+}
+// Keys are only shared, and pay for their reference counts, when asked to be.
+#[derive(Clone)]
+enum Key {
+    Owned(String),
+    Shared(Arc<str>),
+}
+impl Key {
+    fn as_str(&self) -> &str {
+        match self {
+            Key::Owned(key) => key,
+            Key::Shared(key) => key,
+        }
+    }
+}
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    String(String),
+    Number(Number),
+    Array(Vec<JsonValue>),
+    True,
+    False,
+    Null,
+    Object(JsonObject),
+}
+impl JsonValue {
+    pub fn get(&self, identifier: &str) -> Option<&JsonValue> {
+        match self {
+            Self::Object(object) => object.get(identifier),
+            _ => None,
+        }
+    }
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            Self::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+    // Iterates over the elements of an array, or nothing for any other value.
+    pub fn iter(&self) -> core::slice::Iter<'_, JsonValue> {
+        match self {
+            Self::Array(values) => values.iter(),
+            _ => [].iter(),
+        }
+    }
+    // Iterates over the members of an object, or nothing for any other value.
+    pub fn members(&self) -> core::slice::Iter<'_, Member> {
+        match self {
+            Self::Object(object) => object.iter(),
+            _ => [].iter(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Array(values) => values.len(),
+            Self::Object(object) => object.len(),
+            _ => 0,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Self::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::True => Some(true),
+            Self::False => Some(false),
+            _ => None,
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+    // The heap memory held by the value and everything in it.
+    pub fn footprint(&self) -> Footprint {
+        Footprint::of_value(self)
+    }
+    // Looks a value up by RFC 6901 JSON Pointer, e.g. `/records/0/name`.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| match value {
+            Self::Object(object) => object.get(&token),
+            Self::Array(values) => values.get(array_index(&token)?),
+            _ => None,
+        })
+    }
+}
+// Splits a JSON Pointer into its unescaped reference tokens.
+pub fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = String> + '_> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+    Some(
+        pointer
+            .split('/')
+            .skip(1)
+            .map(|token| token.replace("~1", "/").replace("~0", "~")),
+    )
+}
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+fn array_index(token: &str) -> Option<usize> {
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
+impl core::ops::Index<&str> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, identifier: &str) -> &JsonValue {
+        self.get(identifier)
+            .unwrap_or_else(|| panic!("no member `{}` in json value", identifier))
+    }
+}
+impl core::ops::Index<usize> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index)
+            .unwrap_or_else(|| panic!("no element at index {} in json value", index))
+    }
+}
+// The text up to the next delimiter (or at least one char), quoted by error messages.
+fn unexpected(rem: &str) -> &str {
+    match rem.find(['\n', ',', '}', ']']) {
+        Some(0) => &rem[..rem.chars().next().map_or(0, char::len_utf8)],
+        Some(end) => &rem[..end],
+        None => rem,
+    }
+}
+// Gives a recoverable error a better reason and commits it.
+fn fatal(mut error: JsonError<'_>, reason: String) -> JsonError<'_> {
+    if !error.is_fatal() {
+        error.error_mut().set_reason(reason);
+    }
+    error.cut()
+}
+fn fatal_at(s: Remaining<'_>, len: usize, reason: String) -> JsonError<'_> {
+    JsonError::Unsavable(s, ParserError::new(0..len, reason))
+}
+// Whitespace between tokens: any Unicode whitespace by default, only the four JSON
+// whitespace chars in strict mode, and comments as well in JSON5 mode.
+fn space<'a: 'b, 'b>(o: &'b ParseOptions) -> impl Parser<'a, ()> + 'b {
+    let loose = ws();
+    let strict = take_while(|c| matches!(c, ' ' | '\t' | '\n' | '\r'));
+    let json5 = many(alt((take_while1(char::is_whitespace), comment())));
+    move |s| match () {
+        _ if o.json5 => json5(s).map(|(remaining, _)| (remaining, ())),
+        _ if o.strict => strict(s).map(|(remaining, _)| (remaining, ())),
+        _ => loose(s),
+    }
+}
+fn comment<'a>() -> impl Parser<'a, &'a str> {
+    let line = recognize(pair(label("//"), take_while(|c| c != '\n')));
+    let block = recognize(pair(label("/*"), |s: Remaining<'a>| {
+        match s.rem.find("*/") {
+            Some(end) => Ok((s.advance(end + 2), ())),
+            None => Err(fatal_at(
+                s,
+                s.rem.len(),
+                "Unclosed block comment".to_string(),
+            )),
+        }
+    }));
+    alt((line, block))
+}
+// Integers that fit an `isize` stay exact, other numbers are the nearest `f64`, or,
+// with `exact_numbers`, a `BigInt` or a `Decimal` of the literal's digits. JSON5
+// adds hexadecimal integers, a leading `+` and a point with no digits on one side.
+pub fn number<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+) -> impl Parser<'a, B::Value> + 'b {
+    let sign = recognize(opt(one_of("+-")));
+    let hex = preceded(alt((label("0x"), label("0X"))), recognize(many1(digit(16))));
+    let decimal = recognize(many1(digit(10)));
+    let digits = take_while(|c| c.is_ascii_digit());
+    let exponent_sign = opt(one_of("+-"));
+    trace("number", move |s: Remaining<'a>| {
+        let (remaining, sign) = sign(s)?;
+        if sign == "+" && !o.json5 {
+            return Err(JsonError::Failure(
+                s,
+                ParserError::new(0..1, "Numbers can't start with `+`".to_string()),
+            ));
+        }
+        let too_large = |remaining: &Remaining<'a>| {
+            fatal_at(
+                s,
+                s.consumed(remaining).len(),
+                format!("Number `{}` is too large", s.consumed(remaining)),
+            )
+        };
+        if let (Ok((remaining, digits)), true) = (hex(remaining), o.json5) {
+            let literal = format!("{}{}", sign, digits);
+            let number = match o.exact_numbers {
+                true => BigInt::from_str_radix(&literal, 16).map(Number::from),
+                false => isize::from_str_radix(&literal, 16)
+                    .ok()
+                    .map(Number::Integer),
+            };
+            return match number {
+                Some(number) => Ok((remaining, b.number(number))),
+                None => Err(too_large(&remaining)),
+            };
+        }
+        let (remaining, integer) = match o.json5 && remaining.rem.starts_with('.') {
+            true => digits(remaining)?,
+            false => decimal(remaining)?,
+        };
+        if o.strict && integer.len() > 1 && integer.starts_with('0') {
+            return Err(fatal_at(
+                s,
+                s.consumed(&remaining).len(),
+                format!("Number `{}` has a leading zero", s.consumed(&remaining)),
+            ));
+        }
+        let remaining = match remaining.rem.starts_with('.') {
+            true => {
+                let (remaining, fraction) = digits(remaining.advance(1))?;
+                if fraction.is_empty() && (integer.is_empty() || !o.json5) {
+                    return Err(fatal_at(
+                        s,
+                        s.consumed(&remaining).len(),
+                        format!(
+                            "Number `{}` has no digits after its point",
+                            s.consumed(&remaining)
+                        ),
+                    ));
+                }
+                remaining
+            }
+            false => remaining,
+        };
+        let remaining = match remaining.rem.starts_with(['e', 'E']) {
+            true => {
+                let (remaining, _) = exponent_sign(remaining.advance(1))?;
+                let (remaining, exponent) = digits(remaining)?;
+                if exponent.is_empty() {
+                    return Err(fatal_at(
+                        s,
+                        s.consumed(&remaining).len(),
+                        format!(
+                            "Number `{}` has no digits in its exponent",
+                            s.consumed(&remaining)
+                        ),
+                    ));
+                }
+                remaining
+            }
+            false => remaining,
+        };
+        let number = match o.exact_numbers {
+            true => Number::from_literal_exact(s.consumed(&remaining)),
+            false => Number::from_literal(s.consumed(&remaining)),
+        };
+        match number {
+            Some(number) => Ok((remaining, b.number(number))),
+            None => Err(too_large(&remaining)),
+        }
+    })
+}
+// A quoted string, with its escapes left as they are. JSON5 also has single quotes.
+fn string_literal<'a: 'b, 'b>(o: &'b ParseOptions) -> impl Parser<'a, &'a str> + 'b {
+    let double = string();
+    let single = quoted('\'');
+    move |s: Remaining<'a>| {
+        let (remaining, raw) = match o.json5 {
+            true => alt((&double, &single))(s)?,
+            false => double(s)?,
+        };
+        if o.strict {
+            check_string(s.advance(1), raw)?;
+        }
+        Ok((remaining, raw))
+    }
+}
+// Strict mode: only the escapes of RFC 8259, and no raw control characters.
+fn check_string<'a>(start: Remaining<'a>, raw: &str) -> Result<(), JsonError<'a>> {
+    let mut chars = raw.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c < ' ' {
+            return Err(fatal_at(
+                start.advance(i),
+                1,
+                format!("Unescaped control character U+{:04X} in string", c as u32),
+            ));
+        }
+        if c != '\\' {
+            continue;
+        }
+        let valid = match chars.next() {
+            Some((_, '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't')) => true,
+            Some((j, 'u')) => {
+                let hex = raw.get(j + 1..j + 5).unwrap_or("");
+                hex.len() == 4 && hex.chars().all(|c| c.is_ascii_hexdigit())
+            }
+            _ => false,
+        };
+        if !valid {
+            let escape: String = raw[i..].chars().take(2).collect();
+            return Err(fatal_at(
+                start.advance(i),
+                escape.len(),
+                format!("Invalid escape `{}` in string", escape),
+            ));
+        }
+    }
+    Ok(())
+}
+pub fn json_string<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+) -> impl Parser<'a, B::Value> + 'b {
+    trace("string", map(string_literal(o), move |val| b.string(val)))
+}
+pub fn value<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+) -> impl Parser<'a, B::Value> + 'b {
+    // Containers nested in the top-level object start at depth 2.
+    value_at(b, o, 1)
+}
+// `value`, for containers nested in `depth` others.
+fn value_at<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+    depth: usize,
+) -> impl Parser<'a, B::Value> + 'b {
+    recursive(move |value: Recursive<'b, Remaining<'a>, B::Value>| {
+        let any = alt((
+            expected("string", json_string(b, o)),
+            expected("number", number(b, o)),
+            expected("`[`", array(b, o, value.parser())),
+            expected(
+                "`{`",
+                map(object(b, o, value.parser()), move |object| {
+                    b.object_value(object)
+                }),
+            ),
+            keyword(b),
+        ));
+        let depth = Cell::new(depth);
+        trace("value", move |s: Remaining<'a>| {
+            let nested = s.rem.starts_with(['[', '{']);
+            if nested {
+                if o.max_depth.is_some_and(|max| depth.get() >= max) {
+                    return Err(fatal_at(
+                        s,
+                        1,
+                        format!("Exceeded the maximum nesting depth of {}", depth.get()),
+                    ));
+                }
+                depth.set(depth.get() + 1);
+            }
+            let result = any(s);
+            if nested {
+                depth.set(depth.get() - 1);
+            }
+            result
+                .map_err(|mut error| {
+                    if !error.is_fatal() {
+                        let reason = match s.rem.is_empty() {
+                            true => format!("{}, found nothing", error.error().message()),
+                            false => format!(
+                                "{}, found `{}`",
+                                error.error().message(),
+                                unexpected(s.rem)
+                            ),
+                        };
+                        error.error_mut().set_reason(reason);
+                        error.error_mut().set_pos_range(0..unexpected(s.rem).len());
+                    }
+                    error
+                })
+                .map(|(remaining, value)| (remaining, b.with_span(value, s.pos..remaining.pos)))
+        })
+    })
+}
+fn trailing(o: &ParseOptions) -> Trailing {
+    match o.json5 {
+        true => Trailing::Allowed,
+        false => Trailing::Forbidden,
+    }
+}
+pub fn array<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+    element: impl Parser<'a, B::Value> + 'b,
+) -> impl Parser<'a, B::Value> + 'b {
+    let elements = sep_by(
+        between(space(o), element, space(o)),
+        label(","),
+        trailing(o),
+    );
+    let space = space(o);
+    trace("array", move |s: Remaining<'a>| {
+        let (remaining, _) = label("[")(s)?;
+        let (remaining, values) = elements(remaining).map_err(|error| match error {
+            JsonError::Failure(rem, _) if label("]")(rem).is_ok() => fatal(
+                error,
+                "Unexpected character \"]\"\nHelp: trailing comma aren't allowed".to_string(),
+            ),
+            error => error.cut(),
+        })?;
+        let (remaining, _) = space(remaining)?;
+        label("]")(remaining)
+            .map(|(remaining, _)| (remaining, b.array(values)))
+            .map_err(|error| {
+                let found = unexpected(remaining.rem);
+                if value(&TreeBuilder, o)(remaining).is_ok() {
+                    fatal(
+                        error,
+                        format!(
+                            "Unexpected character {:#?}\nHelp: You probably forgot a comma",
+                            found
+                        ),
+                    )
+                } else {
+                    fatal(error, format!("Unexpected character {:#?}", found))
+                }
+            })
+    })
+}
+pub fn keyword<'a: 'b, 'b, B: Builder<'a>>(b: &'b B) -> impl Parser<'a, B::Value> + 'b {
+    let keywords = alt((
+        expected("true", map(label("true"), move |_| b.boolean(true))),
+        expected("false", map(label("false"), move |_| b.boolean(false))),
+        expected("null", map(label("null"), move |_| b.null())),
+    ));
+    trace("keyword", move |s: Remaining<'a>| {
+        keywords(s).map_err(|error| match s.rem.chars().next() {
+            Some(c) if c.is_alphabetic() => fatal(
+                error,
+                format!(
+                    "Expected either true, false or null, found {}",
+                    unexpected(s.rem)
+                ),
+            ),
+            _ => error,
+        })
+    })
+}
+// A member identifier: a string, or in JSON5 also an unquoted identifier.
+fn key<'a: 'b, 'b>(o: &'b ParseOptions) -> impl Parser<'a, &'a str> + 'b {
+    let string = string_literal(o);
+    let identifier = recognize(pair(
+        take_while1(|c| c.is_alphabetic() || c == '_' || c == '$'),
+        take_while(|c| c.is_alphanumeric() || c == '_' || c == '$'),
+    ));
+    move |s| match o.json5 {
+        true => alt((&string, &identifier))(s),
+        false => string(s),
+    }
+}
+// Fails recoverably when there is no key, so that `object` can tell an empty object or
+// a trailing comma from a malformed member.
+pub fn member<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+    value: impl Parser<'a, B::Value> + 'b,
+) -> impl Parser<'a, B::Member> + 'b {
+    let key = key(o);
+    let colon = cut(preceded(space(o), label(":")));
+    let value = cut(preceded(space(o), value));
+    let space = space(o);
+    trace("member", move |s: Remaining<'a>| {
+        let (remaining, _) = space(s)?;
+        let (rem, identifier) = key(remaining)?;
+        let key_span = remaining.pos..rem.pos;
+        let (rem, _) = colon(rem)?;
+        let (rem, value) = value(rem)?;
+        Ok((rem, b.member_span(b.member(identifier, value), key_span)))
+    })
+}
+// Explains why there is no member identifier at `rem`.
+fn key_error<'a>(o: &ParseOptions, rem: Remaining<'a>, error: JsonError<'a>) -> JsonError<'a> {
+    let found = match value(&TreeBuilder, o)(rem) {
+        Ok((_, JsonValue::Array(_))) => "an array",
+        Ok((_, JsonValue::Number(_))) => "a number",
+        Ok((_, JsonValue::Object(_))) => "an object",
+        Ok((_, JsonValue::True)) => "keyword `true`",
+        Ok((_, JsonValue::False)) => "keyword `false`",
+        Ok((_, JsonValue::Null)) => "keyword `null`",
+        _ if label("}")(rem).is_ok() => {
+            return fatal(
+                error,
+                "Expected a string, found `}`\nHelp: Trailing comma aren't allowed in json"
+                    .to_string(),
+            )
+        }
+        _ => {
+            let reason = format!("Expected a string, found `{}`", unexpected(rem.rem));
+            return fatal(error, reason);
+        }
+    };
+    fatal(
+        error,
+        format!(
+            "Expected a string, found {}\nHelp: member identifier can only be a string",
+            found
+        ),
+    )
+}
+pub fn object<'a: 'b, 'b, B: Builder<'a>>(
+    b: &'b B,
+    o: &'b ParseOptions,
+    value: impl Parser<'a, B::Value> + 'b,
+) -> impl Parser<'a, B::Object> + 'b {
+    let members = sep_by(
+        member(b, o, value),
+        preceded(space(o), label(",")),
+        trailing(o),
+    );
+    let space = space(o);
+    trace("object", move |s: Remaining<'a>| {
+        let (remaining, _) = label("{")(s)?;
+        let (remaining, members) = members(remaining).map_err(|error| match error {
+            JsonError::Failure(rem, _) => key_error(o, rem, error),
+            error => error,
+        })?;
+        let (remaining, _) = space(remaining)?;
+        match label("}")(remaining) {
+            Ok((remaining, _)) => Ok((remaining, b.object(members))),
+            Err(error) if members.is_empty() => Err(key_error(o, remaining, error)),
+            Err(error) if string()(remaining).is_ok() => Err(fatal(
+                error,
+                "Expected a `}`, found a string\nHelp: You probably forgot a `,` here".to_string(),
+            )),
+            Err(error) => {
+                let reason = format!("Expected a `}}`, found `{}`", unexpected(remaining.rem));
+                Err(fatal(error, reason))
+            }
+        }
+    })
+}
+/*
+*/
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    // Share one `Arc<str>` between all the members of a document with the same key.
+    pub intern_keys: bool,
+    // Reject what RFC 8259 does not allow but this parser accepts by default: invalid
+    // escapes and raw control characters in strings, non-ASCII whitespace and numbers
+    // with leading zeros.
+    pub strict: bool,
+    // Accept JSON5 comments, trailing commas, unquoted keys, single-quoted strings,
+    // hexadecimal numbers, a leading `+` and points with no digits on one side.
+    pub json5: bool,
+    // Keep numbers exactly as written instead of rounding them to an `f64`: integers
+    // beyond an `isize` become a `Number::BigInt` and numbers with a fraction or an
+    // exponent a `Number::Decimal`, `-0.0` keeping its sign. Without it, numbers
+    // beyond the range of an `f64`, like `1e400`, are rejected as too large.
+    pub exact_numbers: bool,
+    // How deeply arrays and objects may nest, the top-level object being depth 1.
+    // `DEFAULT_MAX_DEPTH` by default; `None` lifts the limit, and the parser then
+    // overflows the stack on deep enough input, which aborts the process.
+    pub max_depth: Option<usize>,
+    // Replace malformed UTF-8, UTF-16 and UTF-32 in `parse_bytes` input by U+FFFD
+    // instead of rejecting it.
+    pub lossy_decoding: bool,
+}
+// The parser recurses once per level of nesting. This many levels fit in the 2 MiB
+// stacks of spawned threads, even in debug builds.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            intern_keys: false,
+            strict: false,
+            json5: false,
+            exact_numbers: false,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            lossy_decoding: false,
+        }
+    }
+}
+pub fn json(input: &str) -> Option<JsonObject> {
+    json_with(&TreeBuilder, input)
+}
+pub fn json_with_options(input: &str, options: &ParseOptions) -> Option<JsonObject> {
+    let result = if options.intern_keys {
+        parse_with(&InterningBuilder::default(), input, options)
+    } else {
+        parse_with(&TreeBuilder, input, options)
+    };
+    result.map_err(report).ok()
+}
+pub fn json_with<'a, B: Builder<'a>>(b: &B, input: &'a str) -> Option<B::Object> {
+    parse_with(b, input, &ParseOptions::default())
+        .map_err(report)
+        .ok()
+}
+// The `Option` entry points print what went wrong, when there is somewhere to print.
+fn report(diagnostic: Diagnostic) {
+    #[cfg(feature = "std")]
+    println!("error: {:#?}", diagnostic);
+    #[cfg(not(feature = "std"))]
+    let _ = diagnostic;
+}
+// Like `json_with_options`, returning what went wrong instead of printing it.
+pub fn parse(input: &str, options: &ParseOptions) -> Result<JsonObject, Diagnostic> {
+    if options.intern_keys {
+        parse_with(&InterningBuilder::default(), input, options)
+    } else {
+        parse_with(&TreeBuilder, input, options)
+    }
+}
+pub fn parse_with<'a, B: Builder<'a>>(
+    b: &B,
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<B::Object, Diagnostic> {
+    parse_document(b, input, options).map(|(object, _)| object)
+}
+// Like `parse`, for documents whose top level is any value, as RFC 8259 allows:
+// `[1, 2]`, `"text"` or `42` as well as objects.
+pub fn parse_value(input: &str, options: &ParseOptions) -> Result<JsonValue, Diagnostic> {
+    let builder = InterningBuilder::default();
+    let (value, _) = match options.intern_keys {
+        true => parse_top_level(input, options, "value", value_at(&builder, options, 0))?,
+        false => parse_top_level(input, options, "value", value_at(&TreeBuilder, options, 0))?,
+    };
+    Ok(value)
+}
+// Like `parse_value`, for bytes in any encoding `encoding::detect` recognizes, with or
+// without a byte order mark. Diagnostics point into the text as `encoding::decode`
+// returns it.
+pub fn parse_bytes(input: &[u8], options: &ParseOptions) -> Result<JsonValue, Diagnostic> {
+    parse_value(&encoding::decode(input, options.lossy_decoding)?, options)
+}
+// Also returns the range of the top-level object, without the whitespace and comments
+// around it.
+pub(crate) fn parse_document<'a, B: Builder<'a>>(
+    b: &B,
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<(B::Object, Range<usize>), Diagnostic> {
+    parse_top_level(
+        input,
+        options,
+        "object",
+        object(b, options, value(b, options)),
+    )
+}
+// `top`, with nothing but whitespace around it. `what` names it in errors.
+fn parse_top_level<'a, T>(
+    input: &'a str,
+    options: &ParseOptions,
+    what: &str,
+    top: impl Parser<'a, T>,
+) -> Result<(T, Range<usize>), Diagnostic> {
+    let space = space(options);
+    let document = |s| {
+        let (s, _) = space(s)?;
+        let (end, top) = top(s)?;
+        let (remaining, _) = space(end)?;
+        Ok((remaining, (top, s.pos..end.pos)))
+    };
+    match document(Remaining::new(input, 0)) {
+        Ok((remaining, _)) if !remaining.rem.is_empty() => Err(Diagnostic::new(
+            input,
+            remaining.pos..remaining.pos + unexpected(remaining.rem).len(),
+            &format!(
+                "Unexpected `{}` after the end of the {}",
+                unexpected(remaining.rem),
+                what
+            ),
+        )),
+        Ok((_, document)) => Ok(document),
+        Err(error) => Err(Diagnostic::from_error(input, &error)),
+    }
+}
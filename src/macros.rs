@@ -0,0 +1,170 @@
+/// Builds a `JsonValue` from a JSON-like literal. Anything that is not `null`, `true`,
+/// `false`, an array or an object is an expression converted with `ToJson`; object keys
+/// are expressions too, so `(prefix + "_id"): 1` computes its key.
+///
+/// Malformed literals fail to compile, with an error saying what is wrong:
+///
+/// ```compile_fail
+/// // expected `,` after `1` in json! array
+/// json_parser::json!([1 2]);
+/// ```
+/// ```compile_fail
+/// // expected `,` before `1` in json! array
+/// json_parser::json!([null 1]);
+/// ```
+/// ```compile_fail
+/// // missing key before `:` in json! object
+/// json_parser::json!({: 1});
+/// ```
+/// ```compile_fail
+/// // expected `:` after key `"a" 1` in json! object
+/// json_parser::json!({"a" 1});
+/// ```
+/// ```compile_fail
+/// // missing value for key `"a"` in json! object
+/// json_parser::json!({"a":});
+/// ```
+/// ```compile_fail
+/// // expected `,` after `1` in json! object
+/// json_parser::json!({"a": 1 2});
+/// ```
+/// ```compile_fail
+/// // expected `,` before `1` in json! object
+/// json_parser::json!({"a": null 1});
+/// ```
+/// ```compile_fail
+/// // invalid key `a b` in json! object, wrap computed keys in parentheses
+/// json_parser::json!({a b: 1});
+/// ```
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+// The array and object rules below munch their input one value at a time, so that a
+// malformed literal ends up on one of the `compile_error!` arms.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    (@array [$($elements:expr),*]) => {
+        $crate::__private::vec![$($elements),*]
+    };
+    (@array [$($elements:expr),*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array_next [$($elements,)* $crate::json_internal!(null)] $($rest)*)
+    };
+    (@array [$($elements:expr),*] true $($rest:tt)*) => {
+        $crate::json_internal!(@array_next [$($elements,)* $crate::json_internal!(true)] $($rest)*)
+    };
+    (@array [$($elements:expr),*] false $($rest:tt)*) => {
+        $crate::json_internal!(@array_next [$($elements,)* $crate::json_internal!(false)] $($rest)*)
+    };
+    (@array [$($elements:expr),*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array_next [$($elements,)* $crate::json_internal!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elements:expr),*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array_next [$($elements,)* $crate::json_internal!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elements:expr),*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!($next)] $($rest)*)
+    };
+    (@array [$($elements:expr),*] $last:expr) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!($last)])
+    };
+    (@array [$($elements:expr),*] $value:tt $($rest:tt)*) => {
+        compile_error!(concat!("expected `,` after `", stringify!($value), "` in json! array"))
+    };
+    (@array_next [$($elements:expr),*]) => {
+        $crate::json_internal!(@array [$($elements),*])
+    };
+    (@array_next [$($elements:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements),*] $($rest)*)
+    };
+    (@array_next [$($elements:expr),*] $unexpected:tt $($rest:tt)*) => {
+        compile_error!(concat!("expected `,` before `", stringify!($unexpected), "` in json! array"))
+    };
+
+    (@object [$($members:expr),*] ()) => {
+        $crate::__private::vec![$($members),*]
+    };
+    (@object [$($members:expr),*] () : $($rest:tt)*) => {
+        compile_error!("missing key before `:` in json! object")
+    };
+    (@object [$($members:expr),*] ($($key:tt)+) : $($rest:tt)*) => {
+        $crate::json_internal!(@value [$($members),*] ($($key)+) $($rest)*)
+    };
+    (@object [$($members:expr),*] ($($key:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::json_internal!(@object [$($members),*] ($($key)* $next) $($rest)*)
+    };
+    (@object [$($members:expr),*] ($($key:tt)+)) => {
+        compile_error!(concat!("expected `:` after key `", stringify!($($key)+), "` in json! object"))
+    };
+    (@value [$($members:expr),*] ($($key:tt)+)) => {
+        compile_error!(concat!("missing value for key `", stringify!($($key)+), "` in json! object"))
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) null $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!(null)) $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) true $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!(true)) $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) false $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!(false)) $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!([$($array)*])) $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!({$($object)*})) $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) $value:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!($value)), $($rest)*)
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) $value:expr) => {
+        $crate::json_internal!(@member [$($members),*] ($($key)+) ($crate::json_internal!($value)))
+    };
+    (@value [$($members:expr),*] ($($key:tt)+) $value:tt $($rest:tt)*) => {
+        compile_error!(concat!("expected `,` after `", stringify!($value), "` in json! object"))
+    };
+    (@member [$($members:expr),*] ($($key:tt)+) ($value:expr)) => {
+        $crate::json_internal!(@object [$($members,)* $crate::json_internal!(@key ($($key)+) $value)] ())
+    };
+    (@member [$($members:expr),*] ($($key:tt)+) ($value:expr) , $($rest:tt)*) => {
+        $crate::json_internal!(@object [$($members,)* $crate::json_internal!(@key ($($key)+) $value)] () $($rest)*)
+    };
+    (@member [$($members:expr),*] ($($key:tt)+) ($value:expr) $unexpected:tt $($rest:tt)*) => {
+        compile_error!(concat!("expected `,` before `", stringify!($unexpected), "` in json! object"))
+    };
+    (@key ($key:expr) $value:expr) => {
+        $crate::Member::new($crate::__private::key(&$key), $value)
+    };
+    (@key ($($key:tt)+) $value:expr) => {
+        compile_error!(concat!("invalid key `", stringify!($($key)+), "` in json! object, wrap computed keys in parentheses"))
+    };
+
+    (null) => {
+        $crate::JsonValue::Null
+    };
+    (true) => {
+        $crate::JsonValue::True
+    };
+    (false) => {
+        $crate::JsonValue::False
+    };
+    ([]) => {
+        $crate::JsonValue::Array($crate::__private::vec![])
+    };
+    ([$($array:tt)+]) => {
+        $crate::JsonValue::Array($crate::json_internal!(@array [] $($array)+))
+    };
+    ({}) => {
+        $crate::JsonValue::Object($crate::JsonObject::new($crate::__private::vec![]))
+    };
+    ({$($object:tt)+}) => {
+        $crate::JsonValue::Object($crate::JsonObject::new($crate::json_internal!(@object [] () $($object)+)))
+    };
+    ($other:expr) => {
+        $crate::ToJson::to_json(&$other)
+    };
+}
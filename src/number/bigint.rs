@@ -0,0 +1,196 @@
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::bignum::Big;
+use super::ConversionError;
+use crate::prelude::*;
+
+// An integer of any size. Zero is never negative.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Big,
+}
+impl BigInt {
+    // `text` as an integer in base 10, like `-123456789012345678901234567890`.
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::from_str_radix(text, 10)
+    }
+    // Like `i128::from_str_radix`, for base 10 or 16: an optional sign, then digits.
+    pub fn from_str_radix(text: &str, radix: u32) -> Option<Self> {
+        assert!(radix == 10 || radix == 16, "unsupported radix {}", radix);
+        let (negative, digits) = match text.as_bytes().first()? {
+            b'-' => (true, &text[1..]),
+            b'+' => (false, &text[1..]),
+            _ => (false, text),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        Some(Self::from_parts(negative, Big::from_digits(digits, radix)))
+    }
+    pub(super) fn from_parts(negative: bool, magnitude: Big) -> Self {
+        Self {
+            negative: negative && !magnitude.is_zero(),
+            magnitude,
+        }
+    }
+    pub(super) fn magnitude(&self) -> &Big {
+        &self.magnitude
+    }
+    pub(crate) fn heap_size(&self) -> usize {
+        self.magnitude.heap_size()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+    pub fn abs(&self) -> Self {
+        Self::from_parts(false, self.magnitude.clone())
+    }
+    // The nearest `f64`, infinite beyond its range.
+    pub fn to_f64(&self) -> f64 {
+        super::parse_float(&self.to_string()).expect("integers are valid float literals")
+    }
+    pub(super) fn to_i128(&self) -> Option<i128> {
+        let magnitude = self.magnitude.to_u128()?;
+        match self.negative {
+            true if magnitude <= i128::MIN.unsigned_abs() => {
+                Some((magnitude as i128).wrapping_neg())
+            }
+            true => None,
+            false => i128::try_from(magnitude).ok(),
+        }
+    }
+    pub(super) fn to_u128(&self) -> Option<u128> {
+        match self.negative {
+            true => None,
+            false => self.magnitude.to_u128(),
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        f.write_str(&self.magnitude.to_digits())
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, self.magnitude.add(&other.magnitude));
+        }
+        // Opposite signs: the larger magnitude decides the sign.
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, other.magnitude.sub(&self.magnitude))
+            }
+            _ => BigInt::from_parts(self.negative, self.magnitude.sub(&other.magnitude)),
+        }
+    }
+}
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            self.magnitude.mul(&other.magnitude),
+        )
+    }
+}
+// The same operations on owned values.
+macro_rules! owned_operators {
+    ($t:ty) => {
+        impl core::ops::Neg for $t {
+            type Output = $t;
+            fn neg(self) -> $t {
+                -&self
+            }
+        }
+        impl core::ops::Add for $t {
+            type Output = $t;
+            fn add(self, other: $t) -> $t {
+                &self + &other
+            }
+        }
+        impl core::ops::Sub for $t {
+            type Output = $t;
+            fn sub(self, other: $t) -> $t {
+                &self - &other
+            }
+        }
+        impl core::ops::Mul for $t {
+            type Output = $t;
+            fn mul(self, other: $t) -> $t {
+                &self * &other
+            }
+        }
+    };
+}
+owned_operators!(BigInt);
+pub(super) use owned_operators;
+
+macro_rules! primitive {
+    ($($t:ty),*) => {$(
+        impl From<$t> for BigInt {
+            #[allow(unused_comparisons)]
+            fn from(n: $t) -> Self {
+                let negative = n < 0;
+                let magnitude = match negative {
+                    true => (n as i128).unsigned_abs(),
+                    false => n as u128,
+                };
+                Self::from_parts(negative, Big::from_u128(magnitude))
+            }
+        }
+        impl TryFrom<&BigInt> for $t {
+            type Error = ConversionError;
+            fn try_from(n: &BigInt) -> Result<Self, ConversionError> {
+                let converted = match n.to_i128() {
+                    Some(i) => <$t>::try_from(i).ok(),
+                    None => n.to_u128().and_then(|u| <$t>::try_from(u).ok()),
+                };
+                converted.ok_or_else(|| ConversionError::out_of_range(n, stringify!($t)))
+            }
+        }
+    )*};
+}
+primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
@@ -0,0 +1,185 @@
+use crate::prelude::*;
+use core::cmp::Ordering;
+
+// An unsigned integer of any size, as little-endian 32-bit limbs without trailing
+// zero limbs: exact float conversions and the magnitude of `BigInt`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub(crate) struct Big {
+    limbs: Vec<u32>,
+}
+impl Big {
+    pub(crate) fn from_u64(n: u64) -> Self {
+        Self::from_u128(n as u128)
+    }
+    pub(crate) fn from_u128(n: u128) -> Self {
+        let mut big = Self {
+            limbs: (0..4).map(|i| (n >> (32 * i)) as u32).collect(),
+        };
+        big.normalize();
+        big
+    }
+    // `digits` must only hold digits of `radix`, at most 16.
+    pub(crate) fn from_digits(digits: &str, radix: u32) -> Self {
+        let mut big = Self::default();
+        // As many digits at a time as fit a limb.
+        for chunk in digits.as_bytes().chunks(7) {
+            let value = chunk.iter().fold(0, |n, &digit| {
+                n * radix + (digit as char).to_digit(radix).expect("a digit")
+            });
+            big.mul_add_small(radix.pow(chunk.len() as u32), value);
+        }
+        big
+    }
+    pub(crate) fn to_u128(&self) -> Option<u128> {
+        match self.limbs.len() {
+            0..=4 => Some(
+                self.limbs
+                    .iter()
+                    .rev()
+                    .fold(0, |n, &limb| n << 32 | limb as u128),
+            ),
+            _ => None,
+        }
+    }
+    pub(crate) fn heap_size(&self) -> usize {
+        self.limbs.capacity() * core::mem::size_of::<u32>()
+    }
+    pub(crate) fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    // `self * factor + carry`.
+    pub(crate) fn mul_add_small(&mut self, factor: u32, mut carry: u32) {
+        for limb in self.limbs.iter_mut() {
+            let product = *limb as u64 * factor as u64 + carry as u64;
+            *limb = product as u32;
+            carry = (product >> 32) as u32;
+        }
+        if carry != 0 {
+            self.limbs.push(carry);
+        }
+        self.normalize();
+    }
+    // Divides in place, returning the remainder.
+    pub(crate) fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let dividend = remainder << 32 | *limb as u64;
+            *limb = (dividend / divisor as u64) as u32;
+            remainder = dividend % divisor as u64;
+        }
+        self.normalize();
+        remainder as u32
+    }
+    pub(crate) fn mul_pow5(&mut self, mut n: u64) {
+        // 5^13 is the largest power of five that fits a limb.
+        while n >= 13 {
+            self.mul_add_small(1_220_703_125, 0);
+            n -= 13;
+        }
+        self.mul_add_small(5u32.pow(n as u32), 0);
+    }
+    pub(crate) fn mul_pow10(&mut self, n: u64) {
+        self.mul_pow5(n);
+        self.shl(n);
+    }
+    pub(crate) fn shl(&mut self, n: u64) {
+        if self.is_zero() {
+            return;
+        }
+        let (limbs, bits) = ((n / 32) as usize, (n % 32) as u32);
+        if bits > 0 {
+            let mut carry = 0;
+            for limb in self.limbs.iter_mut() {
+                let shifted = (*limb as u64) << bits | carry as u64;
+                *limb = shifted as u32;
+                carry = (shifted >> 32) as u32;
+            }
+            if carry != 0 {
+                self.limbs.push(carry);
+            }
+        }
+        self.limbs.splice(0..0, core::iter::repeat_n(0, limbs));
+    }
+
+    pub(crate) fn add(&self, other: &Self) -> Self {
+        let (long, short) = match self.limbs.len() >= other.limbs.len() {
+            true => (self, other),
+            false => (other, self),
+        };
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        let mut carry = 0u64;
+        for (i, &limb) in long.limbs.iter().enumerate() {
+            let sum = limb as u64 + *short.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut sum = Self { limbs };
+        sum.normalize();
+        sum
+    }
+    // `self - other`, where `other` is at most `self`.
+    pub(crate) fn sub(&self, other: &Self) -> Self {
+        debug_assert!(*self >= *other, "negative difference");
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0i64;
+        for (i, &limb) in self.limbs.iter().enumerate() {
+            let mut difference = limb as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = (difference < 0) as i64;
+            difference += borrow << 32;
+            limbs.push(difference as u32);
+        }
+        let mut difference = Self { limbs };
+        difference.normalize();
+        difference
+    }
+    pub(crate) fn mul(&self, other: &Self) -> Self {
+        let mut limbs = vec![0u32; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut product = Self { limbs };
+        product.normalize();
+        product
+    }
+
+    // The decimal digits, without leading zeros: `0` for zero.
+    pub(crate) fn to_digits(&self) -> String {
+        let mut n = self.clone();
+        let mut chunks = vec![];
+        while !n.is_zero() {
+            chunks.push(n.div_rem_small(1_000_000_000));
+        }
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        digits
+    }
+
+    fn normalize(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
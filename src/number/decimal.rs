@@ -0,0 +1,313 @@
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::bigint::{owned_operators, BigInt};
+use super::bignum::Big;
+use super::ConversionError;
+use crate::prelude::*;
+
+// `mantissa * 10^exponent`, exactly. Decimals keep the digits they were written with,
+// so `1.50` is `150 * 10^-2` and prints back as `1.50`, but compare by value:
+// `1.50 == 1.5`. Zeros written with a minus, like `-0.0`, keep it, although they
+// equal every other zero.
+#[derive(Debug, Clone, Default)]
+pub struct Decimal {
+    mantissa: BigInt,
+    exponent: i64,
+    // A zero with a minus, which the mantissa has no room for.
+    negative_zero: bool,
+}
+impl Decimal {
+    pub fn new(mantissa: BigInt, exponent: i64) -> Self {
+        Self {
+            mantissa,
+            exponent,
+            negative_zero: false,
+        }
+    }
+    // `text` as a json number, like `-0.10` or `6.02e23`, kept exactly. `None` if it
+    // isn't one, or if its exponent does not fit an `i64`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match super::is_literal(text) {
+            true => Self::from_literal(text),
+            false => None,
+        }
+    }
+    // Like `parse`, for the literals `Number::from_literal` takes.
+    pub(crate) fn from_literal(literal: &str) -> Option<Self> {
+        let (significand, exponent) = match literal.find(['e', 'E']) {
+            Some(e) => (&literal[..e], literal[e + 1..].parse::<i64>().ok()?),
+            None => (literal, 0),
+        };
+        let (integer, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+        let mantissa = match integer {
+            "" | "+" | "-" => BigInt::parse(&format!("{}0{}", integer, fraction))?,
+            _ => BigInt::parse(&format!("{}{}", integer, fraction))?,
+        };
+        Some(Self {
+            negative_zero: mantissa.is_zero() && literal.starts_with('-'),
+            mantissa,
+            exponent: exponent.checked_sub(fraction.len() as i64)?,
+        })
+    }
+    // `f` exactly, which may take many digits: `0.1` is
+    // `0.1000000000000000055511151231257827021181583404541015625`. `None` for NaN and
+    // the infinities.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        if f == 0.0 {
+            return Some(Self::new(BigInt::default(), 0).with_sign_of_zero(f.is_sign_negative()));
+        }
+        let bits = f.to_bits();
+        let biased = (bits >> 52 & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mut mantissa, exponent) = match biased {
+            0 => (Big::from_u64(fraction), -1074),
+            _ => (Big::from_u64(fraction | 1 << 52), biased - 1075),
+        };
+        let negative = f.is_sign_negative();
+        if exponent >= 0 {
+            mantissa.shl(exponent as u64);
+            return Some(Self::new(BigInt::from_parts(negative, mantissa), 0));
+        }
+        // `m * 2^-k` is `m * 5^k * 10^-k`.
+        mantissa.mul_pow5(-exponent as u64);
+        let mut decimal = Self::new(BigInt::from_parts(negative, mantissa), exponent);
+        decimal.trim();
+        Some(decimal)
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+    pub(crate) fn heap_size(&self) -> usize {
+        self.mantissa.heap_size()
+    }
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+    // Whether the value is below zero, which `-0.0` is not.
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+    // Whether the decimal is written with a minus, `-0.0` included.
+    pub fn is_sign_negative(&self) -> bool {
+        self.is_negative() || self.negative_zero
+    }
+    pub fn abs(&self) -> Self {
+        Self::new(self.mantissa.abs(), self.exponent)
+    }
+    // Whether the value is a whole number, whatever the digits it is written with.
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0 || self.to_bigint().is_ok()
+    }
+    // The nearest `f64`, infinite beyond its range.
+    pub fn to_f64(&self) -> f64 {
+        let sign = if self.negative_zero { "-" } else { "" };
+        super::parse_float(&format!("{}{}e{}", sign, self.mantissa, self.exponent))
+            .expect("decimals are valid float literals")
+    }
+    // The value as an integer, if it has no fraction. Integers with huge exponents,
+    // like `1e999999999`, are out of range.
+    pub fn to_bigint(&self) -> Result<BigInt, ConversionError> {
+        let digits = self.digits() as i64;
+        if self.is_zero() {
+            return Ok(BigInt::default());
+        }
+        if self.exponent >= 0 {
+            // Beyond any primitive integer, and far from what fits in memory.
+            if digits + self.exponent > MAX_INTEGER_DIGITS {
+                return Err(ConversionError::out_of_range(self, "an integer"));
+            }
+            let mut magnitude = self.mantissa.magnitude().clone();
+            magnitude.mul_pow10(self.exponent as u64);
+            return Ok(BigInt::from_parts(self.is_negative(), magnitude));
+        }
+        if -self.exponent >= digits {
+            return Err(ConversionError::not_an_integer(self));
+        }
+        let mut magnitude = self.mantissa.magnitude().clone();
+        for _ in 0..-self.exponent {
+            if magnitude.div_rem_small(10) != 0 {
+                return Err(ConversionError::not_an_integer(self));
+            }
+        }
+        Ok(BigInt::from_parts(self.is_negative(), magnitude))
+    }
+
+    fn with_sign_of_zero(mut self, negative: bool) -> Self {
+        self.negative_zero = negative && self.is_zero();
+        self
+    }
+    fn digits(&self) -> usize {
+        match self.is_zero() {
+            true => 1,
+            false => self.mantissa.magnitude().to_digits().len(),
+        }
+    }
+    // Where the decimal point sits relative to the first digit: 1 for `1.5`, -1 for
+    // `0.015`. Two non-zero decimals with different points are ordered by them.
+    fn point(&self) -> i64 {
+        self.digits() as i64 + self.exponent
+    }
+    // Drops the trailing zeros of the fraction.
+    fn trim(&mut self) {
+        let mut magnitude = self.mantissa.magnitude().clone();
+        while self.exponent < 0 && !magnitude.is_zero() {
+            let mut quotient = magnitude.clone();
+            if quotient.div_rem_small(10) != 0 {
+                break;
+            }
+            magnitude = quotient;
+            self.exponent += 1;
+        }
+        self.mantissa = BigInt::from_parts(self.is_negative(), magnitude);
+    }
+    // Both mantissas over the smaller of the two exponents.
+    fn aligned(&self, other: &Self) -> (BigInt, BigInt, i64) {
+        let scale = |d: &Self, exponent: i64| {
+            let mut magnitude = d.mantissa.magnitude().clone();
+            magnitude.mul_pow10((d.exponent - exponent) as u64);
+            BigInt::from_parts(d.is_negative(), magnitude)
+        };
+        let exponent = self.exponent.min(other.exponent);
+        (scale(self, exponent), scale(other, exponent), exponent)
+    }
+}
+// The most digits an integer converted from a `Decimal` may have.
+const MAX_INTEGER_DIGITS: i64 = 40;
+
+// Positional notation while the point is in or close to the digits, like `1.50` or
+// `0.000125`, and scientific notation otherwise, like `1.5e+30` or `1e-300`. Exponents
+// of zero or more are always scientific, so that decimals read back as decimals.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.magnitude().to_digits();
+        let point = self.point();
+        if self.is_sign_negative() {
+            f.write_str("-")?;
+        }
+        if self.exponent < 0 && point > 0 {
+            let (integer, fraction) = digits.split_at(point as usize);
+            write!(f, "{}.{}", integer, fraction)
+        } else if self.exponent < 0 && point > -6 {
+            write!(f, "0.{}{}", "0".repeat(-point as usize), digits)
+        } else {
+            let (first, rest) = digits.split_at(1);
+            let exponent = point - 1;
+            let sign = if exponent < 0 { '-' } else { '+' };
+            match rest.is_empty() {
+                true => write!(f, "{}e{}{}", first, sign, exponent.abs()),
+                false => write!(f, "{}.{}e{}{}", first, rest, sign, exponent.abs()),
+            }
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Decimal {}
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |d: &Self| match (d.is_zero(), d.is_negative()) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if sign(self) == 0 => return Ordering::Equal,
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+        // Same sign: aligning the exponents only takes as many digits as the numbers
+        // already have once their points are known to match.
+        let magnitudes = match self.point().cmp(&other.point()) {
+            Ordering::Equal => {
+                let (a, b, _) = self.aligned(other);
+                a.abs().cmp(&b.abs())
+            }
+            ordering => ordering,
+        };
+        match self.is_negative() {
+            true => magnitudes.reverse(),
+            false => magnitudes,
+        }
+    }
+}
+
+impl Neg for &Decimal {
+    type Output = Decimal;
+    fn neg(self) -> Decimal {
+        Decimal::new(-&self.mantissa, self.exponent).with_sign_of_zero(!self.negative_zero)
+    }
+}
+// Sums and differences take the smaller exponent, so `1.5 + 1.25` is `2.75`; their
+// cost grows with the gap between the exponents.
+impl Add for &Decimal {
+    type Output = Decimal;
+    fn add(self, other: &Decimal) -> Decimal {
+        let (a, b, exponent) = self.aligned(other);
+        Decimal::new(&a + &b, exponent)
+    }
+}
+impl Sub for &Decimal {
+    type Output = Decimal;
+    fn sub(self, other: &Decimal) -> Decimal {
+        self + &-other
+    }
+}
+// Panics if the exponents add up beyond an `i64`.
+impl Mul for &Decimal {
+    type Output = Decimal;
+    fn mul(self, other: &Decimal) -> Decimal {
+        let exponent = self
+            .exponent
+            .checked_add(other.exponent)
+            .expect("decimal exponent overflow");
+        Decimal::new(&self.mantissa * &other.mantissa, exponent)
+    }
+}
+owned_operators!(Decimal);
+
+impl From<BigInt> for Decimal {
+    fn from(n: BigInt) -> Self {
+        Self::new(n, 0)
+    }
+}
+
+macro_rules! primitive {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Decimal {
+            fn from(n: $t) -> Self {
+                Self::new(BigInt::from(n), 0)
+            }
+        }
+        impl TryFrom<&Decimal> for $t {
+            type Error = ConversionError;
+            fn try_from(d: &Decimal) -> Result<Self, ConversionError> {
+                let out_of_range = |_| ConversionError::out_of_range(d, stringify!($t));
+                match d.to_bigint() {
+                    Ok(n) => <$t>::try_from(&n).map_err(out_of_range),
+                    Err(error) if error.is_out_of_range() => Err(out_of_range(error)),
+                    Err(error) => Err(error),
+                }
+            }
+        }
+    )*};
+}
+primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
@@ -0,0 +1,355 @@
+mod bigint;
+mod bignum;
+mod decimal;
+mod parse;
+mod print;
+mod tables;
+
+use crate::prelude::*;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+use core::error::Error;
+use core::fmt;
+
+pub use bigint::BigInt;
+pub use decimal::Decimal;
+pub(crate) use parse::parse_float;
+
+// A json number. Integers that fit an `isize` are kept exact; everything else, that
+// is numbers with a fraction or an exponent and larger integers, is the nearest `f64`,
+// unless `ParseOptions::exact_numbers` keeps them as a `BigInt` or a `Decimal`.
+//
+// Equality and ordering are those of the values, so `Integer(1) == Float(1.0)`.
+#[derive(Debug, Clone)]
+pub enum Number {
+    Integer(isize),
+    Float(f64),
+    BigInt(BigInt),
+    Decimal(Decimal),
+}
+impl Number {
+    // `text` as a json number, like `-12`, `0.1` or `6.02e23`. `None` if it isn't one,
+    // or if it is too large for an `f64`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match is_literal(text) {
+            true => Self::from_literal(text),
+            false => None,
+        }
+    }
+    // Like `parse`, keeping the exact value: integers beyond an `isize` become a
+    // `BigInt`, and numbers with a fraction or an exponent a `Decimal`.
+    pub fn parse_exact(text: &str) -> Option<Self> {
+        match is_literal(text) {
+            true => Self::from_literal_exact(text),
+            false => None,
+        }
+    }
+    // Like `parse`, for literals already checked by the grammar, which may also be in
+    // one of the forms the lenient and JSON5 modes accept: `+1`, `01`, `.5` or `5.`.
+    pub(crate) fn from_literal(literal: &str) -> Option<Self> {
+        if !literal.contains(['.', 'e', 'E']) {
+            if let Ok(n) = literal.parse() {
+                return Some(Self::Integer(n));
+            }
+        }
+        parse_float(literal)
+            .filter(|f| f.is_finite())
+            .map(Self::Float)
+    }
+    // Like `parse_exact`, for the literals `from_literal` takes. `None` if the
+    // exponent does not fit an `i64`.
+    pub(crate) fn from_literal_exact(literal: &str) -> Option<Self> {
+        match literal.contains(['.', 'e', 'E']) {
+            true => Decimal::from_literal(literal).map(Self::Decimal),
+            false => BigInt::parse(literal).map(Self::from),
+        }
+    }
+    // Bytes of the limbs of exact numbers, on the heap.
+    pub(crate) fn heap_size(&self) -> usize {
+        match self {
+            Self::Integer(_) | Self::Float(_) => 0,
+            Self::BigInt(n) => n.heap_size(),
+            Self::Decimal(n) => n.heap_size(),
+        }
+    }
+
+    // Whether the number is an `Integer` or a `BigInt`; floats and decimals with no
+    // fraction are not.
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Self::Integer(_) | Self::BigInt(_))
+    }
+    // The value as an `isize`, if it is an integer in range, floats included.
+    pub fn as_isize(&self) -> Option<isize> {
+        match *self {
+            Self::Integer(n) => Some(n),
+            Self::Float(f) if is_whole(f) && f >= isize::MIN as f64 && f < isize::MAX as f64 => {
+                Some(f as isize)
+            }
+            Self::Float(_) => None,
+            Self::BigInt(ref n) => isize::try_from(n).ok(),
+            Self::Decimal(ref d) => isize::try_from(d).ok(),
+        }
+    }
+    // The nearest `f64`.
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::Integer(n) => n as f64,
+            Self::Float(f) => f,
+            Self::BigInt(ref n) => n.to_f64(),
+            Self::Decimal(ref d) => d.to_f64(),
+        }
+    }
+    // The exact value. `None` for NaN and the infinities.
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match *self {
+            Self::Integer(n) => Some(Decimal::from(n)),
+            Self::Float(f) => Decimal::from_f64(f),
+            Self::BigInt(ref n) => Some(Decimal::from(n.clone())),
+            Self::Decimal(ref d) => Some(d.clone()),
+        }
+    }
+}
+// Whether `text` has the syntax of a json number.
+fn is_literal(text: &str) -> bool {
+    let unsigned = text.strip_prefix('-').unwrap_or(text);
+    let (integer, rest) = unsigned.split_at(
+        unsigned
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(unsigned.len()),
+    );
+    let rest = match rest.strip_prefix('.') {
+        Some(fraction) if fraction.starts_with(|c: char| c.is_ascii_digit()) => {
+            fraction.trim_start_matches(|c: char| c.is_ascii_digit())
+        }
+        Some(_) => return false,
+        None => rest,
+    };
+    let exponent = match rest.strip_prefix(['e', 'E']) {
+        Some(exponent) => exponent.strip_prefix(['+', '-']).unwrap_or(exponent),
+        None if rest.is_empty() => "0",
+        None => return false,
+    };
+    let valid_integer = integer == "0" || (!integer.is_empty() && !integer.starts_with('0'));
+    let valid_exponent = !exponent.is_empty() && exponent.bytes().all(|b| b.is_ascii_digit());
+    valid_integer && valid_exponent
+}
+impl From<isize> for Number {
+    fn from(n: isize) -> Self {
+        Self::Integer(n)
+    }
+}
+impl From<f64> for Number {
+    fn from(f: f64) -> Self {
+        Self::Float(f)
+    }
+}
+// An `Integer` when it fits.
+impl From<BigInt> for Number {
+    fn from(n: BigInt) -> Self {
+        match isize::try_from(&n) {
+            Ok(n) => Self::Integer(n),
+            Err(_) => Self::BigInt(n),
+        }
+    }
+}
+impl From<Decimal> for Number {
+    fn from(d: Decimal) -> Self {
+        Self::Decimal(d)
+    }
+}
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Integer(a), Self::Integer(b)) => Some(a.cmp(b)),
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+            (Self::Integer(a), Self::Float(b)) => compare_mixed(*a, *b),
+            (Self::Float(a), Self::Integer(b)) => compare_mixed(*b, *a).map(Ordering::reverse),
+            (Self::BigInt(a), Self::BigInt(b)) => Some(a.cmp(b)),
+            (Self::Float(a), _) if !a.is_finite() => compare_infinite(*a),
+            (_, Self::Float(b)) if !b.is_finite() => compare_infinite(*b).map(Ordering::reverse),
+            _ => Some(self.to_decimal()?.cmp(&other.to_decimal()?)),
+        }
+    }
+}
+// Exactly, although `a as f64` may round.
+fn compare_mixed(a: isize, b: f64) -> Option<Ordering> {
+    match (a as f64).partial_cmp(&b)? {
+        // Equal once rounded: either both are exact, or `b` is beyond 2^53 and integral.
+        Ordering::Equal => Some((a as i128).cmp(&(b as i128))),
+        ordering => Some(ordering),
+    }
+}
+// An infinity against any finite number.
+fn compare_infinite(f: f64) -> Option<Ordering> {
+    match f.is_nan() {
+        true => None,
+        false if f > 0.0 => Some(Ordering::Greater),
+        false => Some(Ordering::Less),
+    }
+}
+
+// Why a `Number`, `BigInt` or `Decimal` does not convert to a primitive integer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    number: String,
+    // `None` when the number has a fraction.
+    target: Option<&'static str>,
+}
+impl ConversionError {
+    pub(crate) fn out_of_range(number: &impl fmt::Display, target: &'static str) -> Self {
+        Self {
+            number: number.to_string(),
+            target: Some(target),
+        }
+    }
+    pub(crate) fn not_an_integer(number: &impl fmt::Display) -> Self {
+        Self {
+            number: number.to_string(),
+            target: None,
+        }
+    }
+    pub fn is_out_of_range(&self) -> bool {
+        self.target.is_some()
+    }
+}
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            Some(target) => write!(f, "number {} is out of range for {}", self.number, target),
+            None => write!(f, "number {} is not an integer", self.number),
+        }
+    }
+}
+impl Error for ConversionError {}
+
+macro_rules! primitive {
+    ($($t:ty),*) => {$(
+        impl TryFrom<&Number> for $t {
+            type Error = ConversionError;
+            fn try_from(n: &Number) -> Result<Self, ConversionError> {
+                let out_of_range = || ConversionError::out_of_range(n, stringify!($t));
+                let decimal = match *n {
+                    Number::Integer(i) => return <$t>::try_from(i).map_err(|_| out_of_range()),
+                    Number::BigInt(ref b) => return <$t>::try_from(b).map_err(|_| out_of_range()),
+                    Number::Float(f) if !is_whole(f) && !f.is_infinite() => {
+                        return Err(ConversionError::not_an_integer(n))
+                    }
+                    Number::Float(f) => Decimal::from_f64(f).ok_or_else(out_of_range)?,
+                    Number::Decimal(ref d) => d.clone(),
+                };
+                <$t>::try_from(&decimal).map_err(|error| match error.is_out_of_range() {
+                    true => out_of_range(),
+                    false => ConversionError::not_an_integer(n),
+                })
+            }
+        }
+    )*};
+}
+primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// Floats always show a fraction or an exponent, so that they read back as floats:
+// `1.0`, `0.1`, `1e+21`. Big integers and decimals show their exact digits.
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Integer(n) => write!(f, "{}", n),
+            Self::BigInt(ref n) => write!(f, "{}", n),
+            Self::Decimal(ref d) => write!(f, "{}", d),
+            Self::Float(x) if x == 0.0 && x.is_sign_negative() => f.write_str("-0.0"),
+            Self::Float(x) => {
+                let text = ecmascript(x);
+                f.write_str(&text)?;
+                match text.contains(['.', 'e', 'N', 'I']) {
+                    true => Ok(()),
+                    false => f.write_str(".0"),
+                }
+            }
+        }
+    }
+}
+
+// Rounding, which `core` leaves to `std` as it may call into the system's libm.
+// Floats of 2^52 and beyond have no fraction, and all others fit an `i64`.
+const WHOLE: f64 = 4_503_599_627_370_496.0;
+pub(crate) fn trunc(x: f64) -> f64 {
+    match x.abs() < WHOLE {
+        true => x as i64 as f64,
+        false => x,
+    }
+}
+pub(crate) fn floor(x: f64) -> f64 {
+    let t = trunc(x);
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+pub(crate) fn ceil(x: f64) -> f64 {
+    let t = trunc(x);
+    if t < x {
+        t + 1.0
+    } else {
+        t
+    }
+}
+// Whether `x` is finite and has no fraction.
+fn is_whole(x: f64) -> bool {
+    x.is_finite() && trunc(x) == x
+}
+
+// `x` as ECMAScript's `Number.prototype.toString` writes it: the shortest digits that
+// read back as `x`, in positional notation from 1e-6 up to 1e21 and as `1.5e+21`
+// beyond. `NaN`, `Infinity` and `-Infinity`, which json has no way to write, are
+// written as ECMAScript does.
+pub(crate) fn ecmascript(x: f64) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x.is_infinite() {
+        return match x > 0.0 {
+            true => "Infinity".to_string(),
+            false => "-Infinity".to_string(),
+        };
+    }
+    if x == 0.0 {
+        // Negative zero included.
+        return "0".to_string();
+    }
+    let (digits, exponent) = print::shortest(x.abs());
+    let digits = digits.to_string();
+    let k = digits.len() as i32;
+    // The position of the decimal point relative to the start of `digits`.
+    let point = exponent + k;
+    let mut out = String::with_capacity(k as usize + 8);
+    if x < 0.0 {
+        out.push('-');
+    }
+    if k <= point && point <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((point - k) as usize));
+    } else if 0 < point && point <= 21 {
+        out.push_str(&digits[..point as usize]);
+        out.push('.');
+        out.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-point as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let exponent = point - 1;
+        let sign = if exponent < 0 { '-' } else { '+' };
+        out.push_str(&format!("e{}{}", sign, exponent.abs()));
+    }
+    out
+}
@@ -0,0 +1,273 @@
+use core::cmp::Ordering;
+
+use super::bignum::Big;
+use super::tables::{LARGEST_POWER_OF_FIVE, POWERS_OF_FIVE_128, SMALLEST_POWER_OF_FIVE};
+
+const MANTISSA_BITS: u32 = 52;
+const MANTISSA_MASK: u64 = (1 << MANTISSA_BITS) - 1;
+const INFINITY_BITS: u64 = 0x7ff0_0000_0000_0000;
+// Every halfway point between two doubles has at most 767 significant digits, so the
+// digits past these only matter by being zeros or not.
+const MAX_DIGITS: usize = 768;
+
+// The powers of ten that are exact doubles.
+const POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+// The double nearest to a decimal literal, ties to even: `-?digits[.digits][e[+-]digits]`
+// where either side of the point may be empty. `None` if it is malformed; magnitudes
+// too large for a double give an infinity.
+//
+// Three steps, each only taken when the one before can't tell: Clinger's fast path
+// for small significands and exponents, the Eisel-Lemire algorithm on the first 19
+// significant digits, and an exact comparison of the literal with halfway points
+// between doubles in big integers.
+pub(crate) fn parse_float(literal: &str) -> Option<f64> {
+    let (negative, unsigned) = match literal.as_bytes().first()? {
+        b'-' => (true, &literal[1..]),
+        b'+' => (false, &literal[1..]),
+        _ => (false, literal),
+    };
+    let (significand, exponent) = match unsigned.find(['e', 'E']) {
+        Some(e) => (&unsigned[..e], parse_exponent(&unsigned[e + 1..])?),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if integer.len() + fraction.len() == 0 || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+    let digits = Digits { integer, fraction };
+    let bits = to_bits(&digits, exponent - fraction.len() as i64);
+    let magnitude = f64::from_bits(bits);
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+// Saturates far beyond where every double rounds to zero or infinity, so that
+// `1e99999999999999999999` needs no big integers.
+fn parse_exponent(s: &str) -> Option<i64> {
+    let (negative, digits) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let magnitude = digits
+        .bytes()
+        .fold(0i64, |n, b| (n * 10 + (b - b'0') as i64).min(1 << 40));
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+struct Digits<'s> {
+    integer: &'s str,
+    fraction: &'s str,
+}
+impl<'s> Digits<'s> {
+    // The digits of both parts, leading zeros skipped.
+    fn significant(&self) -> impl Iterator<Item = u8> + 's {
+        self.integer
+            .bytes()
+            .chain(self.fraction.bytes())
+            .map(|b| b - b'0')
+            .skip_while(|&d| d == 0)
+    }
+}
+
+// The bits of the double nearest to `digits * 10^exponent`.
+fn to_bits(digits: &Digits<'_>, exponent: i64) -> u64 {
+    // The first 19 significant digits fit a u64; `w * 10^q` is then the literal with
+    // the others dropped.
+    let (mut w, mut taken, mut dropped, mut truncated) = (0u64, 0, 0i64, false);
+    for digit in digits.significant() {
+        if taken < 19 {
+            w = w * 10 + digit as u64;
+            taken += 1;
+        } else {
+            dropped += 1;
+            truncated |= digit != 0;
+        }
+    }
+    if w == 0 {
+        return 0;
+    }
+    let q = exponent + dropped;
+    if !truncated && (-22..=22).contains(&q) && w <= 1 << 53 {
+        let w = w as f64;
+        let f = match q < 0 {
+            true => w / POWERS_OF_TEN[-q as usize],
+            false => w * POWERS_OF_TEN[q as usize],
+        };
+        return f.to_bits();
+    }
+    let (bits, exact) = eisel_lemire(q, w);
+    if exact && !truncated {
+        return bits;
+    }
+    // The literal lies between `w * 10^q` and `(w + 1) * 10^q`.
+    if exact && truncated && eisel_lemire(q, w + 1) == (bits, true) {
+        return bits;
+    }
+    refine(bits, digits, exponent)
+}
+
+// The Eisel-Lemire algorithm: `w * 10^q` rounded to a double, from a 128-bit
+// approximation of `w * 5^q`. The flag is false when the approximation was too
+// coarse to tell which way to round, and the bits then only an estimate off by at
+// most one unit in the last place.
+fn eisel_lemire(q: i64, w: u64) -> (u64, bool) {
+    if w == 0 || q < SMALLEST_POWER_OF_FIVE {
+        return (0, true);
+    }
+    if q > LARGEST_POWER_OF_FIVE {
+        return (INFINITY_BITS, true);
+    }
+    let zeros = w.leading_zeros();
+    let w = w << zeros;
+    let (lo, hi) = product(q, w);
+    // With all ones in the low word, the truncated product may be off by one, except
+    // for the powers of five whose table entries are exact enough.
+    let exact = lo != u64::MAX || (-27..=55).contains(&q);
+    let upper_bit = (hi >> 63) as i32;
+    let mut mantissa = hi >> (upper_bit + 64 - MANTISSA_BITS as i32 - 3);
+    // The biased exponent: floor(log2(10^q)) + 63, and the bias of 1023.
+    let mut power2 =
+        (((q as i32).wrapping_mul(152_170 + 65_536) >> 16) + 63) + upper_bit - zeros as i32 + 1023;
+    if power2 <= 0 {
+        // Subnormal.
+        if -power2 + 1 >= 64 {
+            return (0, exact);
+        }
+        mantissa >>= -power2 + 1;
+        mantissa += mantissa & 1;
+        mantissa >>= 1;
+        let power2 = (mantissa >= 1 << MANTISSA_BITS) as u64;
+        return (power2 << MANTISSA_BITS | (mantissa & MANTISSA_MASK), exact);
+    }
+    // Exactly halfway between two doubles: round to even rather than up.
+    if lo <= 1
+        && (-4..=23).contains(&q)
+        && mantissa & 3 == 1
+        && mantissa << (upper_bit + 64 - MANTISSA_BITS as i32 - 3) == hi
+    {
+        mantissa &= !1;
+    }
+    mantissa += mantissa & 1;
+    mantissa >>= 1;
+    if mantissa >= 2 << MANTISSA_BITS {
+        mantissa = 1 << MANTISSA_BITS;
+        power2 += 1;
+    }
+    if power2 >= 0x7ff {
+        return (INFINITY_BITS, exact);
+    }
+    (
+        (power2 as u64) << MANTISSA_BITS | (mantissa & MANTISSA_MASK),
+        exact,
+    )
+}
+
+// The high 128 bits of `w * 5^q`, or an underestimate of them by one.
+fn product(q: i64, w: u64) -> (u64, u64) {
+    let mask = u64::MAX >> (MANTISSA_BITS + 3);
+    let (hi5, lo5) = POWERS_OF_FIVE_128[(q - SMALLEST_POWER_OF_FIVE) as usize];
+    let first = w as u128 * hi5 as u128;
+    let (mut lo, mut hi) = (first as u64, (first >> 64) as u64);
+    if hi & mask == mask {
+        let second = ((w as u128 * lo5 as u128) >> 64) as u64;
+        lo = lo.wrapping_add(second);
+        if second > lo {
+            hi += 1;
+        }
+    }
+    (lo, hi)
+}
+
+// Moves the estimate `bits` to the double nearest to `digits * 10^exponent`,
+// comparing the literal with the halfway points on both sides of it exactly.
+fn refine(mut bits: u64, digits: &Digits<'_>, exponent: i64) -> u64 {
+    let (n, p) = big_significand(digits, exponent);
+    loop {
+        // Ties go to the even mantissa, and odd bits are odd mantissas.
+        let odd = bits & 1 == 1;
+        if bits < INFINITY_BITS {
+            match compare_halfway(&n, p, bits) {
+                Ordering::Greater => {
+                    bits += 1;
+                    continue;
+                }
+                Ordering::Equal if odd => {
+                    bits += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if bits > 0 {
+            match compare_halfway(&n, p, bits - 1) {
+                Ordering::Less => {
+                    bits -= 1;
+                    continue;
+                }
+                Ordering::Equal if odd => {
+                    bits -= 1;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        return bits;
+    }
+}
+
+// The significant digits as an integer `n`, with `n * 10^p` the literal. Past
+// `MAX_DIGITS` digits, a non-zero rest is kept as one more `1` digit.
+fn big_significand(digits: &Digits<'_>, exponent: i64) -> (Big, i64) {
+    let mut n = Big::default();
+    let (mut count, mut dropped, mut sticky) = (0, 0i64, false);
+    for digit in digits.significant() {
+        if count < MAX_DIGITS {
+            n.mul_add_small(10, digit as u32);
+            count += 1;
+        } else {
+            dropped += 1;
+            sticky |= digit != 0;
+        }
+    }
+    let mut p = exponent + dropped;
+    if sticky {
+        n.mul_add_small(10, 1);
+        p -= 1;
+    }
+    (n, p)
+}
+
+// How `n * 10^p` compares with the point halfway between the double of `bits` and
+// the next one up.
+fn compare_halfway(n: &Big, p: i64, bits: u64) -> Ordering {
+    let biased = bits >> MANTISSA_BITS;
+    let (mantissa, power2) = match biased {
+        0 => (bits & MANTISSA_MASK, -1074),
+        _ => (
+            bits & MANTISSA_MASK | 1 << MANTISSA_BITS,
+            biased as i64 - 1075,
+        ),
+    };
+    // `(2m + 1) * 2^(e - 1)`.
+    let (mut lhs, mut rhs) = (n.clone(), Big::from_u64(2 * mantissa + 1));
+    let power2 = power2 - 1;
+    if p >= 0 {
+        lhs.mul_pow5(p as u64);
+    } else {
+        rhs.mul_pow5(-p as u64);
+    }
+    if p >= power2 {
+        lhs.shl((p - power2) as u64);
+    } else {
+        rhs.shl((power2 - p) as u64);
+    }
+    lhs.cmp(&rhs)
+}
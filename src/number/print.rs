@@ -0,0 +1,162 @@
+use super::tables::{POW5_INV_SPLIT, POW5_SPLIT};
+
+const MANTISSA_BITS: u32 = 52;
+const BIAS: i32 = 1023;
+const POW5_INV_BITCOUNT: i32 = 125;
+const POW5_BITCOUNT: i32 = 125;
+
+// The shortest `digits * 10^exponent` that parses back to `f`, the nearest to `f` of
+// those when there are several: Ulf Adams' Ryu algorithm. `f` must be finite and
+// positive.
+pub(crate) fn shortest(f: f64) -> (u64, i32) {
+    debug_assert!(f.is_finite() && f > 0.0, "no shortest digits for {}", f);
+    let bits = f.to_bits();
+    let ieee_mantissa = bits & ((1 << MANTISSA_BITS) - 1);
+    let ieee_exponent = (bits >> MANTISSA_BITS) as i32;
+    // `f` is `m2 * 2^e2`, with two more bits of room to write the halfway points to
+    // its neighbours as integers.
+    let (m2, e2) = match ieee_exponent {
+        0 => (ieee_mantissa, 1 - BIAS - MANTISSA_BITS as i32 - 2),
+        _ => (
+            ieee_mantissa | 1 << MANTISSA_BITS,
+            ieee_exponent - BIAS - MANTISSA_BITS as i32 - 2,
+        ),
+    };
+    // Bounds belong to the interval of `f` when its mantissa is even, as ties to even
+    // then parse them back to `f`.
+    let accept_bounds = m2 & 1 == 0;
+    let mv = 4 * m2;
+    // The gap below is half as wide at the powers of two.
+    let mm_shift = (ieee_mantissa != 0 || ieee_exponent <= 1) as u64;
+
+    // `vr`, `vp` and `vm`: `f` and the halfway points above and below it, times
+    // `10^-e10`.
+    let (mut vr, mut vp, mut vm, e10);
+    let (mut vm_trailing_zeros, mut vr_trailing_zeros) = (false, false);
+    if e2 >= 0 {
+        let q = log10_pow2(e2) - (e2 > 3) as i32;
+        e10 = q;
+        let k = POW5_INV_BITCOUNT + pow5_bits(q) - 1;
+        let i = -e2 + q + k;
+        let multiplier = POW5_INV_SPLIT[q as usize];
+        vr = mul_shift(4 * m2, multiplier, i);
+        vp = mul_shift(4 * m2 + 2, multiplier, i);
+        vm = mul_shift(4 * m2 - 1 - mm_shift, multiplier, i);
+        if q <= 21 {
+            // Only one of `mv`, `mp` and `mm` can be a multiple of 5, if any.
+            if mv % 5 == 0 {
+                vr_trailing_zeros = multiple_of_pow5(mv, q);
+            } else if accept_bounds {
+                vm_trailing_zeros = multiple_of_pow5(mv - 1 - mm_shift, q);
+            } else {
+                vp -= multiple_of_pow5(mv + 2, q) as u64;
+            }
+        }
+    } else {
+        let q = log10_pow5(-e2) - (-e2 > 1) as i32;
+        e10 = q + e2;
+        let i = -e2 - q;
+        let k = pow5_bits(i) - POW5_BITCOUNT;
+        let j = q - k;
+        let multiplier = POW5_SPLIT[i as usize];
+        vr = mul_shift(4 * m2, multiplier, j);
+        vp = mul_shift(4 * m2 + 2, multiplier, j);
+        vm = mul_shift(4 * m2 - 1 - mm_shift, multiplier, j);
+        if q <= 1 {
+            // `mv` has at least q trailing zero bits: `vr` is exact.
+            vr_trailing_zeros = true;
+            if accept_bounds {
+                vm_trailing_zeros = mm_shift == 1;
+            } else {
+                vp -= 1;
+            }
+        } else if q < 63 {
+            vr_trailing_zeros = multiple_of_pow2(mv, q);
+        }
+    }
+
+    // Drops digits while `vm` and `vp` still differ, rounding `vr` as they go.
+    let mut removed = 0;
+    let mut last_removed = 0;
+    let output = if vm_trailing_zeros || vr_trailing_zeros {
+        // The rare case where the digits dropped so far may all be zeros matters.
+        while vp / 10 > vm / 10 {
+            vm_trailing_zeros &= vm % 10 == 0;
+            vr_trailing_zeros &= last_removed == 0;
+            last_removed = vr % 10;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        if vm_trailing_zeros {
+            while vm % 10 == 0 {
+                vr_trailing_zeros &= last_removed == 0;
+                last_removed = vr % 10;
+                vr /= 10;
+                vm /= 10;
+                removed += 1;
+            }
+        }
+        if vr_trailing_zeros && last_removed == 5 && vr % 2 == 0 {
+            // Exactly halfway: round to even.
+            last_removed = 4;
+        }
+        let round_up = (vr == vm && (!accept_bounds || !vm_trailing_zeros)) || last_removed >= 5;
+        vr + round_up as u64
+    } else {
+        let mut round_up = false;
+        if vp / 100 > vm / 100 {
+            round_up = vr % 100 >= 50;
+            vr /= 100;
+            vp /= 100;
+            vm /= 100;
+            removed += 2;
+        }
+        while vp / 10 > vm / 10 {
+            round_up = vr % 10 >= 5;
+            vr /= 10;
+            vp /= 10;
+            vm /= 10;
+            removed += 1;
+        }
+        vr + (vr == vm || round_up) as u64
+    };
+    // Rounding up may have left zeros: `19` to `20`.
+    let (mut output, mut exponent) = (output, e10 + removed);
+    while output % 10 == 0 {
+        output /= 10;
+        exponent += 1;
+    }
+    (output, exponent)
+}
+
+// ceil(log2(5^e)), or 1 when `e` is 0.
+fn pow5_bits(e: i32) -> i32 {
+    ((e as u32 * 1_217_359) >> 19) as i32 + 1
+}
+// floor(log10(2^e))
+fn log10_pow2(e: i32) -> i32 {
+    ((e as u32 * 78_913) >> 18) as i32
+}
+// floor(log10(5^e))
+fn log10_pow5(e: i32) -> i32 {
+    ((e as u32 * 732_923) >> 20) as i32
+}
+fn multiple_of_pow5(mut value: u64, p: i32) -> bool {
+    let mut count = 0;
+    while value.is_multiple_of(5) {
+        value /= 5;
+        count += 1;
+    }
+    count >= p
+}
+fn multiple_of_pow2(value: u64, p: i32) -> bool {
+    value & ((1 << p) - 1) == 0
+}
+// `(m * multiplier) >> j`, with `multiplier` as (high, low) words.
+fn mul_shift(m: u64, (hi, lo): (u64, u64), j: i32) -> u64 {
+    let low = m as u128 * lo as u128;
+    let high = m as u128 * hi as u128;
+    (((low >> 64) + high) >> (j - 64)) as u64
+}
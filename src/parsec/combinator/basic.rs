@@ -0,0 +1,236 @@
+use alloc::rc::{Rc, Weak};
+use core::cell::OnceCell;
+
+use crate::parsec::{Input, InputError, InputParser, InputResult, ParserError};
+use crate::prelude::*;
+
+pub fn map<I, T, U>(parser: impl InputParser<I, T>, f: impl Fn(T) -> U) -> impl InputParser<I, U> {
+    move |s| parser(s).map(|(remaining, value)| (remaining, f(value)))
+}
+
+// Never fails recoverably: a recoverable failure of `parser` becomes `None`.
+pub fn opt<I: Input, T>(parser: impl InputParser<I, T>) -> impl InputParser<I, Option<T>> {
+    move |s| match parser(s) {
+        Ok((remaining, value)) => Ok((remaining, Some(value))),
+        Err(error) if error.is_fatal() => Err(error),
+        Err(_) => Ok((s, None)),
+    }
+}
+
+// Commits to `parser`: once it is reached, its failures are no longer recoverable, so
+// the enclosing alternatives report this error instead of trying another branch.
+pub fn cut<I: Input, T>(parser: impl InputParser<I, T>) -> impl InputParser<I, T> {
+    move |s| parser(s).map_err(InputError::cut)
+}
+
+// Names what `parser` looks for when it fails without getting past its first char, so
+// that the error of an `alt` reads "Expected one of: string, number, ...".
+pub fn expected<'n, I: Input, T>(
+    name: &'n str,
+    parser: impl InputParser<I, T> + 'n,
+) -> impl InputParser<I, T> + 'n {
+    move |s: I| {
+        parser(s).map_err(|mut error| {
+            if !error.is_fatal() && error.pos() == s.offset() {
+                error.error_mut().set_expected(name.to_string());
+            }
+            error
+        })
+    }
+}
+
+// Runs `parser` without consuming any input.
+pub fn peek<I: Input, T>(parser: impl InputParser<I, T>) -> impl InputParser<I, T> {
+    move |s| parser(s).map(|(_, value)| (s, value))
+}
+
+// Succeeds without consuming anything when `parser` does not match here.
+pub fn not_followed_by<I: Input, T>(parser: impl InputParser<I, T>) -> impl InputParser<I, ()> {
+    move |s: I| match parser(s) {
+        Ok((remaining, _)) => {
+            let found = s.consumed(&remaining);
+            Err(InputError::Failure(
+                s,
+                ParserError::new(
+                    0..s.rem_len() - remaining.rem_len(),
+                    format!("Unexpected `{}`", I::describe(found)),
+                ),
+            ))
+        }
+        Err(error) if error.is_fatal() => Err(error),
+        Err(_) => Ok((s, ())),
+    }
+}
+
+// Returns the slice of input consumed by `parser` instead of its value.
+pub fn recognize<I: Input, T>(parser: impl InputParser<I, T>) -> impl InputParser<I, I::Fragment> {
+    move |s: I| parser(s).map(|(remaining, _)| (remaining, s.consumed(&remaining)))
+}
+
+pub fn eof<I: Input>() -> impl InputParser<I, ()> {
+    |s: I| match s.next_char() {
+        None => Ok((s, ())),
+        Some((c, len)) => {
+            let mut error =
+                ParserError::new(0..len, format!("Expected the end of input, found `{}`", c));
+            error.set_expected("the end of input".to_string());
+            Err(InputError::Failure(s, error))
+        }
+    }
+}
+
+type BoxedParser<'b, I, T> = Box<dyn Fn(I) -> InputResult<I, T> + 'b>;
+
+// A handle on the parser being defined by `recursive`, usable inside its own definition.
+pub struct Recursive<'b, I, T> {
+    parser: Weak<OnceCell<BoxedParser<'b, I, T>>>,
+}
+impl<'b, I, T> Clone for Recursive<'b, I, T> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: self.parser.clone(),
+        }
+    }
+}
+impl<'b, I: 'b, T: 'b> Recursive<'b, I, T> {
+    pub fn parse(&self, s: I) -> InputResult<I, T> {
+        let parser = self
+            .parser
+            .upgrade()
+            .expect("a recursive parser was used after the parser defining it was dropped");
+        let parser = parser
+            .get()
+            .expect("a recursive parser was run while it was being defined");
+        parser(s)
+    }
+    pub fn parser(&self) -> impl InputParser<I, T> + 'b {
+        let handle = self.clone();
+        move |s| handle.parse(s)
+    }
+}
+
+// Ties the knot for self-referential grammars: `define` receives a handle on the parser
+// it is building, e.g. `recursive(|list| between(label("("), opt(list.parser()), label(")")))`.
+pub fn recursive<'b, I: 'b, T: 'b, P>(
+    define: impl FnOnce(Recursive<'b, I, T>) -> P,
+) -> impl InputParser<I, T> + 'b
+where
+    P: InputParser<I, T> + 'b,
+{
+    let cell: Rc<OnceCell<BoxedParser<'b, I, T>>> = Rc::new(OnceCell::new());
+    let parser = define(Recursive {
+        parser: Rc::downgrade(&cell),
+    });
+    let _ = cell.set(Box::new(parser));
+    move |s| (cell.get().unwrap())(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::branch::alt;
+    use crate::parsec::character::{digit, label};
+    use crate::parsec::sequence::pair;
+    use crate::parsec::Remaining;
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    #[test]
+    fn opt_recovers_only_from_recoverable_failures() {
+        let (rem, value) = opt(label("a"))(input("b")).unwrap();
+        assert_eq!((rem.rem, value), ("b", None));
+        let error = opt(cut(label("a")))(input("b")).unwrap_err();
+        assert!(error.is_fatal());
+    }
+
+    #[test]
+    fn cut_stops_the_enclosing_alternatives() {
+        let parser = alt((
+            pair(label("["), cut(label("]"))),
+            pair(label("["), label("x")),
+        ));
+        let error = parser(input("[x")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.pos(), 1);
+        assert_eq!(error.error().message(), "Expected `]` found `x`");
+    }
+
+    #[test]
+    fn merge_prefers_fatal_then_furthest_errors() {
+        let at = |s, pos| Remaining::new(s, pos);
+        let failure = |pos, expected: &str| {
+            let mut error = ParserError::new(0..1, format!("no {}", expected));
+            error.set_expected(expected.to_string());
+            InputError::Failure(at("", pos), error)
+        };
+        let merged = failure(1, "a").merge(failure(2, "b"));
+        assert_eq!(
+            (merged.pos(), merged.error().expected()),
+            (2, &["b".to_string()][..])
+        );
+        let merged = failure(2, "a").merge(failure(1, "b"));
+        assert_eq!(merged.error().expected(), ["a"]);
+        let merged = failure(1, "a").cut().merge(failure(2, "b"));
+        assert!(merged.is_fatal());
+        assert_eq!(merged.error().expected(), ["a"]);
+
+        let merged = failure(1, "a")
+            .merge(failure(1, "b"))
+            .merge(failure(1, "a"));
+        assert!(!merged.is_fatal());
+        assert_eq!(merged.error().expected(), ["a", "b"]);
+        assert_eq!(merged.error().reason(), None);
+        assert_eq!(merged.error().message(), "Expected one of: a, b");
+    }
+
+    #[test]
+    fn expected_names_failures_at_the_start() {
+        let number = expected("number", pair(digit(10), digit(10)));
+        let error = number(input("x")).unwrap_err();
+        assert_eq!(error.error().expected(), ["number"]);
+        // Past the first char, the error is about what went wrong there.
+        let error = number(input("1x")).unwrap_err();
+        assert_eq!(error.error().expected(), ["digit"]);
+    }
+
+    #[test]
+    fn peek_and_not_followed_by_consume_nothing() {
+        let (rem, value) = peek(label("ab"))(input("abc")).unwrap();
+        assert_eq!((rem.rem, value), ("abc", "ab"));
+        let (rem, ()) = not_followed_by(label("b"))(input("abc")).unwrap();
+        assert_eq!(rem.rem, "abc");
+        let error = not_followed_by(label("ab"))(input("abc")).unwrap_err();
+        assert!(!error.is_fatal());
+        assert_eq!(error.pos(), 0);
+        assert_eq!(error.error().message(), "Unexpected `ab`");
+        assert_eq!(error.error().pos_range(), Some(0..2));
+        let error = not_followed_by(cut(label("b")))(input("a")).unwrap_err();
+        assert!(error.is_fatal());
+    }
+
+    #[test]
+    fn recognize_and_eof() {
+        let (rem, text) = recognize(pair(label("a"), digit(10)))(input("a1;")).unwrap();
+        assert_eq!((rem.rem, text), (";", "a1"));
+        assert!(eof()(input("")).is_ok());
+        let error = eof()(input(";")).unwrap_err();
+        assert_eq!(
+            error.error().message(),
+            "Expected the end of input, found `;`"
+        );
+    }
+
+    #[test]
+    fn recursive_parsers_nest() {
+        let parens = recursive(|parens: Recursive<'_, Remaining<'_>, usize>| {
+            let inner = parens.parser();
+            map(opt(pair(label("("), pair(inner, label(")")))), |nested| {
+                nested.map_or(0, |(_, (depth, _))| depth + 1)
+            })
+        });
+        let (rem, depth) = parens(input("((()))x")).unwrap();
+        assert_eq!((rem.rem, depth), ("x", 3));
+    }
+}
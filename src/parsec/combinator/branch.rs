@@ -0,0 +1,132 @@
+use crate::parsec::{Input, InputError, InputParser, InputResult, ParserError};
+use crate::prelude::*;
+
+// A tuple of parsers of the same output type, tried in order by `alt`.
+pub trait Alt<I, T> {
+    fn parse_alt(&self, s: I) -> InputResult<I, T>;
+}
+
+macro_rules! alt_tuple {
+    ($first:ident $first_index:tt, $($parser:ident $index:tt),+) => {
+        impl<I: Input, T, $first: InputParser<I, T>, $($parser: InputParser<I, T>),+> Alt<I, T>
+            for ($first, $($parser,)+)
+        {
+            fn parse_alt(&self, s: I) -> InputResult<I, T> {
+                let result = (self.$first_index)(s);
+                $(
+                    let result = match result {
+                        Err(error) if !error.is_fatal() => {
+                            (self.$index)(s).map_err(|next| error.merge(next))
+                        }
+                        result => result,
+                    };
+                )+
+                result
+            }
+        }
+    };
+}
+alt_tuple!(A 0, B 1);
+alt_tuple!(A 0, B 1, C 2);
+alt_tuple!(A 0, B 1, C 2, D 3);
+alt_tuple!(A 0, B 1, C 2, D 3, E 4);
+alt_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+alt_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+alt_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+// Tries each parser of the tuple in order from the same input, returning the first
+// success or fatal error; if every branch fails, their errors are merged.
+pub fn alt<I, T>(alternatives: impl Alt<I, T>) -> impl InputParser<I, T> {
+    move |s| alternatives.parse_alt(s)
+}
+
+// Like `alt`, for any number of parsers of the same type.
+pub fn choice<I: Input, T, P: InputParser<I, T>>(alternatives: Vec<P>) -> impl InputParser<I, T> {
+    move |s| {
+        let mut errors: Option<InputError<I>> = None;
+        for parser in &alternatives {
+            match parser(s) {
+                Err(error) if !error.is_fatal() => {
+                    errors = Some(match errors {
+                        Some(errors) => errors.merge(error),
+                        None => error,
+                    })
+                }
+                result => return result,
+            }
+        }
+        Err(errors.unwrap_or_else(|| {
+            InputError::Failure(
+                s,
+                ParserError::new(0..0, "No alternative to try".to_string()),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::basic::{cut, expected};
+    use crate::parsec::character::label;
+    use crate::parsec::sequence::{pair, preceded};
+    use crate::parsec::{ParseResult, Remaining};
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    #[test]
+    fn alt_returns_the_first_match() {
+        let parser = alt((label("ab"), label("a"), label("b")));
+        assert_eq!(parser(input("abc")).unwrap().1, "ab");
+        assert_eq!(parser(input("ac")).unwrap().1, "a");
+        assert_eq!(parser(input("b")).unwrap().1, "b");
+    }
+
+    #[test]
+    fn alt_merges_the_errors_of_every_branch() {
+        let parser = alt((
+            expected("true", label("true")),
+            expected("false", label("false")),
+            expected("null", label("null")),
+        ));
+        let error = parser(input("x")).unwrap_err();
+        assert!(!error.is_fatal());
+        assert_eq!(error.error().expected(), ["true", "false", "null"]);
+        assert_eq!(
+            error.error().message(),
+            "Expected one of: true, false, null"
+        );
+    }
+
+    #[test]
+    fn alt_reports_the_branch_that_got_furthest() {
+        let parser = alt((preceded(label("a"), label("b")), label("c")));
+        let error = parser(input("ax")).unwrap_err();
+        assert_eq!(error.pos(), 1);
+        assert_eq!(error.error().message(), "Expected `b` found `x`");
+    }
+
+    #[test]
+    fn alt_stops_at_a_fatal_error() {
+        let parser = alt((
+            pair(label("a"), cut(label("b"))),
+            pair(label("a"), label("c")),
+        ));
+        let error = parser(input("ac")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.error().message(), "Expected `b` found `c`");
+    }
+
+    #[test]
+    fn choice_tries_any_number_of_parsers() {
+        let parser = choice(vec![label("x"), label("y"), label("z")]);
+        assert_eq!(parser(input("z")).unwrap().1, "z");
+        let error = parser(input("w")).unwrap_err();
+        assert_eq!(error.error().expected(), ["`x`", "`y`", "`z`"]);
+        let none = choice(Vec::<fn(Remaining<'_>) -> ParseResult<'_, ()>>::new());
+        let error = none(input("w")).unwrap_err();
+        assert_eq!(error.error().message(), "No alternative to try");
+    }
+}
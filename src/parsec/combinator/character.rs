@@ -1,92 +1,205 @@
-use crate::parsec::{JsonError, Parser, ParserError, Remaining};
+use crate::parsec::{Input, InputError, InputParser, ParserError};
+use crate::prelude::*;
 
-pub fn label<'a>(str_to_match: &'a str) -> impl Parser<'a, &'a str> {
-    move |s: Remaining<'a>| {
-        if str_to_match.len() > s.rem_len() {
-            return Err(JsonError::Failure(
-                s,
-                ParserError::new(
-                    0..s.rem.find(|c| c == '\n').unwrap_or(s.rem.len()),
-                    format!("Expected `{}` found `{}`", str_to_match, &s.rem),
-                ),
-            ));
+// An error showing the next `chars` chars of the input as what was found, without
+// copying the rest of the input into the error.
+fn found<I: Input>(s: I, chars: usize, reason: impl FnOnce(String) -> String) -> ParserError {
+    let end = s.skip_chars(chars);
+    let found = I::describe(s.consumed(&end));
+    ParserError::new(0..s.rem_len() - end.rem_len(), reason(found))
+}
+
+// Matches `tag` exactly. Tags are bytes so that the same parser works on text and
+// binary inputs.
+pub fn label<I: Input, T: AsRef<[u8]>>(tag: T) -> impl InputParser<I, I::Fragment> {
+    move |s: I| {
+        let tag = tag.as_ref();
+        if s.starts_with(tag) {
+            let remaining = s.advance(tag.len());
+            return Ok((remaining, s.consumed(&remaining)));
         }
-        let mut schars = s.rem.chars();
-        let chars_to_match = str_to_match.chars();
-        for i in chars_to_match.into_iter() {
-            if i != schars.next().unwrap() {
-                return Err(JsonError::Failure(
-                    s,
-                    ParserError::new(
-                        0..s.rem.find(|c| c == '\n').unwrap_or(s.rem.len()),
-                        format!(
-                            "Expected `{}` found `{}`",
-                            str_to_match,
-                            &s.rem[..str_to_match.len() - 1]
-                        ),
-                    ),
-                ));
+        let tag = String::from_utf8_lossy(tag);
+        let mut error = found(s, tag.chars().count(), |found| {
+            format!("Expected `{}` found `{}`", tag, found)
+        });
+        error.set_expected(format!("`{}`", tag));
+        Err(InputError::Failure(s, error))
+    }
+}
+
+pub fn digit<I: Input>(base: u32) -> impl InputParser<I, char> {
+    move |s: I| {
+        let mut error = match s.next_char() {
+            Some((c, len)) if c.is_digit(base) => return Ok((s.advance(len), c)),
+            Some((c, len)) => ParserError::new(0..len, format!("{} is not a digit", c)),
+            None => ParserError::new(0..1, "Expected a digit, found nothing".to_string()),
+        };
+        error.set_expected("digit".to_string());
+        Err(InputError::Failure(s, error))
+    }
+}
+
+// The longest prefix whose chars all satisfy `predicate`; may be empty.
+pub fn take_while<I: Input>(predicate: impl Fn(char) -> bool) -> impl InputParser<I, I::Fragment> {
+    move |s: I| {
+        let mut remaining = s;
+        while let Some((c, len)) = remaining.next_char() {
+            if !predicate(c) {
+                break;
             }
+            remaining = remaining.advance(len);
         }
-        Ok((
-            Remaining::new(&s.rem[str_to_match.len()..], s.pos + str_to_match.len()),
-            str_to_match,
-        ))
+        Ok((remaining, s.consumed(&remaining)))
+    }
+}
+// Like `take_while`, but fails when not even one char matches.
+pub fn take_while1<I: Input>(predicate: impl Fn(char) -> bool) -> impl InputParser<I, I::Fragment> {
+    let take = take_while(predicate);
+    move |s: I| {
+        let (remaining, taken) = take(s)?;
+        if remaining.rem_len() == s.rem_len() {
+            let error = found(s, 1, |found| format!("Unexpected `{}`", found));
+            return Err(InputError::Failure(s, error));
+        }
+        Ok((remaining, taken))
     }
 }
 
-pub fn digit<'a>(base: u32) -> impl Parser<'a, char> {
-    move |s: Remaining<'a>| {
-        if let Some(c) = s.rem.chars().nth(0) {
-            if c.is_digit(base) {
-                return Ok((Remaining::new(&s.rem[c.len_utf8()..], s.pos + 1), c));
-            } else {
-                Err(JsonError::Failure(
-                    s,
-                    ParserError::new(0..1, format!("{} is not a digit", c)),
-                ))
+fn char_matching<'c, I: Input>(
+    predicate: impl Fn(char) -> bool + 'c,
+    expected: impl Fn() -> String + 'c,
+) -> impl InputParser<I, char> + 'c {
+    move |s: I| {
+        let mut error = match s.next_char() {
+            Some((c, len)) if predicate(c) => return Ok((s.advance(len), c)),
+            Some((c, len)) => {
+                ParserError::new(0..len, format!("Expected {}, found `{}`", expected(), c))
             }
-        } else {
-            Err(JsonError::Failure(
-                s,
-                ParserError::new(0..1, format!("Expected a digit, found nothing")),
-            ))
-        }
+            None => ParserError::new(0..0, format!("Expected {}, found nothing", expected())),
+        };
+        error.set_expected(expected());
+        Err(InputError::Failure(s, error))
     }
 }
-pub fn string<'a>() -> impl Parser<'a, &'a str> {
-    // This code is clearly not elegant. Any improvements are welcome
+// Any single char of `chars`.
+pub fn one_of<'c, I: Input>(chars: &'c str) -> impl InputParser<I, char> + 'c {
+    char_matching(
+        move |c| chars.contains(c),
+        move || format!("one of `{}`", chars),
+    )
+}
+// Any single char that is not in `chars`.
+pub fn none_of<'c, I: Input>(chars: &'c str) -> impl InputParser<I, char> + 'c {
+    char_matching(
+        move |c| !chars.contains(c),
+        move || format!("anything but `{}`", chars),
+    )
+}
+
+// A double-quoted string, returned with its escapes left as they are.
+pub fn string<I: Input>() -> impl InputParser<I, I::Fragment> {
+    quoted('"')
+}
+// A string delimited by `quote` that can't span lines; escapes are left as they are.
+pub fn quoted<I: Input>(quote: char) -> impl InputParser<I, I::Fragment> {
+    let delimiter = quote.to_string();
     move |s| {
-        label("\"")(s)
-            .and_then(|(remaining, _)| {
-                let mut last_escaped = false;
-                for (k, i) in remaining.rem.chars().enumerate() {
-                    if i == '\"' && !last_escaped {
-                        return Ok((
-                            Remaining::new(&remaining.rem[k..], remaining.pos + k),
-                            &remaining.rem[..k],
-                        ));
-                    } else if i == '\\' {
-                        last_escaped = true;
-                    } else if last_escaped {
-                        last_escaped = false;
-                    } else if i == '\n' {
-                        return Err(JsonError::Unsavable(
-                            remaining.pos,
-                            ParserError::new(0..k, "Unclosed string delimiter".to_string()),
-                        ));
-                    }
-                }
-                Err(JsonError::Unsavable(
-                    remaining.pos,
-                    ParserError::new(
-                        0..remaining.rem.len(),
-                        "Unclosed string delimiter".to_string(),
-                    ),
-                ))
-            })
-            .and_then(|(remaining, val)| {
-                label("\"")(remaining).map(|(remaining, _)| (remaining, val))
-            })
+        let (remaining, _) = label(&delimiter)(s)?;
+        // A scan rather than combinators, so that strings parse without allocating.
+        let mut rest = remaining;
+        loop {
+            match rest.next_char() {
+                Some(('\\', len)) => match rest.advance(len).next_char() {
+                    Some((c, escaped)) if c != '\n' => rest = rest.advance(len + escaped),
+                    _ => break,
+                },
+                Some((c, len)) if c != quote && c != '\n' => rest = rest.advance(len),
+                _ => break,
+            }
+        }
+        let value = remaining.consumed(&rest);
+        match label(&delimiter)(rest) {
+            Ok((rest, _)) => Ok((rest, value)),
+            Err(_) => Err(InputError::Unsavable(
+                remaining,
+                ParserError::new(
+                    0..remaining.rem_len() - rest.rem_len(),
+                    "Unclosed string delimiter".to_string(),
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::{Bytes, Remaining};
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    #[test]
+    fn label_matches_whole_chars() {
+        let (rem, tag) = label("nu")(input("null")).unwrap();
+        assert_eq!((rem.rem, tag), ("ll", "nu"));
+        let error = label("null")(input("nul!")).unwrap_err();
+        assert_eq!(error.error().message(), "Expected `null` found `nul!`");
+        assert_eq!(error.error().expected(), ["`null`"]);
+        // The first byte of `é` is not a char of its own.
+        assert!(label([0xc3])(input("é")).is_err());
+        let (rem, tag) = label([0xc3])(Bytes::new("é".as_bytes())).unwrap();
+        assert_eq!((rem.rem, tag), (&[0xa9][..], &[0xc3][..]));
+    }
+
+    #[test]
+    fn digits_in_a_base() {
+        assert_eq!(digit(16)(input("f")).unwrap().1, 'f');
+        let error = digit(10)(input("f")).unwrap_err();
+        assert_eq!(error.error().message(), "f is not a digit");
+        let error = digit(10)(input("")).unwrap_err();
+        assert_eq!(error.error().message(), "Expected a digit, found nothing");
+    }
+
+    #[test]
+    fn take_while_and_take_while1() {
+        let (rem, taken) = take_while(char::is_alphabetic)(input("héllo1")).unwrap();
+        assert_eq!((rem.rem, taken), ("1", "héllo"));
+        let (rem, taken) = take_while(char::is_alphabetic)(input("1")).unwrap();
+        assert_eq!((rem.rem, taken), ("1", ""));
+        let error = take_while1(char::is_alphabetic)(input("1")).unwrap_err();
+        assert_eq!(error.error().message(), "Unexpected `1`");
+    }
+
+    #[test]
+    fn one_of_and_none_of() {
+        assert_eq!(one_of("+-")(input("-1")).unwrap().1, '-');
+        let error = one_of("+-")(input("1")).unwrap_err();
+        assert_eq!(error.error().message(), "Expected one of `+-`, found `1`");
+        assert_eq!(none_of("\"")(input("a")).unwrap().1, 'a');
+        let error = none_of("\"")(input("")).unwrap_err();
+        assert_eq!(
+            error.error().message(),
+            "Expected anything but `\"`, found nothing"
+        );
+    }
+
+    #[test]
+    fn strings_keep_their_escapes() {
+        let (rem, body) = string()(input(r#""a\"b" x"#)).unwrap();
+        assert_eq!((rem.rem, body), (" x", r#"a\"b"#));
+        let (_, body) = quoted('\'')(input("'it''")).unwrap();
+        assert_eq!(body, "it");
+        assert!(!string()(input("x")).unwrap_err().is_fatal());
+    }
+
+    #[test]
+    fn unclosed_strings_are_fatal() {
+        let error = string()(input("\"ab\ncd\"")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.pos(), 1);
+        assert_eq!(error.error().message(), "Unclosed string delimiter");
+        assert_eq!(error.error().pos_range(), Some(0..2));
     }
 }
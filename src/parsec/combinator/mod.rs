@@ -1,3 +1,6 @@
+pub mod basic;
+pub mod branch;
 pub mod character;
 pub mod multi;
+pub mod sequence;
 pub mod whitespace;
@@ -1,29 +1,256 @@
-use crate::parsec::Parser;
+use crate::parsec::{Input, InputParser, InputResult};
+use crate::prelude::*;
 
-pub fn many<'a, T>(mut predicate: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+// Applies `predicate` until it fails recoverably (or stops consuming input), folding
+// the values into an accumulator. Fatal errors are passed through.
+pub fn fold_many<I: Input, T, A>(
+    predicate: impl InputParser<I, T>,
+    init: impl Fn() -> A,
+    f: impl Fn(A, T) -> A,
+) -> impl InputParser<I, A> {
     move |s| {
-        let mut result = vec![];
+        let mut acc = init();
         let mut remaining = s;
-        while let Ok((rem, v)) = predicate(remaining) {
-            result.push(v);
+        loop {
+            match predicate(remaining) {
+                Ok((rem, v)) => {
+                    let progressed = rem.rem_len() < remaining.rem_len();
+                    acc = f(acc, v);
+                    remaining = rem;
+                    if !progressed {
+                        return Ok((remaining, acc));
+                    }
+                }
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => return Ok((remaining, acc)),
+            }
+        }
+    }
+}
+pub fn many<I: Input, T>(predicate: impl InputParser<I, T>) -> impl InputParser<I, Vec<T>> {
+    fold_many(predicate, Vec::new, |mut values, v| {
+        values.push(v);
+        values
+    })
+}
+pub fn many1<I: Input, T>(predicate: impl InputParser<I, T>) -> impl InputParser<I, Vec<T>> {
+    move |s| {
+        let (remaining, first) = predicate(s)?;
+        many(&predicate)(remaining).map(|(remaining, mut values)| {
+            values.insert(0, first);
+            (remaining, values)
+        })
+    }
+}
+
+// Exactly `n` repetitions.
+pub fn count<I: Input, T>(
+    predicate: impl InputParser<I, T>,
+    n: usize,
+) -> impl InputParser<I, Vec<T>> {
+    move |s| {
+        let mut values = Vec::with_capacity(n);
+        let mut remaining = s;
+        for _ in 0..n {
+            let (rem, v) = predicate(remaining)?;
+            values.push(v);
             remaining = rem;
         }
-        Ok((remaining, result))
+        Ok((remaining, values))
     }
 }
-pub fn many1<'a, T>(mut predicate: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+
+// Repeats `predicate` until `end` matches, returning both.
+pub fn many_till<I: Input, T, E>(
+    predicate: impl InputParser<I, T>,
+    end: impl InputParser<I, E>,
+) -> impl InputParser<I, (Vec<T>, E)> {
     move |s| {
         let mut values = vec![];
-        match predicate(s) {
-            Ok((mut remaining, val)) => {
-                values.push(val);
-                while let Ok((rem, val)) = predicate(remaining.clone()) {
-                    values.push(val);
+        let mut remaining = s;
+        loop {
+            match end(remaining) {
+                Ok((rem, e)) => return Ok((rem, (values, e))),
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => {
+                    let (rem, v) = predicate(remaining)?;
+                    values.push(v);
                     remaining = rem;
                 }
-                Ok((remaining, values))
             }
-            Err(e) => Err(e),
         }
     }
 }
+
+// `operand (operator operand)*`, folded from the left: `1 - 2 - 3` is `(1 - 2) - 3`.
+// An operator must be followed by an operand.
+pub fn chain_left<I: Input, T, O>(
+    operand: impl InputParser<I, T>,
+    operator: impl InputParser<I, O>,
+    fold: impl Fn(T, O, T) -> T,
+) -> impl InputParser<I, T> {
+    move |s| {
+        let (mut remaining, mut value) = operand(s)?;
+        loop {
+            let (rem, o) = match operator(remaining) {
+                Ok(result) => result,
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => return Ok((remaining, value)),
+            };
+            let (rem, rhs) = operand(rem)?;
+            value = fold(value, o, rhs);
+            remaining = rem;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trailing {
+    // A separator must be followed by another element; the element's error is returned.
+    Forbidden,
+    // A separator may end the list, and is consumed.
+    Allowed,
+}
+
+// Zero or more `element`s separated by `separator`.
+pub fn sep_by<I: Input, T, S>(
+    element: impl InputParser<I, T>,
+    separator: impl InputParser<I, S>,
+    trailing: Trailing,
+) -> impl InputParser<I, Vec<T>> {
+    move |s| match element(s) {
+        Ok((remaining, first)) => separated(&element, &separator, trailing, remaining, first),
+        Err(e) if e.is_fatal() => Err(e),
+        Err(_) => Ok((s, vec![])),
+    }
+}
+// One or more `element`s separated by `separator`.
+pub fn sep_by1<I: Input, T, S>(
+    element: impl InputParser<I, T>,
+    separator: impl InputParser<I, S>,
+    trailing: Trailing,
+) -> impl InputParser<I, Vec<T>> {
+    move |s| {
+        let (remaining, first) = element(s)?;
+        separated(&element, &separator, trailing, remaining, first)
+    }
+}
+fn separated<I: Input, T, S>(
+    element: &impl InputParser<I, T>,
+    separator: &impl InputParser<I, S>,
+    trailing: Trailing,
+    mut remaining: I,
+    first: T,
+) -> InputResult<I, Vec<T>> {
+    let mut values = vec![first];
+    loop {
+        let after_separator = match separator(remaining) {
+            Ok((rem, _)) => rem,
+            Err(e) if e.is_fatal() => return Err(e),
+            Err(_) => return Ok((remaining, values)),
+        };
+        match element(after_separator) {
+            Ok((rem, v)) => {
+                values.push(v);
+                remaining = rem;
+            }
+            Err(e) if e.is_fatal() || trailing == Trailing::Forbidden => return Err(e),
+            Err(_) => return Ok((after_separator, values)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::basic::{cut, map};
+    use crate::parsec::character::{digit, label};
+    use crate::parsec::sequence::preceded;
+    use crate::parsec::Remaining;
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    fn number<'a>() -> impl InputParser<Remaining<'a>, i64> {
+        map(digit(10), |c| i64::from(c.to_digit(10).unwrap()))
+    }
+
+    #[test]
+    fn many_stops_at_the_first_failure_or_without_progress() {
+        let (rem, digits) = many(digit(10))(input("123x")).unwrap();
+        assert_eq!((rem.rem, digits), ("x", vec!['1', '2', '3']));
+        let (rem, empty) = many(label(""))(input("x")).unwrap();
+        assert_eq!((rem.rem, empty.len()), ("x", 1));
+        assert!(many1(digit(10))(input("x")).is_err());
+        let error = many(preceded(label("a"), cut(digit(10))))(input("a1ax")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.pos(), 3);
+    }
+
+    #[test]
+    fn count_needs_every_repetition() {
+        let (rem, digits) = count(digit(10), 2)(input("123")).unwrap();
+        assert_eq!((rem.rem, digits), ("3", vec!['1', '2']));
+        assert_eq!(count(digit(10), 2)(input("1x")).unwrap_err().pos(), 1);
+    }
+
+    #[test]
+    fn many_till_stops_at_the_end() {
+        let parser = many_till(digit(10), label(";"));
+        let (rem, (digits, end)) = parser(input("12;3")).unwrap();
+        assert_eq!((rem.rem, digits, end), ("3", vec!['1', '2'], ";"));
+        let (_, (digits, _)) = parser(input(";")).unwrap();
+        assert!(digits.is_empty());
+        let error = parser(input("12x")).unwrap_err();
+        assert_eq!(error.pos(), 2);
+        assert_eq!(error.error().message(), "x is not a digit");
+        let error = many_till(digit(10), cut(label(";")))(input("1;")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.pos(), 0);
+    }
+
+    #[test]
+    fn chain_left_folds_from_the_left() {
+        let parser = chain_left(number(), label("-"), |a, _, b| a - b);
+        let (rem, value) = parser(input("9-2-3;")).unwrap();
+        assert_eq!((rem.rem, value), (";", 4));
+        assert_eq!(parser(input("7")).unwrap().1, 7);
+        // An operator must be followed by an operand.
+        let error = parser(input("9-;")).unwrap_err();
+        assert_eq!(error.pos(), 2);
+    }
+
+    #[test]
+    fn sep_by_forbids_trailing_separators() {
+        let parser = sep_by(digit(10), label(","), Trailing::Forbidden);
+        let (rem, digits) = parser(input("1,2,3]")).unwrap();
+        assert_eq!((rem.rem, digits), ("]", vec!['1', '2', '3']));
+        let (rem, digits) = parser(input("]")).unwrap();
+        assert_eq!((rem.rem, digits), ("]", vec![]));
+        let error = parser(input("1,2,]")).unwrap_err();
+        assert_eq!(error.pos(), 4);
+        assert_eq!(error.error().message(), "] is not a digit");
+    }
+
+    #[test]
+    fn sep_by_consumes_an_allowed_trailing_separator() {
+        let parser = sep_by(digit(10), label(","), Trailing::Allowed);
+        let (rem, digits) = parser(input("1,2,]")).unwrap();
+        assert_eq!((rem.rem, digits), ("]", vec!['1', '2']));
+        let (rem, digits) = parser(input("1]")).unwrap();
+        assert_eq!((rem.rem, digits), ("]", vec!['1']));
+        // Only one: a second separator is not an element.
+        let (rem, _) = parser(input("1,,")).unwrap();
+        assert_eq!(rem.rem, ",");
+        let fatal = sep_by(cut(digit(10)), label(","), Trailing::Allowed);
+        assert!(fatal(input("1,]")).unwrap_err().is_fatal());
+    }
+
+    #[test]
+    fn sep_by1_needs_an_element() {
+        let parser = sep_by1(digit(10), label(","), Trailing::Allowed);
+        assert_eq!(parser(input("]")).unwrap_err().pos(), 0);
+        assert_eq!(parser(input("1,2")).unwrap().1, vec!['1', '2']);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::parsec::InputParser;
+
+pub fn pair<I, A, B>(
+    first: impl InputParser<I, A>,
+    second: impl InputParser<I, B>,
+) -> impl InputParser<I, (A, B)> {
+    move |s| {
+        let (remaining, a) = first(s)?;
+        let (remaining, b) = second(remaining)?;
+        Ok((remaining, (a, b)))
+    }
+}
+
+pub fn preceded<I, A, B>(
+    first: impl InputParser<I, A>,
+    second: impl InputParser<I, B>,
+) -> impl InputParser<I, B> {
+    move |s| {
+        let (remaining, _) = first(s)?;
+        second(remaining)
+    }
+}
+
+pub fn terminated<I, A, B>(
+    first: impl InputParser<I, A>,
+    second: impl InputParser<I, B>,
+) -> impl InputParser<I, A> {
+    move |s| {
+        let (remaining, a) = first(s)?;
+        let (remaining, _) = second(remaining)?;
+        Ok((remaining, a))
+    }
+}
+
+pub fn between<I, O, T, C>(
+    open: impl InputParser<I, O>,
+    parser: impl InputParser<I, T>,
+    close: impl InputParser<I, C>,
+) -> impl InputParser<I, T> {
+    move |s| {
+        let (remaining, _) = open(s)?;
+        let (remaining, value) = parser(remaining)?;
+        let (remaining, _) = close(remaining)?;
+        Ok((remaining, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::character::{digit, label};
+    use crate::parsec::Remaining;
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    #[test]
+    fn sequences_keep_the_values_asked_for() {
+        let (rem, value) = pair(label("a"), digit(10))(input("a1b")).unwrap();
+        assert_eq!((rem.rem, value), ("b", ("a", '1')));
+        assert_eq!(preceded(label("a"), digit(10))(input("a1")).unwrap().1, '1');
+        assert_eq!(
+            terminated(label("a"), digit(10))(input("a1")).unwrap().1,
+            "a"
+        );
+        let parser = between(label("("), digit(10), label(")"));
+        let (rem, value) = parser(input("(7);")).unwrap();
+        assert_eq!((rem.rem, value), (";", '7'));
+    }
+
+    #[test]
+    fn sequences_fail_where_a_part_fails() {
+        assert_eq!(
+            pair(label("a"), digit(10))(input("ax")).unwrap_err().pos(),
+            1
+        );
+        assert_eq!(
+            terminated(label("a"), digit(10))(input("a"))
+                .unwrap_err()
+                .pos(),
+            1
+        );
+        let parser = between(label("("), digit(10), label(")"));
+        let error = parser(input("(7]")).unwrap_err();
+        assert_eq!(error.pos(), 2);
+        assert_eq!(error.error().message(), "Expected `)` found `]`");
+    }
+}
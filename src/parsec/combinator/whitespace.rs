@@ -1,11 +1,7 @@
-use crate::parsec::{Parser, Remaining};
+use crate::parsec::character::take_while;
+use crate::parsec::{Input, InputParser};
 
-pub fn ws<'a>() -> impl Parser<'a, ()> {
-    |s: Remaining<'a>| match s.rem.find(|c: char| !c.is_whitespace()) {
-        Some(index) => Ok((Remaining::new(&s.rem[index..], s.pos + index), ())),
-        None => Ok((
-            Remaining::new(&s.rem[s.rem.len()..s.rem.len()], s.pos + s.rem.len()),
-            (),
-        )),
-    }
+pub fn ws<I: Input>() -> impl InputParser<I, ()> {
+    let spaces = take_while(char::is_whitespace);
+    move |s| spaces(s).map(|(remaining, _)| (remaining, ()))
 }
@@ -1,24 +1,114 @@
-use std::ops::Range;
+use crate::prelude::*;
+use core::ops::Range;
 
 #[derive(Debug, Clone)]
 pub struct ParserError {
     pos_range: Option<Range<usize>>,
     reason: Option<String>,
+    // What the parsers that failed here were looking for, e.g. `string` or `` `[` ``.
+    expected: Vec<String>,
 }
 impl ParserError {
     pub fn new_empty() -> Self {
         Self {
             pos_range: None,
             reason: None,
+            expected: vec![],
         }
     }
     pub fn new(pos_range: Range<usize>, reason: String) -> Self {
         Self {
             pos_range: Some(pos_range),
             reason: Some(reason),
+            expected: vec![],
         }
     }
     pub fn set_reason(&mut self, new_reason: String) {
         self.reason = Some(new_reason);
     }
+    pub fn set_pos_range(&mut self, pos_range: Range<usize>) {
+        self.pos_range = Some(pos_range);
+    }
+    pub fn set_expected(&mut self, expected: String) {
+        self.expected = vec![expected];
+    }
+    pub fn pos_range(&self) -> Option<Range<usize>> {
+        self.pos_range.clone()
+    }
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+    // Combines the errors of two alternatives that failed at the same position. Once
+    // more than one thing was expected, the individual reasons no longer apply.
+    pub fn merge(mut self, other: Self) -> Self {
+        for expected in other.expected {
+            if !self.expected.contains(&expected) {
+                self.expected.push(expected);
+            }
+        }
+        self.pos_range = match (self.pos_range, other.pos_range) {
+            (Some(a), Some(b)) => Some(a.start.min(b.start)..a.end.max(b.end)),
+            (a, b) => a.or(b),
+        };
+        self.reason = if self.expected.len() > 1 {
+            None
+        } else {
+            other.reason.or(self.reason)
+        };
+        self
+    }
+    pub fn message(&self) -> String {
+        match (&self.reason, self.expected.as_slice()) {
+            (Some(reason), _) => reason.clone(),
+            (None, []) => "Unexpected input".to_string(),
+            (None, [expected]) => format!("Expected {}", expected),
+            (None, expected) => format!("Expected one of: {}", expected.join(", ")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expecting(range: Range<usize>, expected: &str) -> ParserError {
+        let mut error = ParserError::new(range, format!("Expected {} here", expected));
+        error.set_expected(expected.to_string());
+        error
+    }
+
+    #[test]
+    fn messages() {
+        assert_eq!(ParserError::new_empty().message(), "Unexpected input");
+        let mut error = ParserError::new_empty();
+        error.set_expected("string".to_string());
+        assert_eq!(error.message(), "Expected string");
+        error.set_reason("Bad string".to_string());
+        assert_eq!(error.message(), "Bad string");
+    }
+
+    #[test]
+    fn merging_collects_what_was_expected() {
+        let error = expecting(0..1, "string")
+            .merge(expecting(0..3, "number"))
+            .merge(expecting(0..2, "string"));
+        assert_eq!(error.expected(), ["string", "number"]);
+        assert_eq!(error.pos_range(), Some(0..3));
+        assert_eq!(error.reason(), None);
+        assert_eq!(error.message(), "Expected one of: string, number");
+    }
+
+    #[test]
+    fn merging_keeps_the_reason_of_a_single_expectation() {
+        let error = expecting(0..1, "string").merge(expecting(1..2, "string"));
+        assert_eq!(error.message(), "Expected string here");
+        let error = ParserError::new(0..1, "first".to_string()).merge(ParserError::new_empty());
+        assert_eq!(error.reason(), Some("first"));
+        assert_eq!(error.pos_range(), Some(0..1));
+        let error = ParserError::new_empty().merge(ParserError::new(2..3, "second".to_string()));
+        assert_eq!(error.reason(), Some("second"));
+    }
 }
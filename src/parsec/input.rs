@@ -0,0 +1,259 @@
+use crate::prelude::*;
+use core::fmt;
+
+// What the combinators need from the text they parse. Positions and lengths are in
+// bytes; `next_char` decodes the next char so that no parser ever splits one.
+pub trait Input: Copy {
+    // A slice of the input: `&str` or `&[u8]`.
+    type Fragment: Copy;
+    // Bytes from the start of the whole input.
+    fn offset(&self) -> usize;
+    // Bytes left.
+    fn rem_len(&self) -> usize;
+    // The next char and its width in bytes. Byte inputs read one char per byte, with
+    // `U+FFFD` for anything that is not ASCII.
+    fn next_char(&self) -> Option<(char, usize)>;
+    fn starts_with(&self, tag: &[u8]) -> bool;
+    // Moves `len` bytes forward.
+    fn advance(&self, len: usize) -> Self;
+    // The next `len` bytes.
+    fn fragment(&self, len: usize) -> Self::Fragment;
+    // A fragment as text, for error messages.
+    fn describe(fragment: Self::Fragment) -> String;
+
+    fn is_empty(&self) -> bool {
+        self.rem_len() == 0
+    }
+    // The input between `self` and a later position of the same input.
+    fn consumed(&self, until: &Self) -> Self::Fragment {
+        self.fragment(self.rem_len() - until.rem_len())
+    }
+    // Moves `chars` chars forward, or to the end.
+    fn skip_chars(&self, chars: usize) -> Self {
+        let mut rest = *self;
+        for _ in 0..chars {
+            match rest.next_char() {
+                Some((_, len)) => rest = rest.advance(len),
+                None => break,
+            }
+        }
+        rest
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Remaining<'a> {
+    pub pos: usize,
+    pub rem: &'a str,
+}
+impl<'a> Remaining<'a> {
+    pub fn new(rem: &'a str, pos: usize) -> Self {
+        Self { rem, pos }
+    }
+    pub fn rem_len(&self) -> usize {
+        self.rem.len()
+    }
+    pub fn advance(&self, len: usize) -> Self {
+        Self::new(&self.rem[len..], self.pos + len)
+    }
+    pub fn consumed(&self, until: &Remaining<'a>) -> &'a str {
+        &self.rem[..self.rem.len() - until.rem.len()]
+    }
+}
+impl<'a> fmt::Display for Remaining<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rem)
+    }
+}
+impl<'a> Input for Remaining<'a> {
+    type Fragment = &'a str;
+    fn offset(&self) -> usize {
+        self.pos
+    }
+    fn rem_len(&self) -> usize {
+        self.rem.len()
+    }
+    fn next_char(&self) -> Option<(char, usize)> {
+        self.rem.chars().next().map(|c| (c, c.len_utf8()))
+    }
+    fn starts_with(&self, tag: &[u8]) -> bool {
+        self.rem.as_bytes().starts_with(tag) && self.rem.is_char_boundary(tag.len())
+    }
+    fn advance(&self, len: usize) -> Self {
+        Remaining::advance(self, len)
+    }
+    fn fragment(&self, len: usize) -> &'a str {
+        &self.rem[..len]
+    }
+    fn describe(fragment: &'a str) -> String {
+        fragment.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bytes<'a> {
+    pub pos: usize,
+    pub rem: &'a [u8],
+}
+impl<'a> Bytes<'a> {
+    pub fn new(rem: &'a [u8]) -> Self {
+        Self { rem, pos: 0 }
+    }
+}
+impl<'a> Input for Bytes<'a> {
+    type Fragment = &'a [u8];
+    fn offset(&self) -> usize {
+        self.pos
+    }
+    fn rem_len(&self) -> usize {
+        self.rem.len()
+    }
+    fn next_char(&self) -> Option<(char, usize)> {
+        self.rem.first().map(|&b| match b.is_ascii() {
+            true => (char::from(b), 1),
+            false => (char::REPLACEMENT_CHARACTER, 1),
+        })
+    }
+    fn starts_with(&self, tag: &[u8]) -> bool {
+        self.rem.starts_with(tag)
+    }
+    fn advance(&self, len: usize) -> Self {
+        Self {
+            rem: &self.rem[len..],
+            pos: self.pos + len,
+        }
+    }
+    fn fragment(&self, len: usize) -> &'a [u8] {
+        &self.rem[..len]
+    }
+    fn describe(fragment: &'a [u8]) -> String {
+        fragment.escape_ascii().to_string()
+    }
+}
+
+// Text that also keeps track of the char offset and of the 1-based line and column
+// (in chars) of the current position.
+#[derive(Debug, Clone, Copy)]
+pub struct Located<'a> {
+    pub rem: &'a str,
+    offset: usize,
+    char_offset: usize,
+    line: usize,
+    column: usize,
+}
+impl<'a> Located<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            rem: input,
+            offset: 0,
+            char_offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+    pub fn char_offset(&self) -> usize {
+        self.char_offset
+    }
+    pub fn line(&self) -> usize {
+        self.line
+    }
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+impl<'a> Input for Located<'a> {
+    type Fragment = &'a str;
+    fn offset(&self) -> usize {
+        self.offset
+    }
+    fn rem_len(&self) -> usize {
+        self.rem.len()
+    }
+    fn next_char(&self) -> Option<(char, usize)> {
+        self.rem.chars().next().map(|c| (c, c.len_utf8()))
+    }
+    fn starts_with(&self, tag: &[u8]) -> bool {
+        self.rem.as_bytes().starts_with(tag) && self.rem.is_char_boundary(tag.len())
+    }
+    fn advance(&self, len: usize) -> Self {
+        let mut next = Self {
+            rem: &self.rem[len..],
+            offset: self.offset + len,
+            ..*self
+        };
+        for c in self.rem[..len].chars() {
+            next.char_offset += 1;
+            if c == '\n' {
+                next.line += 1;
+                next.column = 1;
+            } else {
+                next.column += 1;
+            }
+        }
+        next
+    }
+    fn fragment(&self, len: usize) -> &'a str {
+        &self.rem[..len]
+    }
+    fn describe(fragment: &'a str) -> String {
+        fragment.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::character::{digit, label, string};
+
+    #[test]
+    fn remaining_moves_by_whole_chars() {
+        let s = Remaining::new("é1", 10);
+        assert_eq!(s.next_char(), Some(('é', 2)));
+        let rest = s.skip_chars(1);
+        assert_eq!((rest.rem, rest.offset()), ("1", 12));
+        assert_eq!(Input::consumed(&s, &rest), "é");
+        assert!(!s.starts_with(&[0xc3]));
+        assert!(s.starts_with("é".as_bytes()));
+        assert_eq!(s.skip_chars(5).rem_len(), 0);
+        assert!(s.skip_chars(5).is_empty());
+    }
+
+    #[test]
+    fn bytes_read_one_char_per_byte() {
+        let s = Bytes::new(b"\xffa");
+        assert_eq!(s.next_char(), Some((char::REPLACEMENT_CHARACTER, 1)));
+        let rest = s.advance(1);
+        assert_eq!((rest.next_char(), rest.offset()), (Some(('a', 1)), 1));
+        assert_eq!(s.consumed(&rest), b"\xff");
+        assert_eq!(Bytes::describe(b"\xffa\n"), "\\xffa\\n");
+        assert!(s.starts_with(b"\xff"));
+    }
+
+    #[test]
+    fn located_tracks_lines_columns_and_offsets() {
+        let s = Located::new("a\néb");
+        let rest = s.advance(4);
+        assert_eq!(rest.rem, "b");
+        assert_eq!((rest.offset(), rest.char_offset()), (4, 3));
+        assert_eq!((rest.line(), rest.column()), (2, 2));
+        let start = Located::new("");
+        assert_eq!(
+            (start.line(), start.column(), start.char_offset()),
+            (1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn combinators_keep_non_ascii_positions_right() {
+        let (rest, body) = string()(Located::new("\"日本\"x")).unwrap();
+        assert_eq!((body, rest.rem), ("日本", "x"));
+        assert_eq!(
+            (rest.offset(), rest.char_offset(), rest.column()),
+            (8, 4, 5)
+        );
+        let error = digit(10)(Remaining::new("é", 0)).unwrap_err();
+        assert_eq!(error.error().pos_range(), Some(0..2));
+        let (rest, _) = label("ü")(Located::new("ü\nü")).unwrap();
+        assert_eq!((rest.line(), rest.column()), (1, 2));
+    }
+}
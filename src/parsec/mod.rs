@@ -1,46 +1,109 @@
-use std::fmt;
-
 mod combinator;
 mod error;
+mod input;
+pub mod trace;
 
-pub use crate::parsec::combinator::{character, multi, whitespace};
+pub use crate::parsec::combinator::{basic, branch, character, multi, sequence, whitespace};
 pub use crate::parsec::error::ParserError;
+pub use crate::parsec::input::{Bytes, Input, Located, Remaining};
 
 #[derive(Debug)]
-pub enum JsonError<'a> {
-    Savable(Remaining<'a>),
-    Failure(Remaining<'a>, ParserError),
-    Unsavable(usize, ParserError),
+pub enum InputError<I> {
+    // The parser did not match: the enclosing alternative may try its next branch.
+    Failure(I, ParserError),
+    // A committed branch failed (see `basic::cut`): no other alternative is tried.
+    Unsavable(I, ParserError),
 }
-impl<'a> JsonError<'a> {
-    pub fn rem(&self) -> Remaining<'a> {
+impl<I: Input> InputError<I> {
+    // Where the error was raised.
+    pub fn rem(&self) -> I {
+        match self {
+            Self::Failure(rem, _) | Self::Unsavable(rem, _) => *rem,
+        }
+    }
+    pub fn pos(&self) -> usize {
+        self.rem().offset()
+    }
+    pub fn error(&self) -> &ParserError {
+        match self {
+            Self::Failure(_, error) | Self::Unsavable(_, error) => error,
+        }
+    }
+    pub fn error_mut(&mut self) -> &mut ParserError {
+        match self {
+            Self::Failure(_, error) | Self::Unsavable(_, error) => error,
+        }
+    }
+    pub fn into_error(self) -> ParserError {
+        match self {
+            Self::Failure(_, error) | Self::Unsavable(_, error) => error,
+        }
+    }
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::Unsavable(_, _))
+    }
+    // Commits the error so that it stops every enclosing alternative.
+    pub fn cut(self) -> Self {
         match self {
-            Self::Failure(rem, _) => *rem,
-            Self::Savable(rem) => *rem,
-            Self::Unsavable(_, _) => panic!(
-                "Internal parser error, `Unexpected JsonError.rem() call on an Unsavable variant"
-            ),
+            Self::Failure(rem, error) => Self::Unsavable(rem, error),
+            fatal => fatal,
+        }
+    }
+    // Combines the errors of two alternatives tried from the same input: a fatal error
+    // wins, then the one that got furthest, and errors at the same position merge
+    // what they expected.
+    pub fn merge(self, other: Self) -> Self {
+        if self.is_fatal() {
+            return self;
+        }
+        if other.is_fatal() || other.pos() > self.pos() {
+            return other;
         }
+        if self.pos() > other.pos() {
+            return self;
+        }
+        let rem = self.rem();
+        Self::Failure(rem, self.into_error().merge(other.into_error()))
     }
 }
-pub trait Parser<'a, T> = FnMut(Remaining<'a>) -> Result<(Remaining<'a>, T), JsonError>;
+pub type InputResult<I, T> = Result<(I, T), InputError<I>>;
+// Parsers are plain `Fn`s so that they can be shared, re-entered and boxed by `recursive`.
+pub trait InputParser<I, T> = Fn(I) -> InputResult<I, T>;
 
-#[derive(Debug, Clone, Copy)]
-pub struct Remaining<'a> {
-    pub pos: usize,
-    pub rem: &'a str,
-}
+// The parsers of `&str` the JSON grammar is written with.
+pub type JsonError<'a> = InputError<Remaining<'a>>;
+pub type ParseResult<'a, T> = InputResult<Remaining<'a>, T>;
+pub trait Parser<'a, T> = InputParser<Remaining<'a>, T>;
 
-impl<'a> Remaining<'a> {
-    pub fn new(rem: &'a str, pos: usize) -> Self {
-        Self { rem, pos }
-    }
-    pub fn rem_len(&self) -> usize {
-        self.rem.len()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_value, ParseOptions};
+
+    #[test]
+    fn accessors_work_on_fatal_errors() {
+        let rem = Remaining::new("rest", 3);
+        let mut error = InputError::Unsavable(rem, ParserError::new(0..1, "bad".to_string()));
+        assert!(error.is_fatal());
+        assert_eq!((error.rem().rem, error.pos()), ("rest", 3));
+        error.error_mut().set_reason("worse".to_string());
+        assert_eq!(error.error().reason(), Some("worse"));
+        assert!(error.cut().is_fatal());
+        let error = InputError::Failure(rem, ParserError::new_empty());
+        assert!(!error.is_fatal());
+        assert!(error.cut().is_fatal());
     }
-}
-impl<'a> fmt::Display for Remaining<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.rem)
+
+    #[test]
+    fn json_errors_list_every_value_expected() {
+        let error = parse_value("[1,", &ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            error.message,
+            "Expected one of: string, number, `[`, `{`, true, false, null, found nothing"
+        );
+        assert_eq!(error.range, 3..3);
+        // A string that was started commits to being a string.
+        let error = parse_value("[\"abc", &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.message, "Unclosed string delimiter");
     }
 }
@@ -0,0 +1,312 @@
+// Opt-in tracing of named parsers. With the `trace` feature, `capture` records every
+// `trace`d parser run on the current thread as a call tree; without it `trace` hands the
+// parser back untouched, so the grammar can stay instrumented for free.
+#[cfg(not(feature = "trace"))]
+use crate::parsec::{Input, InputParser};
+
+#[cfg(not(feature = "trace"))]
+pub fn trace<I: Input, T>(
+    _name: &'static str,
+    parser: impl InputParser<I, T>,
+) -> impl InputParser<I, T> {
+    parser
+}
+
+#[cfg(feature = "trace")]
+pub use self::enabled::*;
+
+#[cfg(feature = "trace")]
+mod enabled {
+    use core::cell::RefCell;
+    use core::fmt::Write;
+
+    use crate::parsec::{Input, InputParser};
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Outcome {
+        Matched,
+        Failed(String),
+        Fatal(String),
+    }
+    impl Outcome {
+        fn class(&self) -> &'static str {
+            match self {
+                Self::Matched => "matched",
+                Self::Failed(_) => "failed",
+                Self::Fatal(_) => "fatal",
+            }
+        }
+    }
+
+    // One run of a traced parser. `end` is where it stopped: the end of what it consumed
+    // when it matched, or the position of its error.
+    #[derive(Debug, Clone)]
+    pub struct TraceNode {
+        pub name: &'static str,
+        pub start: usize,
+        pub end: usize,
+        pub outcome: Outcome,
+        pub children: Vec<TraceNode>,
+    }
+
+    #[derive(Debug, Clone, Default)]
+    pub struct Trace {
+        pub roots: Vec<TraceNode>,
+    }
+
+    thread_local! {
+        // The nodes being run, outermost first; the first one collects the roots.
+        static STACK: RefCell<Option<Vec<TraceNode>>> = const { RefCell::new(None) };
+    }
+
+    fn node(name: &'static str, start: usize) -> TraceNode {
+        TraceNode {
+            name,
+            start,
+            end: start,
+            outcome: Outcome::Matched,
+            children: vec![],
+        }
+    }
+
+    // Runs `f`, recording the traced parsers it runs.
+    pub fn capture<R>(f: impl FnOnce() -> R) -> (R, Trace) {
+        let outer = STACK.with(|stack| stack.replace(Some(vec![node("", 0)])));
+        let result = f();
+        let mut stack = STACK.with(|stack| stack.replace(outer)).unwrap_or_default();
+        let roots = stack.drain(..).next().map_or(vec![], |root| root.children);
+        (result, Trace { roots })
+    }
+
+    pub fn trace<I: Input, T>(
+        name: &'static str,
+        parser: impl InputParser<I, T>,
+    ) -> impl InputParser<I, T> {
+        move |s: I| {
+            let capturing = STACK.with(|stack| match stack.borrow_mut().as_mut() {
+                Some(stack) => {
+                    stack.push(node(name, s.offset()));
+                    true
+                }
+                None => false,
+            });
+            if !capturing {
+                return parser(s);
+            }
+            let result = parser(s);
+            let (end, outcome) = match &result {
+                Ok((remaining, _)) => (remaining.offset(), Outcome::Matched),
+                Err(error) if error.is_fatal() => {
+                    (error.pos(), Outcome::Fatal(error.error().message()))
+                }
+                Err(error) => (error.pos(), Outcome::Failed(error.error().message())),
+            };
+            STACK.with(|stack| {
+                if let Some(stack) = stack.borrow_mut().as_mut() {
+                    if stack.len() > 1 {
+                        let mut done = stack.pop().unwrap();
+                        done.end = end;
+                        done.outcome = outcome;
+                        stack.last_mut().unwrap().children.push(done);
+                    }
+                }
+            });
+            result
+        }
+    }
+
+    impl Trace {
+        // One line per parser run, indented by nesting depth.
+        pub fn to_text(&self) -> String {
+            fn write_node(out: &mut String, node: &TraceNode, depth: usize) {
+                let _ = write!(out, "{:indent$}{} ", "", node.name, indent = depth * 2);
+                let _ = match &node.outcome {
+                    Outcome::Matched => writeln!(out, "{}..{} matched", node.start, node.end),
+                    Outcome::Failed(reason) => {
+                        writeln!(out, "{} failed at {}: {:?}", node.start, node.end, reason)
+                    }
+                    Outcome::Fatal(reason) => {
+                        writeln!(out, "{} fatal at {}: {:?}", node.start, node.end, reason)
+                    }
+                };
+                for child in &node.children {
+                    write_node(out, child, depth + 1);
+                }
+            }
+            let mut out = String::new();
+            for root in &self.roots {
+                write_node(&mut out, root, 0);
+            }
+            out
+        }
+
+        // A self-contained page showing `input` next to the call tree; hovering a parser
+        // highlights the part of the input it consumed, or where it failed. `input` must
+        // be the text the traced offsets point into.
+        pub fn to_html(&self, input: &str) -> String {
+            // Offsets are in bytes, JavaScript strings count UTF-16 code units.
+            let utf16 = |offset: usize| {
+                input
+                    .get(..offset)
+                    .map_or(offset, |prefix| prefix.encode_utf16().count())
+            };
+            fn write_node(out: &mut String, node: &TraceNode, utf16: &dyn Fn(usize) -> usize) {
+                let _ = write!(
+                    out,
+                    "<li class=\"{}\" data-start=\"{}\" data-end=\"{}\"><span>{} <code>{}..{}</code>",
+                    node.outcome.class(),
+                    utf16(node.start),
+                    utf16(node.end),
+                    escape(node.name),
+                    node.start,
+                    node.end
+                );
+                if let Outcome::Failed(reason) | Outcome::Fatal(reason) = &node.outcome {
+                    let _ = write!(out, " {}", escape(reason));
+                }
+                out.push_str("</span>");
+                if !node.children.is_empty() {
+                    out.push_str("<ul>");
+                    for child in &node.children {
+                        write_node(out, child, utf16);
+                    }
+                    out.push_str("</ul>");
+                }
+                out.push_str("</li>");
+            }
+            let mut tree = String::new();
+            for root in &self.roots {
+                write_node(&mut tree, root, &utf16);
+            }
+            format!(
+                "{}<pre id=\"input\">{}</pre>\n<ul id=\"tree\">{}</ul>\n{}",
+                HTML_HEAD,
+                escape(input),
+                tree,
+                HTML_SCRIPT
+            )
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>parser trace</title>
+<style>
+body { display: flex; gap: 2em; font-family: monospace; }
+#input { flex: 1; white-space: pre-wrap; border-right: 1px solid #ccc; padding-right: 1em; }
+#tree { flex: 1; }
+li > span { cursor: default; }
+li.matched > span { color: #22863a; }
+li.failed > span { color: #b08800; }
+li.fatal > span { color: #cb2431; font-weight: bold; }
+mark.matched { background: #dcffe4; }
+mark.failed { background: #fff5b1; }
+mark.fatal { background: #ffdce0; }
+</style></head><body>
+"#;
+
+    const HTML_SCRIPT: &str = r#"<script>
+const input = document.getElementById('input');
+const text = input.textContent;
+document.querySelectorAll('#tree li').forEach(node => {
+    node.firstChild.addEventListener('mouseover', () => {
+        const start = Number(node.dataset.start);
+        const end = Math.max(start, Number(node.dataset.end));
+        const mark = document.createElement('mark');
+        mark.className = node.className;
+        mark.textContent = text.slice(start, end) || '‸';
+        input.replaceChildren(text.slice(0, start), mark, text.slice(end));
+    });
+});
+</script></body></html>
+"#;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsec::basic::{cut, recognize};
+    use crate::parsec::branch::alt;
+    use crate::parsec::character::{digit, label};
+    use crate::parsec::sequence::pair;
+    use crate::parsec::Remaining;
+
+    fn input(s: &str) -> Remaining<'_> {
+        Remaining::new(s, 0)
+    }
+
+    #[test]
+    fn traced_parsers_parse_the_same() {
+        let parser = trace(
+            "pair",
+            alt((label("b"), recognize(pair(label("a"), cut(digit(10)))))),
+        );
+        assert_eq!(parser(input("a1;")).unwrap().1, "a1");
+        let error = parser(input("ax")).unwrap_err();
+        assert!(error.is_fatal());
+        assert_eq!(error.pos(), 1);
+    }
+
+    #[cfg(feature = "trace")]
+    fn traced(s: &str) -> Trace {
+        let parser = trace(
+            "value",
+            alt((
+                trace("one", label("1")),
+                trace(
+                    "list",
+                    recognize(pair(label("["), cut(trace("digit", digit(10))))),
+                ),
+            )),
+        );
+        capture(|| parser(input(s))).1
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn text_dumps_indent_the_call_tree() {
+        assert_eq!(
+            traced("1").to_text(),
+            "value 0..1 matched\n  one 0..1 matched\n"
+        );
+        assert_eq!(
+            traced("[x").to_text(),
+            "value 0 fatal at 1: \"x is not a digit\"\n\
+             \x20 one 0 failed at 0: \"Expected `1` found `[`\"\n\
+             \x20 list 0 fatal at 1: \"x is not a digit\"\n\
+             \x20   digit 1 failed at 1: \"x is not a digit\"\n"
+        );
+        // Nothing is recorded outside `capture`.
+        let parser = trace("one", label("1"));
+        assert!(parser(input("1")).is_ok());
+        assert!(capture(|| ()).1.roots.is_empty());
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn html_dumps_escape_and_count_utf16() {
+        let parser = pair(label("é"), trace("angle", label("<")));
+        let (_, trace) = capture(|| parser(input("é<")));
+        let html = trace.to_html("é<");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<pre id=\"input\">é&lt;</pre>"));
+        assert!(html.contains(
+            "<li class=\"matched\" data-start=\"1\" data-end=\"2\"><span>angle <code>2..3</code></span></li>"
+        ));
+        let (_, trace) = capture(|| trace_failure(input(">")));
+        assert!(trace
+            .to_html(">")
+            .contains("<span>angle <code>0..0</code> Expected `&lt;` found `&gt;`</span>"));
+    }
+
+    #[cfg(feature = "trace")]
+    fn trace_failure(s: Remaining<'_>) -> bool {
+        trace("angle", label("<"))(s).is_err()
+    }
+}
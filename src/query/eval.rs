@@ -0,0 +1,567 @@
+use alloc::borrow::Cow;
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+
+use crate::number::{ceil, floor, trunc};
+use crate::prelude::*;
+use crate::query::{BinaryOp, Builtin, Expr, QueryError};
+use crate::{Decimal, JsonObject, JsonValue, Member, Number};
+
+// Filters produce their outputs by calling `emit`, so a long `.[]` never has to be
+// collected before the rest of a pipe sees its first element. Outputs that are part of
+// the input or the filter, like those of paths and literals, borrow them: only what is
+// computed is owned.
+type Emit<'e, 'v> = dyn FnMut(Cow<'v, JsonValue>) -> Result<(), QueryError> + 'e;
+
+pub(crate) fn eval<'v>(
+    expr: &'v Expr,
+    input: &'v JsonValue,
+    emit: &mut Emit<'_, 'v>,
+) -> Result<(), QueryError> {
+    match expr {
+        Expr::Identity => emit(Cow::Borrowed(input)),
+        Expr::Recurse => recurse(input, emit),
+        Expr::Literal(value) => emit(Cow::Borrowed(value)),
+        Expr::Field(target, name) => eval(target, input, &mut |value| emit(field(&value, name)?)),
+        Expr::Index(target, key) => eval(target, input, &mut |value| {
+            eval(key, input, &mut |key| emit(index(&value, &key)?))
+        }),
+        Expr::Slice(target, from, to) => eval(target, input, &mut |value| {
+            bound(from.as_deref(), input, &mut |from| {
+                bound(to.as_deref(), input, &mut |to| {
+                    emit(Cow::Owned(slice(&value, &from, &to)?))
+                })
+            })
+        }),
+        Expr::Iterate(target) => eval(target, input, &mut |value| match value {
+            Cow::Borrowed(JsonValue::Array(values)) => values
+                .iter()
+                .try_for_each(|value| emit(Cow::Borrowed(value))),
+            Cow::Borrowed(JsonValue::Object(object)) => object
+                .iter()
+                .try_for_each(|member| emit(Cow::Borrowed(member.value()))),
+            Cow::Owned(JsonValue::Array(values)) => values
+                .into_iter()
+                .try_for_each(|value| emit(Cow::Owned(value))),
+            Cow::Owned(JsonValue::Object(object)) => object
+                .members
+                .into_iter()
+                .try_for_each(|member| emit(Cow::Owned(member.value))),
+            other => Err(QueryError::new(format!(
+                "Cannot iterate over {}",
+                type_name(&other)
+            ))),
+        }),
+        Expr::Try(body) => {
+            let mut downstream = false;
+            let result = eval(body, input, &mut |value| {
+                emit(value).inspect_err(|_| downstream = true)
+            });
+            match result {
+                Err(error) if downstream => Err(error),
+                _ => Ok(()),
+            }
+        }
+        // What `then` makes of a computed value can't outlive it, so it is owned too.
+        Expr::Pipe(first, then) => eval(first, input, &mut |value| match value {
+            Cow::Borrowed(value) => eval(then, value, emit),
+            Cow::Owned(value) => eval(then, &value, &mut |output| {
+                emit(Cow::Owned(output.into_owned()))
+            }),
+        }),
+        Expr::Comma(first, second) => {
+            eval(first, input, emit)?;
+            eval(second, input, emit)
+        }
+        // The truthy outputs of `first`, or when there are none, or it fails, `second`.
+        Expr::Alternative(first, second) => {
+            let (mut any, mut downstream) = (false, false);
+            let result = eval(first, input, &mut |value| {
+                if !truthy(&value) {
+                    return Ok(());
+                }
+                any = true;
+                emit(value).inspect_err(|_| downstream = true)
+            });
+            match result {
+                Err(error) if downstream => Err(error),
+                _ if any => Ok(()),
+                _ => eval(second, input, emit),
+            }
+        }
+        // Like jq, the right side is the outer loop: `(1, 2) + (10, 20)` is 11, 12, 21, 22.
+        Expr::Binary(lhs, op, rhs) => eval(rhs, input, &mut |b| {
+            eval(lhs, input, &mut |a| emit(Cow::Owned(binary(*op, &a, &b)?)))
+        }),
+        Expr::And(lhs, rhs) => eval(lhs, input, &mut |a| match truthy(&a) {
+            false => emit(Cow::Owned(JsonValue::False)),
+            true => eval(rhs, input, &mut |b| emit(Cow::Owned(boolean(truthy(&b))))),
+        }),
+        Expr::Or(lhs, rhs) => eval(lhs, input, &mut |a| match truthy(&a) {
+            true => emit(Cow::Owned(JsonValue::True)),
+            false => eval(rhs, input, &mut |b| emit(Cow::Owned(boolean(truthy(&b))))),
+        }),
+        Expr::Negate(operand) => eval(operand, input, &mut |value| match value.into_owned() {
+            JsonValue::Number(n) => emit(Cow::Owned(JsonValue::Number(match n {
+                Number::Integer(n) => n
+                    .checked_neg()
+                    .map_or(Number::Float(-(n as f64)), Number::Integer),
+                Number::Float(f) => Number::Float(-f),
+                Number::BigInt(n) => Number::from(-n),
+                Number::Decimal(d) => Number::Decimal(-d),
+            }))),
+            other => Err(QueryError::new(format!(
+                "{} cannot be negated",
+                type_name(&other)
+            ))),
+        }),
+        Expr::Array(None) => emit(Cow::Owned(JsonValue::Array(vec![]))),
+        Expr::Array(Some(elements)) => {
+            let mut values = vec![];
+            eval(elements, input, &mut |value| {
+                values.push(value.into_owned());
+                Ok(())
+            })?;
+            emit(Cow::Owned(JsonValue::Array(values)))
+        }
+        Expr::Object(entries) => object(entries, input, &mut vec![], emit),
+        Expr::Call(builtin, arguments) => call(*builtin, arguments, input, emit),
+    }
+}
+
+fn recurse<'v>(value: &'v JsonValue, emit: &mut Emit<'_, 'v>) -> Result<(), QueryError> {
+    emit(Cow::Borrowed(value))?;
+    match value {
+        JsonValue::Array(values) => values.iter().try_for_each(|value| recurse(value, emit)),
+        JsonValue::Object(object) => object
+            .iter()
+            .try_for_each(|member| recurse(member.value(), emit)),
+        _ => Ok(()),
+    }
+}
+
+// A missing slice bound is `null`.
+fn bound<'v>(
+    expr: Option<&'v Expr>,
+    input: &'v JsonValue,
+    emit: &mut Emit<'_, 'v>,
+) -> Result<(), QueryError> {
+    match expr {
+        Some(expr) => eval(expr, input, emit),
+        None => emit(Cow::Owned(JsonValue::Null)),
+    }
+}
+
+// Every combination of the outputs of each key and value, in order.
+fn object<'v>(
+    entries: &'v [(Expr, Expr)],
+    input: &'v JsonValue,
+    members: &mut Vec<Member>,
+    emit: &mut Emit<'_, 'v>,
+) -> Result<(), QueryError> {
+    let ((key, value), rest) = match entries.split_first() {
+        Some(entry) => entry,
+        None => {
+            let mut object = vec![];
+            for member in members.iter() {
+                insert(&mut object, member.clone());
+            }
+            return emit(Cow::Owned(JsonValue::Object(JsonObject::new(object))));
+        }
+    };
+    eval(key, input, &mut |key| {
+        let key = match &*key {
+            JsonValue::String(key) => key,
+            other => {
+                return Err(QueryError::new(format!(
+                    "Object keys must be strings, not {}",
+                    type_name(other)
+                )))
+            }
+        };
+        eval(value, input, &mut |value| {
+            members.push(Member::new(key.as_str(), value.into_owned()));
+            let result = object(rest, input, members, emit);
+            members.pop();
+            result
+        })
+    })
+}
+
+// Adds `member`, replacing the value of a member with the same key in place.
+fn insert(members: &mut Vec<Member>, member: Member) {
+    match members
+        .iter_mut()
+        .find(|m| m.identifier() == member.identifier())
+    {
+        Some(existing) => *existing = member,
+        None => members.push(member),
+    }
+}
+
+fn call<'v>(
+    builtin: Builtin,
+    arguments: &'v [Expr],
+    input: &'v JsonValue,
+    emit: &mut Emit<'_, 'v>,
+) -> Result<(), QueryError> {
+    match builtin {
+        Builtin::Length => emit(Cow::Owned(JsonValue::Number(match input {
+            JsonValue::Null => Number::Integer(0),
+            JsonValue::Number(Number::Integer(n)) => n
+                .checked_abs()
+                .map_or(Number::Float((*n as f64).abs()), Number::Integer),
+            JsonValue::Number(Number::Float(f)) => Number::Float(f.abs()),
+            JsonValue::Number(Number::BigInt(n)) => Number::BigInt(n.abs()),
+            JsonValue::Number(Number::Decimal(d)) => Number::Decimal(d.abs()),
+            JsonValue::String(s) => Number::Integer(s.chars().count() as isize),
+            JsonValue::Array(values) => Number::Integer(values.len() as isize),
+            JsonValue::Object(object) => Number::Integer(object.len() as isize),
+            other => {
+                return Err(QueryError::new(format!(
+                    "{} has no length",
+                    type_name(other)
+                )))
+            }
+        }))),
+        Builtin::Keys => match input {
+            JsonValue::Object(object) => emit(Cow::Owned(JsonValue::Array(
+                sorted_keys(object)
+                    .into_iter()
+                    .map(|key| JsonValue::String(key.to_string()))
+                    .collect(),
+            ))),
+            JsonValue::Array(values) => emit(Cow::Owned(JsonValue::Array(
+                (0..values.len() as isize)
+                    .map(|i| JsonValue::Number(Number::Integer(i)))
+                    .collect(),
+            ))),
+            other => Err(QueryError::new(format!("{} has no keys", type_name(other)))),
+        },
+        Builtin::Not => emit(Cow::Owned(boolean(!truthy(input)))),
+        Builtin::Empty => Ok(()),
+        Builtin::Type => emit(Cow::Owned(JsonValue::String(type_name(input).to_string()))),
+        Builtin::Add => {
+            let values: Vec<&JsonValue> = match input {
+                JsonValue::Array(values) => values.iter().collect(),
+                JsonValue::Object(object) => object.iter().map(Member::value).collect(),
+                JsonValue::Null => vec![],
+                other => {
+                    return Err(QueryError::new(format!(
+                        "Cannot add the elements of {}",
+                        type_name(other)
+                    )))
+                }
+            };
+            let sum = values.into_iter().try_fold(JsonValue::Null, |sum, value| {
+                binary(BinaryOp::Add, &sum, value)
+            })?;
+            emit(Cow::Owned(sum))
+        }
+        Builtin::Select => eval(
+            &arguments[0],
+            input,
+            &mut |condition| match truthy(&condition) {
+                true => emit(Cow::Borrowed(input)),
+                false => Ok(()),
+            },
+        ),
+        Builtin::Has => eval(&arguments[0], input, &mut |key| {
+            let has = match (input, &*key) {
+                (JsonValue::Object(object), JsonValue::String(key)) => object.get(key).is_some(),
+                (JsonValue::Array(values), JsonValue::Number(i)) => {
+                    let i = position(i, floor);
+                    i >= 0 && (i as usize) < values.len()
+                }
+                _ => {
+                    return Err(QueryError::new(format!(
+                        "Cannot check whether {} has {} key",
+                        type_name(input),
+                        type_name(&key)
+                    )))
+                }
+            };
+            emit(Cow::Owned(boolean(has)))
+        }),
+    }
+}
+
+// What `value` holds at `key`, borrowed when `value` is.
+fn index<'v>(
+    value: &Cow<'v, JsonValue>,
+    key: &JsonValue,
+) -> Result<Cow<'v, JsonValue>, QueryError> {
+    Ok(match value {
+        Cow::Borrowed(value) => {
+            element(value, key)?.map_or(Cow::Owned(JsonValue::Null), Cow::Borrowed)
+        }
+        Cow::Owned(value) => Cow::Owned(element(value, key)?.cloned().unwrap_or(JsonValue::Null)),
+    })
+}
+// `index` with a string key, without allocating one.
+fn field<'v>(value: &Cow<'v, JsonValue>, name: &str) -> Result<Cow<'v, JsonValue>, QueryError> {
+    match value {
+        Cow::Borrowed(JsonValue::Object(object)) => Ok(object
+            .get(name)
+            .map_or(Cow::Owned(JsonValue::Null), Cow::Borrowed)),
+        Cow::Owned(JsonValue::Object(object)) => Ok(Cow::Owned(
+            object.get(name).cloned().unwrap_or(JsonValue::Null),
+        )),
+        _ => index(value, &JsonValue::String(name.to_string())),
+    }
+}
+// `None` for what is missing, which reads as `null`.
+fn element<'v>(value: &'v JsonValue, key: &JsonValue) -> Result<Option<&'v JsonValue>, QueryError> {
+    match (value, key) {
+        (JsonValue::Null, JsonValue::String(_) | JsonValue::Number(_)) => Ok(None),
+        (JsonValue::Object(object), JsonValue::String(key)) => Ok(object.get(key)),
+        (JsonValue::Array(values), JsonValue::Number(i)) => {
+            let i = match position(i, floor) {
+                i if i < 0 => (values.len() as isize).saturating_add(i),
+                i => i,
+            };
+            Ok(usize::try_from(i).ok().and_then(|i| values.get(i)))
+        }
+        (value, JsonValue::String(key)) => Err(QueryError::new(format!(
+            "Cannot index {} with \"{}\"",
+            type_name(value),
+            key
+        ))),
+        (value, key) => Err(QueryError::new(format!(
+            "Cannot index {} with {}",
+            type_name(value),
+            type_name(key)
+        ))),
+    }
+}
+
+fn slice(value: &JsonValue, from: &JsonValue, to: &JsonValue) -> Result<JsonValue, QueryError> {
+    let len = match value {
+        JsonValue::Null => return Ok(JsonValue::Null),
+        JsonValue::Array(values) => values.len(),
+        JsonValue::String(s) => s.chars().count(),
+        other => {
+            return Err(QueryError::new(format!(
+                "Cannot slice {}",
+                type_name(other)
+            )))
+        }
+    };
+    // Negative bounds count from the end; both are clamped to the value. Like jq,
+    // fractional bounds widen the slice.
+    let bound = |bound: &JsonValue, default: usize, round: fn(f64) -> f64| match bound {
+        JsonValue::Null => Ok(default),
+        JsonValue::Number(n) => match position(n, round) {
+            n if n < 0 => Ok(len.saturating_sub(n.unsigned_abs())),
+            n => Ok((n as usize).min(len)),
+        },
+        other => Err(QueryError::new(format!(
+            "Slice bounds must be numbers, not {}",
+            type_name(other)
+        ))),
+    };
+    let from = bound(from, 0, floor)?;
+    let to = bound(to, len, ceil)?.max(from);
+    Ok(match value {
+        JsonValue::Array(values) => JsonValue::Array(values[from..to].to_vec()),
+        JsonValue::String(s) => JsonValue::String(s.chars().skip(from).take(to - from).collect()),
+        _ => unreachable!("only arrays and strings have a length here"),
+    })
+}
+
+fn binary(op: BinaryOp, a: &JsonValue, b: &JsonValue) -> Result<JsonValue, QueryError> {
+    use JsonValue::{Array, Null, Number, Object, String};
+
+    let ordering = || compare(a, b);
+    Ok(match (op, a, b) {
+        (BinaryOp::Eq, _, _) => boolean(ordering() == Ordering::Equal),
+        (BinaryOp::Ne, _, _) => boolean(ordering() != Ordering::Equal),
+        (BinaryOp::Lt, _, _) => boolean(ordering() == Ordering::Less),
+        (BinaryOp::Le, _, _) => boolean(ordering() != Ordering::Greater),
+        (BinaryOp::Gt, _, _) => boolean(ordering() == Ordering::Greater),
+        (BinaryOp::Ge, _, _) => boolean(ordering() != Ordering::Less),
+        (BinaryOp::Add, Null, value) | (BinaryOp::Add, value, Null) => value.clone(),
+        (op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul), Number(x), Number(y)) => {
+            Number(arithmetic(op, x, y)?)
+        }
+        (BinaryOp::Add, String(x), String(y)) => String(format!("{}{}", x, y)),
+        (BinaryOp::Add, Array(x), Array(y)) => Array(x.iter().chain(y).cloned().collect()),
+        (BinaryOp::Add, Object(x), Object(y)) => {
+            let mut members: Vec<Member> = x.iter().cloned().collect();
+            for member in y.iter() {
+                insert(&mut members, member.clone());
+            }
+            Object(JsonObject::new(members))
+        }
+        (BinaryOp::Sub, Array(x), Array(y)) => Array(
+            x.iter()
+                .filter(|value| {
+                    !y.iter()
+                        .any(|other| compare(value, other) == Ordering::Equal)
+                })
+                .cloned()
+                .collect(),
+        ),
+        (op @ (BinaryOp::Div | BinaryOp::Rem), Number(x), Number(y)) => {
+            Number(arithmetic(op, x, y)?)
+        }
+        (BinaryOp::Div, String(x), String(y)) => Array(
+            x.split(y.as_str())
+                .map(|part| String(part.to_string()))
+                .collect(),
+        ),
+        (op, a, b) => {
+            let verb = match op {
+                BinaryOp::Add => "added",
+                BinaryOp::Sub => "subtracted",
+                BinaryOp::Mul => "multiplied",
+                _ => "divided",
+            };
+            return Err(QueryError::new(format!(
+                "{} ({}) and {} ({}) cannot be {}",
+                type_name(a),
+                a,
+                type_name(b),
+                b,
+                verb
+            )));
+        }
+    })
+}
+
+// Integers stay exact while they fit; otherwise, like jq, numbers are doubles. `%`
+// truncates both sides to integers first. Big integers and decimals, from parsing with
+// `exact_numbers`, stay exact through `+`, `-` and `*`.
+fn arithmetic(op: BinaryOp, x: &Number, y: &Number) -> Result<Number, QueryError> {
+    let by_zero = match op {
+        BinaryOp::Div => y.as_f64() == 0.0,
+        BinaryOp::Rem => position(y, trunc) == 0,
+        _ => false,
+    };
+    if by_zero {
+        return Err(QueryError::new(format!("{} cannot be divided by zero", x)));
+    }
+    if let (&Number::Integer(a), &Number::Integer(b)) = (x, y) {
+        let exact = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Sub => a.checked_sub(b),
+            BinaryOp::Mul => a.checked_mul(b),
+            BinaryOp::Div if a.checked_rem(b) == Some(0) => a.checked_div(b),
+            _ => None,
+        };
+        if let Some(n) = exact {
+            return Ok(Number::Integer(n));
+        }
+    } else if let (true, Some(a), Some(b)) = (
+        matches!(op, BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul),
+        exact(x),
+        exact(y),
+    ) {
+        let result = match op {
+            BinaryOp::Add => &a + &b,
+            BinaryOp::Sub => &a - &b,
+            _ => &a * &b,
+        };
+        // Integers are decimals with an exponent of zero, and so are their results.
+        return Ok(match x.is_integer() && y.is_integer() {
+            true => Number::from(result.mantissa().clone()),
+            false => Number::Decimal(result),
+        });
+    }
+    let (a, b) = (x.as_f64(), y.as_f64());
+    let (result, symbol) = match op {
+        BinaryOp::Add => (a + b, "+"),
+        BinaryOp::Sub => (a - b, "-"),
+        BinaryOp::Mul => (a * b, "*"),
+        BinaryOp::Div => (a / b, "/"),
+        _ => {
+            let (a, b) = (position(x, trunc), position(y, trunc));
+            return Ok(Number::Integer(a.wrapping_rem(b)));
+        }
+    };
+    match result.is_finite() {
+        true => Ok(Number::Float(result)),
+        false => Err(QueryError::new(format!("`{}` overflowed on {}", symbol, x))),
+    }
+}
+// A number used as an index or bound, rounded with `round` and saturated.
+fn position(n: &Number, round: fn(f64) -> f64) -> isize {
+    match *n {
+        Number::Integer(n) => n,
+        _ => round(n.as_f64()) as isize,
+    }
+}
+// `n` exactly, unless it is a float or its exponent is so large that aligning it with
+// another number's would take too many digits.
+fn exact(n: &Number) -> Option<Decimal> {
+    match n {
+        Number::Float(_) => None,
+        Number::Decimal(d) if d.exponent().abs() > MAX_EXACT_EXPONENT => None,
+        n => n.to_decimal(),
+    }
+}
+const MAX_EXACT_EXPONENT: i64 = 1000;
+
+// jq's order: null, false, true, numbers, strings, arrays, then objects, which compare
+// their sorted keys first and then their values key by key.
+fn compare(a: &JsonValue, b: &JsonValue) -> Ordering {
+    fn rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Null => 0,
+            JsonValue::False => 1,
+            JsonValue::True => 2,
+            JsonValue::Number(_) => 3,
+            JsonValue::String(_) => 4,
+            JsonValue::Array(_) => 5,
+            JsonValue::Object(_) => 6,
+        }
+    }
+    match (a, b) {
+        (JsonValue::Number(x), JsonValue::Number(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+        (JsonValue::String(x), JsonValue::String(y)) => x.cmp(y),
+        (JsonValue::Array(x), JsonValue::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare(x, y))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (JsonValue::Object(x), JsonValue::Object(y)) => {
+            let (x_keys, y_keys) = (sorted_keys(x), sorted_keys(y));
+            x_keys.cmp(&y_keys).then_with(|| {
+                x_keys
+                    .iter()
+                    .map(|key| compare(x.get(key).unwrap(), y.get(key).unwrap()))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn sorted_keys(object: &JsonObject) -> Vec<&str> {
+    let mut keys: Vec<&str> = object.iter().map(Member::identifier).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+fn truthy(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Null | JsonValue::False)
+}
+fn boolean(b: bool) -> JsonValue {
+    match b {
+        true => JsonValue::True,
+        false => JsonValue::False,
+    }
+}
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::True | JsonValue::False => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
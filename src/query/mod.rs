@@ -0,0 +1,133 @@
+mod eval;
+mod parse;
+
+use core::error::Error;
+use core::fmt;
+
+use crate::parsec::Remaining;
+use crate::prelude::*;
+use crate::{Diagnostic, JsonValue};
+
+// A filter of the jq language, or rather of the subset of it that ops scripts use:
+// paths (`.a.b`, `.[0]`, `.[]`, `.[2:5]`, `..`), pipes and `,`, `?`, array and object
+// construction, arithmetic, comparisons, `and`/`or`, the `//` alternative and the
+// `select`, `map`, `has`, `keys`, `length`, `add`, `type`, `not` and `empty` builtins.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, Diagnostic> {
+        match parse::filter()(Remaining::new(source, 0)) {
+            Ok((_, expr)) => Ok(Self { expr }),
+            Err(error) => Err(Diagnostic::from_error(source, &error)),
+        }
+    }
+    // Runs the filter on `input`, handing each output to `emit` as soon as it is
+    // produced. An error from `emit` stops the run and is returned as is.
+    pub fn run(
+        &self,
+        input: &JsonValue,
+        mut emit: impl FnMut(JsonValue) -> Result<(), QueryError>,
+    ) -> Result<(), QueryError> {
+        eval::eval(&self.expr, input, &mut |value| emit(value.into_owned()))
+    }
+    pub fn outputs(&self, input: &JsonValue) -> Result<Vec<JsonValue>, QueryError> {
+        let mut outputs = vec![];
+        self.run(input, |value| {
+            outputs.push(value);
+            Ok(())
+        })?;
+        Ok(outputs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    message: String,
+}
+impl QueryError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+impl Error for QueryError {}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Identity,
+    // `..`, the input and everything it holds.
+    Recurse,
+    Literal(JsonValue),
+    Field(Box<Expr>, String),
+    // Indices and slice bounds are evaluated against the input of the whole path, so
+    // `.items[.cursor]` reads `.cursor` from the same object as `.items`.
+    Index(Box<Expr>, Box<Expr>),
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    Iterate(Box<Expr>),
+    Try(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Array(Option<Box<Expr>>),
+    // Keys and values, each of which may produce any number of outputs.
+    Object(Vec<(Expr, Expr)>),
+    Call(Builtin, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Builtin {
+    Length,
+    Keys,
+    Not,
+    Empty,
+    Type,
+    Add,
+    Select,
+    Has,
+}
+impl Builtin {
+    // The builtin called `name` and how many arguments it takes.
+    fn named(name: &str) -> Option<(Self, usize)> {
+        Some(match name {
+            "length" => (Self::Length, 0),
+            "keys" => (Self::Keys, 0),
+            "not" => (Self::Not, 0),
+            "empty" => (Self::Empty, 0),
+            "type" => (Self::Type, 0),
+            "add" => (Self::Add, 0),
+            "select" => (Self::Select, 1),
+            "has" => (Self::Has, 1),
+            _ => return None,
+        })
+    }
+}
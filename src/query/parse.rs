@@ -0,0 +1,326 @@
+use crate::parsec::{
+    basic::{cut, eof, expected, map, not_followed_by, opt, recognize, recursive, Recursive},
+    branch::alt,
+    character::{label, string, take_while, take_while1},
+    multi::{chain_left, many, sep_by, sep_by1, Trailing},
+    sequence::{between, pair, preceded, terminated},
+    JsonError, Parser, ParserError, Remaining,
+};
+use crate::prelude::*;
+use crate::query::{BinaryOp, Builtin, Expr};
+use crate::serialize::unescape;
+use crate::{JsonValue, Number};
+
+type Handle<'a> = Recursive<'a, Remaining<'a>, Expr>;
+
+enum Suffix {
+    Field(String),
+    Index(Expr),
+    Slice(Option<Expr>, Option<Expr>),
+    Iterate,
+    Try,
+}
+
+fn fatal_at(s: Remaining<'_>, len: usize, reason: String) -> JsonError<'_> {
+    JsonError::Unsavable(s, ParserError::new(0..len, reason))
+}
+fn boxed(a: Expr, b: Expr) -> (Box<Expr>, Box<Expr>) {
+    (Box::new(a), Box::new(b))
+}
+
+// Whitespace and `#` comments.
+fn space<'a>() -> impl Parser<'a, ()> {
+    let comment = recognize(pair(label("#"), take_while(|c| c != '\n')));
+    map(
+        many(alt((take_while1(char::is_whitespace), comment))),
+        |_| (),
+    )
+}
+fn symbol<'a>(text: &'static str) -> impl Parser<'a, &'a str> {
+    terminated(label(text), space())
+}
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+// `and` or `or`, but not the start of a longer name.
+fn keyword<'a>(name: &'static str) -> impl Parser<'a, &'a str> {
+    terminated(
+        terminated(
+            label(name),
+            not_followed_by(take_while1(is_identifier_char)),
+        ),
+        space(),
+    )
+}
+fn identifier<'a>() -> impl Parser<'a, &'a str> {
+    expected(
+        "identifier",
+        recognize(pair(
+            take_while1(|c: char| c.is_ascii_alphabetic() || c == '_'),
+            take_while(is_identifier_char),
+        )),
+    )
+}
+fn string_literal<'a>() -> impl Parser<'a, String> {
+    map(string(), |raw| unescape(raw).into_owned())
+}
+// What follows the `.` of `.name` or `."name"`.
+fn field_name<'a>() -> impl Parser<'a, String> {
+    terminated(
+        alt((map(identifier(), str::to_string), string_literal())),
+        space(),
+    )
+}
+
+// `12`, `0.5` or `1e3`; a point must be followed by digits, so that `1.foo` is an error
+// rather than a number.
+fn number<'a>() -> impl Parser<'a, Expr> {
+    let digits = take_while1(|c| c.is_ascii_digit());
+    let fraction = opt(pair(label("."), take_while1(|c| c.is_ascii_digit())));
+    let exponent = opt(pair(
+        alt((label("e"), label("E"))),
+        pair(
+            opt(alt((label("+"), label("-")))),
+            take_while1(|c| c.is_ascii_digit()),
+        ),
+    ));
+    let literal = recognize(pair(digits, pair(fraction, exponent)));
+    move |s: Remaining<'a>| {
+        let (rest, literal) = literal(s)?;
+        match Number::from_literal(literal) {
+            Some(n) => Ok((space()(rest)?.0, Expr::Literal(JsonValue::Number(n)))),
+            None => Err(fatal_at(
+                s,
+                literal.len(),
+                format!("Number `{}` is too large", literal),
+            )),
+        }
+    }
+}
+
+// A builtin, `map`, or one of the `null`, `true` and `false` keywords.
+fn call<'a>(pipe: Handle<'a>) -> impl Parser<'a, Expr> {
+    let arguments = opt(between(
+        symbol("("),
+        sep_by1(pipe.parser(), symbol(";"), Trailing::Forbidden),
+        cut(symbol(")")),
+    ));
+    let name = terminated(identifier(), space());
+    move |s: Remaining<'a>| {
+        let (rest, name) = name(s)?;
+        let (rest, arguments) = arguments(rest)?;
+        let mut arguments = arguments.unwrap_or_default();
+        let expr = match (name, arguments.len()) {
+            ("null", 0) => Expr::Literal(JsonValue::Null),
+            ("true", 0) => Expr::Literal(JsonValue::True),
+            ("false", 0) => Expr::Literal(JsonValue::False),
+            // `[.[] | f]`
+            ("map", 1) => {
+                let f = arguments.remove(0);
+                let (each, f) = boxed(Expr::Iterate(Box::new(Expr::Identity)), f);
+                Expr::Array(Some(Box::new(Expr::Pipe(each, f))))
+            }
+            ("map", n) => return Err(arity(s, name, 1, n)),
+            _ => match Builtin::named(name) {
+                Some((builtin, arity)) if arity == arguments.len() => {
+                    Expr::Call(builtin, arguments)
+                }
+                Some((_, expected)) => return Err(arity(s, name, expected, arguments.len())),
+                None => {
+                    return Err(fatal_at(
+                        s,
+                        name.len(),
+                        format!("Unknown function `{}`", name),
+                    ))
+                }
+            },
+        };
+        Ok((rest, expr))
+    }
+}
+fn arity<'a>(s: Remaining<'a>, name: &str, expected: usize, found: usize) -> JsonError<'a> {
+    let arguments = |n| match n {
+        1 => "1 argument".to_string(),
+        n => format!("{} arguments", n),
+    };
+    fatal_at(
+        s,
+        name.len(),
+        format!("`{}` takes {}, not {}", name, arguments(expected), found),
+    )
+}
+
+// Object values are paths or pipes of them, as in jq: `{a: .b | length}`, but
+// `{a: 1 + 2}` needs parentheses.
+fn object_value<'a>(term: Handle<'a>) -> impl Parser<'a, Expr> {
+    chain_left(term.parser(), symbol("|"), |a, _, b| {
+        let (a, b) = boxed(a, b);
+        Expr::Pipe(a, b)
+    })
+}
+// `{a, "b": .c, (.d): 1}`; `{a}` is short for `{a: .a}`.
+fn object<'a>(pipe: Handle<'a>, term: Handle<'a>) -> impl Parser<'a, Expr> {
+    let named = map(
+        pair(
+            alt((
+                terminated(map(identifier(), str::to_string), space()),
+                terminated(string_literal(), space()),
+            )),
+            opt(preceded(symbol(":"), cut(object_value(term.clone())))),
+        ),
+        |(name, value)| {
+            let value =
+                value.unwrap_or_else(|| Expr::Field(Box::new(Expr::Identity), name.clone()));
+            (Expr::Literal(JsonValue::String(name)), value)
+        },
+    );
+    let computed = pair(
+        between(symbol("("), pipe.parser(), cut(symbol(")"))),
+        preceded(cut(symbol(":")), cut(object_value(term))),
+    );
+    map(
+        between(
+            symbol("{"),
+            sep_by(alt((named, computed)), symbol(","), Trailing::Forbidden),
+            cut(symbol("}")),
+        ),
+        Expr::Object,
+    )
+}
+
+fn primary<'a>(pipe: Handle<'a>, term: Handle<'a>) -> impl Parser<'a, Expr> {
+    let dot = map(preceded(label("."), opt(field_name())), |name| match name {
+        Some(name) => Expr::Field(Box::new(Expr::Identity), name),
+        None => Expr::Identity,
+    });
+    alt((
+        expected("`..`", map(symbol(".."), |_| Expr::Recurse)),
+        expected("`.`", terminated(dot, space())),
+        expected("number", number()),
+        expected(
+            "string",
+            map(terminated(string_literal(), space()), |s| {
+                Expr::Literal(JsonValue::String(s))
+            }),
+        ),
+        expected("`(`", between(symbol("("), pipe.parser(), cut(symbol(")")))),
+        expected(
+            "`[`",
+            map(
+                between(symbol("["), opt(pipe.parser()), cut(symbol("]"))),
+                |e| Expr::Array(e.map(Box::new)),
+            ),
+        ),
+        expected("`{`", object(pipe.clone(), term)),
+        call(pipe),
+    ))
+}
+
+// A primary followed by any number of `.name`, `[...]` and `?`.
+fn postfix<'a>(primary: impl Parser<'a, Expr>, pipe: Handle<'a>) -> impl Parser<'a, Expr> {
+    let brackets = map(
+        pair(
+            opt(pipe.parser()),
+            opt(preceded(symbol(":"), opt(pipe.parser()))),
+        ),
+        |bounds| match bounds {
+            (None, None) => Suffix::Iterate,
+            (Some(index), None) => Suffix::Index(index),
+            (from, Some(to)) => Suffix::Slice(from, to),
+        },
+    );
+    let suffix = alt((
+        map(preceded(label("."), field_name()), Suffix::Field),
+        between(symbol("["), cut(brackets), cut(symbol("]"))),
+        map(symbol("?"), |_| Suffix::Try),
+    ));
+    map(pair(primary, many(suffix)), |(expr, suffixes)| {
+        suffixes.into_iter().fold(expr, |expr, suffix| {
+            let expr = Box::new(expr);
+            match suffix {
+                Suffix::Field(name) => Expr::Field(expr, name),
+                Suffix::Index(index) => Expr::Index(expr, Box::new(index)),
+                Suffix::Slice(from, to) => Expr::Slice(expr, from.map(Box::new), to.map(Box::new)),
+                Suffix::Iterate => Expr::Iterate(expr),
+                Suffix::Try => Expr::Try(expr),
+            }
+        })
+    })
+}
+
+fn binary<'a>(
+    operators: impl Parser<'a, BinaryOp>,
+    operand: impl Parser<'a, Expr>,
+) -> impl Parser<'a, Expr> {
+    chain_left(operand, operators, |a, op, b| {
+        let (a, b) = boxed(a, b);
+        Expr::Binary(a, op, b)
+    })
+}
+fn operator<'a>(parser: impl Parser<'a, &'a str>, op: BinaryOp) -> impl Parser<'a, BinaryOp> {
+    map(parser, move |_| op)
+}
+
+// From the loosest to the tightest: `|`, `,`, `//`, `or`, `and`, comparisons, `+` and
+// `-`, `*`, `/` and `%`, then unary minus.
+pub(crate) fn filter<'a>() -> impl Parser<'a, Expr> {
+    let pipe = recursive(|pipe: Handle<'a>| {
+        let inner = pipe.clone();
+        let term = recursive(move |term: Handle<'a>| postfix(primary(inner.clone(), term), inner));
+        let unary = map(pair(many(symbol("-")), term), |(signs, expr)| {
+            signs
+                .iter()
+                .fold(expr, |expr, _| Expr::Negate(Box::new(expr)))
+        });
+        let product = binary(
+            alt((
+                operator(symbol("*"), BinaryOp::Mul),
+                operator(
+                    terminated(terminated(label("/"), not_followed_by(label("/"))), space()),
+                    BinaryOp::Div,
+                ),
+                operator(symbol("%"), BinaryOp::Rem),
+            )),
+            unary,
+        );
+        let sum = binary(
+            alt((
+                operator(symbol("+"), BinaryOp::Add),
+                operator(symbol("-"), BinaryOp::Sub),
+            )),
+            product,
+        );
+        let comparison = binary(
+            alt((
+                operator(symbol("=="), BinaryOp::Eq),
+                operator(symbol("!="), BinaryOp::Ne),
+                operator(symbol("<="), BinaryOp::Le),
+                operator(symbol(">="), BinaryOp::Ge),
+                operator(symbol("<"), BinaryOp::Lt),
+                operator(symbol(">"), BinaryOp::Gt),
+            )),
+            sum,
+        );
+        let and = chain_left(comparison, keyword("and"), |a, _, b| {
+            let (a, b) = boxed(a, b);
+            Expr::And(a, b)
+        });
+        let or = chain_left(and, keyword("or"), |a, _, b| {
+            let (a, b) = boxed(a, b);
+            Expr::Or(a, b)
+        });
+        let alternative = chain_left(or, symbol("//"), |a, _, b| {
+            let (a, b) = boxed(a, b);
+            Expr::Alternative(a, b)
+        });
+        let comma = chain_left(alternative, symbol(","), |a, _, b| {
+            let (a, b) = boxed(a, b);
+            Expr::Comma(a, b)
+        });
+        chain_left(comma, symbol("|"), |a, _, b| {
+            let (a, b) = boxed(a, b);
+            Expr::Pipe(a, b)
+        })
+    });
+    preceded(space(), terminated(pipe, eof()))
+}
@@ -0,0 +1,444 @@
+use alloc::borrow::Cow;
+use core::fmt;
+use core::ops::Range;
+
+use crate::prelude::*;
+use crate::{escape_pointer_token, JsonObject, JsonValue, Member, SpanTree, Spanned};
+
+// How `to_string_with` lays a value out.
+#[derive(Debug, Clone, Default)]
+pub struct Format {
+    // One level of indentation; `None` writes everything on one line, without spaces.
+    pub indent: Option<String>,
+    // Write the members of objects ordered by key instead of in document order.
+    pub sort_keys: bool,
+}
+impl Format {
+    pub fn minified() -> Self {
+        Self::default()
+    }
+    pub fn pretty() -> Self {
+        Self {
+            indent: Some("  ".to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+pub fn to_string(value: &JsonValue) -> String {
+    to_string_with(value, &Format::minified())
+}
+pub fn to_string_pretty(value: &JsonValue) -> String {
+    to_string_with(value, &Format::pretty())
+}
+pub fn to_string_with(value: &JsonValue, format: &Format) -> String {
+    to_string_commented(value, format, &Comments::default())
+}
+// Like `to_string_with`, writing `comments` next to the values they were attached to.
+// Comments need line breaks, so a minified format drops them.
+pub fn to_string_commented(value: &JsonValue, format: &Format, comments: &Comments) -> String {
+    let mut writer = Writer {
+        out: String::new(),
+        format,
+        comments,
+        depth: 0,
+    };
+    let attached = writer.attached("");
+    for comment in attached.leading.iter() {
+        writer.comment_line(comment);
+    }
+    writer.value(value, &mut String::new());
+    for comment in attached.trailing.iter() {
+        writer.trailing_comment(comment);
+    }
+    writer.out
+}
+
+// `s` as a json string literal, quotes included.
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    write_string(&mut out, s);
+    out
+}
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' || c == '\u{7f}' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Decodes the escapes of a string literal as written between its quotes. Escapes that
+// only the lenient and JSON5 modes accept decode to the char they name, `\q` to `q`.
+pub fn unescape(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('\\') {
+        return Cow::Borrowed(raw);
+    }
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('b') => out.push('\u{8}'),
+            Some('f') => out.push('\u{c}'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('t') => out.push('\t'),
+            Some('v') => out.push('\u{b}'),
+            Some('0') => out.push('\0'),
+            Some('x') => match hex(chars.as_str(), 2) {
+                Some(code) => {
+                    out.push(char::from(code as u8));
+                    chars.nth(1);
+                }
+                None => out.push('x'),
+            },
+            Some('u') => match hex(chars.as_str(), 4) {
+                Some(high @ 0xd800..=0xdbff) => {
+                    chars.nth(3);
+                    let low = chars
+                        .as_str()
+                        .strip_prefix("\\u")
+                        .and_then(|rest| hex(rest, 4))
+                        .filter(|low| (0xdc00..=0xdfff).contains(low));
+                    match low {
+                        Some(low) => {
+                            let code = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                            out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                            chars.nth(5);
+                        }
+                        None => out.push(char::REPLACEMENT_CHARACTER),
+                    }
+                }
+                Some(code) => {
+                    out.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    chars.nth(3);
+                }
+                None => out.push('u'),
+            },
+            // A JSON5 line continuation.
+            Some('\r') => {
+                if chars.as_str().starts_with('\n') {
+                    chars.next();
+                }
+            }
+            Some('\n') | Some('\u{2028}') | Some('\u{2029}') => {}
+            Some(c) => out.push(c),
+            None => out.push('\\'),
+        }
+    }
+    Cow::Owned(out)
+}
+fn hex(s: &str, len: usize) -> Option<u32> {
+    let digits = s.get(..len)?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+impl fmt::Display for JsonValue {
+    // `{}` is minified, `{:#}` pretty-printed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.alternate() {
+            true => f.write_str(&to_string_pretty(self)),
+            false => f.write_str(&to_string(self)),
+        }
+    }
+}
+impl fmt::Display for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&JsonValue::Object(self.clone()), f)
+    }
+}
+
+// The comments of a JSON5 document, attached to the JSON Pointer of a nearby value so
+// that they survive reformatting and key sorting.
+#[derive(Debug, Clone, Default)]
+pub struct Comments {
+    attached: HashMap<String, Attached>,
+}
+#[derive(Debug, Clone, Default)]
+struct Attached {
+    // On their own lines, before the value (or before the key of a member).
+    leading: Vec<String>,
+    // After the value, on its line.
+    trailing: Vec<String>,
+    // Inside an array or object, after its last element.
+    closing: Vec<String>,
+}
+
+// One array element or object member of a document, in source order.
+struct Node {
+    pointer: String,
+    start: usize,
+    end: usize,
+    // The range between the brackets, for arrays and objects.
+    inner: Option<Range<usize>>,
+    children: Vec<usize>,
+}
+
+impl Comments {
+    // Finds the comments of `spanned.source` and attaches each to a value:
+    // - to the value before it when it ends a line, `"a": 1, // like this`;
+    // - otherwise to the next value of the innermost array or object holding it;
+    // - or, when nothing follows it there, as a closing comment of that container.
+    pub fn collect(spanned: &Spanned) -> Self {
+        let source = spanned.source;
+        let mut nodes = vec![];
+        flatten(&spanned.value, &spanned.spans, String::new(), &mut nodes);
+        let mut comments = Self::default();
+        for range in comment_ranges(source) {
+            let text = source[range.clone()].to_string();
+            let rest_of_line = source[range.end..].split('\n').next().unwrap_or("").trim();
+            let ends_line = rest_of_line.is_empty()
+                || rest_of_line.starts_with("//")
+                || rest_of_line.starts_with("/*");
+            let before = nodes
+                .iter()
+                .filter(|node| node.end <= range.start)
+                .max_by_key(|node| node.end)
+                .filter(|node| {
+                    let gap = &source[node.end..range.start];
+                    ends_line
+                        && !gap.contains('\n')
+                        && gap.trim_start_matches([' ', '\t', ',']).is_empty()
+                });
+            if let Some(node) = before {
+                comments.entry(&node.pointer).trailing.push(text);
+                continue;
+            }
+            let container = nodes
+                .iter()
+                .filter(|node| {
+                    node.inner
+                        .as_ref()
+                        .is_some_and(|inner| inner.start <= range.start && range.end <= inner.end)
+                })
+                .min_by_key(|node| node.end - node.start);
+            match container {
+                Some(container) => {
+                    let next = container
+                        .children
+                        .iter()
+                        .map(|&i| &nodes[i])
+                        .find(|child| child.start >= range.end);
+                    match next {
+                        Some(next) => comments.entry(&next.pointer).leading.push(text),
+                        None => comments.entry(&container.pointer).closing.push(text),
+                    }
+                }
+                None if range.start < nodes[0].start => comments.entry("").leading.push(text),
+                None => comments.entry("").trailing.push(text),
+            }
+        }
+        comments
+    }
+    pub fn is_empty(&self) -> bool {
+        self.attached.is_empty()
+    }
+    fn entry(&mut self, pointer: &str) -> &mut Attached {
+        self.attached.entry(pointer.to_string()).or_default()
+    }
+}
+
+// Pushes `value` and everything it holds to `nodes`, returning the index of `value`.
+fn flatten(value: &JsonValue, spans: &SpanTree, pointer: String, nodes: &mut Vec<Node>) -> usize {
+    let range = spans.range();
+    let index = nodes.len();
+    nodes.push(Node {
+        start: spans.key().map_or(range.start, |key| key.start),
+        end: range.end,
+        inner: match value {
+            JsonValue::Array(_) | JsonValue::Object(_) => Some(range.start + 1..range.end - 1),
+            _ => None,
+        },
+        pointer: pointer.clone(),
+        children: vec![],
+    });
+    let children = match value {
+        JsonValue::Array(values) => values
+            .iter()
+            .zip(spans.children())
+            .enumerate()
+            .map(|(i, (value, spans))| flatten(value, spans, format!("{}/{}", pointer, i), nodes))
+            .collect(),
+        JsonValue::Object(object) => object
+            .iter()
+            .zip(spans.children())
+            .map(|(member, spans)| {
+                let pointer = format!("{}/{}", pointer, escape_pointer_token(member.identifier()));
+                flatten(member.value(), spans, pointer, nodes)
+            })
+            .collect(),
+        _ => vec![],
+    };
+    nodes[index].children = children;
+    index
+}
+
+// The byte ranges of the `//` and `/* */` comments outside of string literals.
+fn comment_ranges(source: &str) -> Vec<Range<usize>> {
+    let bytes = source.as_bytes();
+    let mut ranges = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                let end = source[i..].find('\n').map_or(source.len(), |end| i + end);
+                ranges.push(i..source[..end].trim_end().len());
+                i = end;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let end = source[i + 2..]
+                    .find("*/")
+                    .map_or(source.len(), |end| i + end + 4);
+                ranges.push(i..end);
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+    ranges
+}
+
+struct Writer<'f> {
+    out: String,
+    format: &'f Format,
+    comments: &'f Comments,
+    depth: usize,
+}
+impl<'f> Writer<'f> {
+    fn attached(&self, pointer: &str) -> &'f Attached {
+        const NONE: &Attached = &Attached {
+            leading: vec![],
+            trailing: vec![],
+            closing: vec![],
+        };
+        match self.format.indent {
+            Some(_) => self.comments.attached.get(pointer).unwrap_or(NONE),
+            None => NONE,
+        }
+    }
+    fn newline(&mut self) {
+        if let Some(indent) = &self.format.indent {
+            self.out.push('\n');
+            for _ in 0..self.depth {
+                self.out.push_str(indent);
+            }
+        }
+    }
+    fn comment_line(&mut self, comment: &str) {
+        self.out.push_str(comment);
+        self.newline();
+    }
+    fn trailing_comment(&mut self, comment: &str) {
+        self.out.push(' ');
+        self.out.push_str(comment);
+    }
+    // `pointer` is the JSON Pointer of `value`, only grown when there are comments.
+    fn value(&mut self, value: &JsonValue, pointer: &mut String) {
+        match value {
+            JsonValue::Null => self.out.push_str("null"),
+            JsonValue::True => self.out.push_str("true"),
+            JsonValue::False => self.out.push_str("false"),
+            JsonValue::Number(n) => self.out.push_str(&n.to_string()),
+            JsonValue::String(s) => write_string(&mut self.out, s),
+            JsonValue::Array(values) => self.container(
+                ('[', ']'),
+                values.iter(),
+                pointer,
+                |p, _, i| p.push_str(&i.to_string()),
+                |w, v, p| w.value(v, p),
+            ),
+            JsonValue::Object(object) => {
+                let mut members: Vec<&Member> = object.iter().collect();
+                if self.format.sort_keys {
+                    members.sort_by(|a, b| a.identifier().cmp(b.identifier()));
+                }
+                let separator = match self.format.indent {
+                    Some(_) => ": ",
+                    None => ":",
+                };
+                self.container(
+                    ('{', '}'),
+                    members.iter().copied(),
+                    pointer,
+                    |p, member, _| p.push_str(&escape_pointer_token(member.identifier())),
+                    |w, member, p| {
+                        write_string(&mut w.out, member.identifier());
+                        w.out.push_str(separator);
+                        w.value(member.value(), p);
+                    },
+                );
+            }
+        }
+    }
+    // `token` appends the pointer token of the `i`th item, only needed for comments.
+    fn container<T>(
+        &mut self,
+        (open, close): (char, char),
+        items: impl ExactSizeIterator<Item = T>,
+        pointer: &mut String,
+        token: impl Fn(&mut String, &T, usize),
+        mut write: impl FnMut(&mut Self, T, &mut String),
+    ) {
+        let closing = &self.attached(pointer).closing;
+        if items.len() == 0 && closing.is_empty() {
+            self.out.push(open);
+            self.out.push(close);
+            return;
+        }
+        let with_comments = !self.comments.is_empty() && self.format.indent.is_some();
+        let len = items.len();
+        self.out.push(open);
+        self.depth += 1;
+        for (i, item) in items.enumerate() {
+            let parent = pointer.len();
+            if with_comments {
+                pointer.push('/');
+                token(pointer, &item, i);
+            }
+            let attached = self.attached(pointer);
+            self.newline();
+            for comment in attached.leading.iter() {
+                self.comment_line(comment);
+            }
+            write(self, item, pointer);
+            if i + 1 < len {
+                self.out.push(',');
+            }
+            for comment in attached.trailing.iter() {
+                self.trailing_comment(comment);
+            }
+            pointer.truncate(parent);
+        }
+        for comment in closing.iter() {
+            self.newline();
+            self.out.push_str(comment);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.out.push(close);
+    }
+}
@@ -0,0 +1,196 @@
+use core::fmt;
+use core::ops::Range;
+
+use crate::prelude::*;
+use crate::{
+    array_index, json_with, parse_document, parse_top_level, pointer_tokens, value_at, Builder,
+    Diagnostic, JsonObject, JsonValue, Member, Number, ParseOptions, TreeBuilder,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+impl Location {
+    // Lines and columns start at 1; columns count chars, not bytes.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Self {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+// Mirrors the shape of a parsed `JsonValue`: array elements and object members map to
+// `children` in order, and members also remember where their key was.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpanTree {
+    range: Range<usize>,
+    key: Option<Range<usize>>,
+    children: Vec<SpanTree>,
+}
+impl SpanTree {
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+    pub fn key(&self) -> Option<Range<usize>> {
+        self.key.clone()
+    }
+    pub fn children(&self) -> &[SpanTree] {
+        &self.children
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Spanned<'a> {
+    pub value: JsonValue,
+    pub spans: SpanTree,
+    pub source: &'a str,
+}
+impl<'a> Spanned<'a> {
+    pub fn span_at(&self, pointer: &str) -> Option<&SpanTree> {
+        let mut value = &self.value;
+        let mut spans = &self.spans;
+        for token in pointer_tokens(pointer)? {
+            let index = match value {
+                JsonValue::Object(object) => object.iter().position(|m| m.identifier() == token)?,
+                JsonValue::Array(values) => array_index(&token).filter(|i| *i < values.len())?,
+                _ => return None,
+            };
+            value = match value {
+                JsonValue::Object(object) => object.iter().nth(index)?.value(),
+                _ => value.get_index(index)?,
+            };
+            spans = spans.children.get(index)?;
+        }
+        Some(spans)
+    }
+    pub fn location(&self, offset: usize) -> Location {
+        Location::from_offset(self.source, offset)
+    }
+    // Converts the value, attaching the source location of the failing node to errors.
+    pub fn decode<T: crate::FromJson>(&self) -> Result<T, crate::FromJsonError> {
+        T::from_json(&self.value).map_err(|error| {
+            let span = self.span_at(&error.pointer());
+            let offset = match span {
+                Some(span) if error.is_key_error() => span.key().unwrap_or(span.range()).start,
+                Some(span) => span.range().start,
+                None => return error,
+            };
+            error.with_location(self.location(offset))
+        })
+    }
+}
+
+// Parses like `json()`, keeping the byte range of every value and key.
+pub fn json_spanned(input: &str) -> Option<Spanned<'_>> {
+    let (object, children) = json_with(&SpannedBuilder, input)?;
+    let start = input.len() - input.trim_start().len();
+    Some(Spanned {
+        value: JsonValue::Object(object),
+        spans: SpanTree {
+            range: start..input.trim_end().len(),
+            key: None,
+            children,
+        },
+        source: input,
+    })
+}
+// Like `json_spanned`, with options, returning what went wrong instead of printing it.
+pub fn parse_spanned<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<Spanned<'a>, Diagnostic> {
+    let ((object, children), range) = parse_document(&SpannedBuilder, input, options)?;
+    Ok(Spanned {
+        value: JsonValue::Object(object),
+        spans: SpanTree {
+            range,
+            key: None,
+            children,
+        },
+        source: input,
+    })
+}
+// Like `parse_spanned`, for documents whose top level is any value.
+pub fn parse_spanned_value<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<Spanned<'a>, Diagnostic> {
+    let ((value, spans), _) = parse_top_level(
+        input,
+        options,
+        "value",
+        value_at(&SpannedBuilder, options, 0),
+    )?;
+    Ok(Spanned {
+        value,
+        spans,
+        source: input,
+    })
+}
+
+struct SpannedBuilder;
+
+impl<'a> Builder<'a> for SpannedBuilder {
+    type Value = (JsonValue, SpanTree);
+    type Member = (Member, SpanTree);
+    type Object = (JsonObject, Vec<SpanTree>);
+
+    fn number(&self, n: Number) -> Self::Value {
+        (TreeBuilder.number(n), SpanTree::default())
+    }
+    fn string(&self, s: &'a str) -> Self::Value {
+        (TreeBuilder.string(s), SpanTree::default())
+    }
+    fn boolean(&self, b: bool) -> Self::Value {
+        (TreeBuilder.boolean(b), SpanTree::default())
+    }
+    fn null(&self) -> Self::Value {
+        (TreeBuilder.null(), SpanTree::default())
+    }
+    fn array(&self, items: Vec<Self::Value>) -> Self::Value {
+        let (values, children) = items.into_iter().unzip();
+        (
+            JsonValue::Array(values),
+            SpanTree {
+                children,
+                ..SpanTree::default()
+            },
+        )
+    }
+    fn member(&self, identifier: &'a str, (value, spans): Self::Value) -> Self::Member {
+        (TreeBuilder.member(identifier, value), spans)
+    }
+    fn object(&self, members: Vec<Self::Member>) -> Self::Object {
+        let (members, children) = members.into_iter().unzip();
+        (JsonObject::new(members), children)
+    }
+    fn object_value(&self, (object, children): Self::Object) -> Self::Value {
+        (
+            JsonValue::Object(object),
+            SpanTree {
+                children,
+                ..SpanTree::default()
+            },
+        )
+    }
+    fn with_span(&self, (value, mut spans): Self::Value, span: Range<usize>) -> Self::Value {
+        spans.range = span;
+        (value, spans)
+    }
+    fn member_span(&self, (member, mut spans): Self::Member, key: Range<usize>) -> Self::Member {
+        spans.key = Some(key);
+        (member, spans)
+    }
+}
@@ -0,0 +1,157 @@
+use json_parser::canonical::{content_hash, sha256, to_canonical_string, Sha256};
+use json_parser::{json, parse_value, JsonValue, Number, ParseOptions};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn canonical(input: &str) -> String {
+    to_canonical_string(&parse_value(input, &ParseOptions::default()).unwrap()).unwrap()
+}
+
+// The examples of FIPS 180-2, appendix B.
+#[test]
+fn sha256_matches_the_fips_examples() {
+    let vectors = [
+        (
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+        (
+            "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopq\
+             klmnopqrlmnopqrsmnopqrstnopqrstu",
+            "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+        ),
+    ];
+    for (input, digest) in &vectors {
+        assert_eq!(hex(&sha256(input.as_bytes())), *digest, "{:?}", input);
+    }
+    // A million `a`s, fed in pieces that don't line up with the 64-byte blocks.
+    let mut hasher = Sha256::new();
+    for _ in 0..10_000 {
+        hasher.update(&[b'a'; 100]);
+    }
+    assert_eq!(
+        hex(&hasher.finalize()),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+
+// RFC 8785, section 3.2.2.
+#[test]
+fn rfc_8785_serialization_example() {
+    let input = r#"{
+  "numbers": [333333333.33333329, 1E30, 4.50,
+              2e-3, 0.000000000000000000000000001],
+  "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+  "literals": [null, true, false]
+}"#;
+    assert_eq!(
+        canonical(input),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+}
+
+// RFC 8785, section 3.2.3: members sorted by their UTF-16 code units, so the emoji,
+// a surrogate pair, comes before U+FB33.
+#[test]
+fn rfc_8785_sorting_example() {
+    let input = r#"{
+  "\u20ac": "Euro Sign",
+  "\r": "Carriage Return",
+  "\ufb33": "Hebrew Letter Dalet With Dagesh",
+  "1": "One",
+  "\ud83d\ude00": "Emoji: Grinning Face",
+  "\u0080": "Control",
+  "\u00f6": "Latin Small Letter O With Diaeresis"
+}"#;
+    assert_eq!(
+        canonical(input),
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+         \"ö\":\"Latin Small Letter O With Diaeresis\",\"€\":\"Euro Sign\",\
+         \"😀\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+    );
+}
+
+// RFC 8785, appendix B: the bits of a double and how it serializes.
+#[test]
+fn rfc_8785_number_examples() {
+    let vectors = [
+        (0x0000000000000000, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ];
+    for &(bits, text) in &vectors {
+        let value = JsonValue::Number(Number::Float(f64::from_bits(bits)));
+        assert_eq!(to_canonical_string(&value).unwrap(), text, "{:016x}", bits);
+    }
+}
+
+#[test]
+fn non_finite_numbers_are_errors() {
+    for bits in [0x7fffffffffffffff, 0x7ff0000000000000, 0xfff0000000000000] {
+        let value = json!({"a": [1, JsonValue::Number(Number::Float(f64::from_bits(bits)))]});
+        let error = to_canonical_string(&value).unwrap_err();
+        assert_eq!(error.pointer(), "/a/1");
+        assert!(content_hash(&value).is_err());
+    }
+    // Exact numbers beyond the largest double round to an infinity.
+    let options = ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    };
+    let value = parse_value(r#"{"a/b": 1e400}"#, &options).unwrap();
+    let error = to_canonical_string(&value).unwrap_err();
+    assert_eq!(error.pointer(), "/a~1b");
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "the number {} at `/a~1b` is not a finite double and has no canonical form",
+            error.number()
+        )
+    );
+}
+
+#[test]
+fn equal_values_hash_the_same() {
+    let a = parse_value(r#"{"b": [1.0, "x"], "a": null}"#, &ParseOptions::default()).unwrap();
+    let b = json!({"a": null, "b": [1, "x"]});
+    assert_eq!(content_hash(&a).unwrap(), content_hash(&b).unwrap());
+    assert_ne!(
+        content_hash(&a).unwrap(),
+        content_hash(&json!({"a": null})).unwrap()
+    );
+    assert_eq!(
+        content_hash(&json!({})).unwrap().to_string(),
+        hex(&sha256(b"{}"))
+    );
+}
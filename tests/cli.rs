@@ -0,0 +1,132 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Runs the command line tool with `stdin` as its input, returning its exit code,
+// stdout and stderr.
+fn run(args: &[&str], stdin: &str) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+// Writes `contents` to a file of the test's own, returning its path.
+fn file(name: &str, contents: &str) -> String {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, contents).unwrap();
+    path.display().to_string()
+}
+
+// Top-level values other than objects, as RFC 8259 allows.
+const SCALARS: [&str; 6] = ["[1, [2]]", "\"text\"", "42", "-1.5e3", "true", "null"];
+
+#[test]
+fn validate_accepts_any_top_level_value() {
+    for input in SCALARS.iter().chain(&["{\"a\": 1}"]) {
+        assert_eq!(run(&["validate"], input), (0, String::new(), String::new()));
+    }
+    let (code, _, stderr) = run(&["validate"], "[1] 2");
+    assert_eq!(code, 1);
+    assert!(
+        stderr.contains("Unexpected `2` after the end of the value"),
+        "{}",
+        stderr
+    );
+    let (code, _, stderr) = run(&["validate"], "[1,]");
+    assert_eq!(code, 1);
+    assert!(stderr.contains("<stdin>:1:4"), "{}", stderr);
+}
+
+#[test]
+fn fmt_formats_any_top_level_value() {
+    assert_eq!(
+        run(&["fmt"], "[1,[2]]"),
+        (
+            0,
+            "[\n  1,\n  [\n    2\n  ]\n]\n".to_string(),
+            String::new()
+        )
+    );
+    for (input, formatted) in SCALARS[1..]
+        .iter()
+        .zip(&["\"text\"", "42", "-1500.0", "true", "null"])
+    {
+        assert_eq!(
+            run(&["fmt", "--minify"], input).1,
+            format!("{}\n", formatted)
+        );
+        assert_eq!(run(&["fmt", "--check"], &format!("{}\n", formatted)).0, 0);
+    }
+    let (code, stdout, _) = run(&["fmt", "--json5", "--keep-comments"], "[1, // one\n 2]");
+    assert_eq!(code, 0);
+    assert_eq!(stdout, "[\n  1, // one\n  2\n]\n");
+    // Comments are attached by pointer, with the keys escaped.
+    let input = "{\"a/b\": [1, // one\n 2], /* k */ \"c~\": {\"d\": true // t\n}}";
+    let (code, stdout, _) = run(&["fmt", "--json5", "--keep-comments"], input);
+    assert_eq!(code, 0);
+    assert_eq!(
+        stdout,
+        "{\n  \"a/b\": [\n    1, // one\n    2\n  ],\n  /* k */\n  \"c~\": {\n    \"d\": true // t\n  }\n}\n"
+    );
+}
+
+#[test]
+fn query_runs_on_any_top_level_value() {
+    assert_eq!(run(&["query", ".[1][0]"], "[1, [2]]").1, "2\n");
+    assert_eq!(run(&["query", "-r", "."], "\"text\"").1, "text\n");
+    assert_eq!(run(&["query", ". + 1"], "42").1, "43\n");
+    assert_eq!(run(&["query", "not"], "true").1, "false\n");
+    assert_eq!(run(&["query", ".a"], "null").1, "null\n");
+    let (code, _, stderr) = run(&["query", ".a"], "[1]");
+    assert_eq!(code, 1);
+    assert!(stderr.contains("<stdin>"), "{}", stderr);
+}
+
+#[test]
+fn diff_compares_any_top_level_values() {
+    let old = file("diff_old.json", "[1, 2]");
+    let new = file("diff_new.json", "[1, 3, 4]");
+    assert_eq!(
+        run(&["diff", &old, &new], ""),
+        (1, "~ [1]: 2 -> 3\n+ [2]: 4\n".to_string(), String::new())
+    );
+    let text = file("diff_text.json", "\"x\"");
+    let number = file("diff_number.json", "5");
+    let (code, stdout, _) = run(&["diff", "--format", "patch", &text, &number], "");
+    assert_eq!(code, 1);
+    assert!(stdout.contains("\"path\": \"\""), "{}", stdout);
+    assert_eq!(run(&["diff", &number, &number], "").0, 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn globstar_does_not_follow_symlink_loops() {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("globstar");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/b/deep.json"), "[]").unwrap();
+    fs::write(root.join("top.json"), "{}").unwrap();
+    std::os::unix::fs::symlink("..", root.join("a/b/up")).unwrap();
+    let pattern = format!("{}/**/*.json", root.display());
+    let (code, _, stderr) = run(&["validate", "--format", "gcc", &pattern], "");
+    assert_eq!((code, stderr), (0, String::new()));
+    let (_, stdout, _) = run(&["validate", "--format", "json", &pattern], "");
+    assert_eq!(stdout.matches("\"valid\": true").count(), 2, "{}", stdout);
+}
@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+use json_parser::{
+    json, parse_spanned_value, parse_value, FromJson, FromJsonError, JsonValue, Number,
+    ParseOptions, ToJson,
+};
+
+fn exact(input: &str) -> JsonValue {
+    let options = ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    };
+    parse_value(input, &options).unwrap()
+}
+
+fn round_trip<T: FromJson + ToJson + PartialEq + std::fmt::Debug>(value: T) {
+    assert_eq!(T::from_json(&value.to_json()).as_ref(), Ok(&value));
+}
+
+#[test]
+fn integers_convert_within_range() {
+    assert_eq!(u8::from_json(&json!(255)), Ok(255));
+    assert_eq!(i64::from_json(&json!(-3.0)), Ok(-3));
+    assert_eq!(
+        u128::from_json(&exact("3.4e20")),
+        Ok(340_000_000_000_000_000_000)
+    );
+    let error = u8::from_json(&json!(256)).unwrap_err();
+    assert_eq!(error.reason(), "number 256 is out of range for u8");
+    let error = i32::from_json(&json!(1.5)).unwrap_err();
+    assert_eq!(error.reason(), "number 1.5 is not an integer");
+    let error = u64::from_json(&json!(-1)).unwrap_err();
+    assert_eq!(error.reason(), "number -1 is out of range for u64");
+    let error = i8::from_json(&json!("1")).unwrap_err();
+    assert_eq!(error.reason(), "expected an integer, found a string");
+    round_trip(i128::MIN);
+    round_trip(u128::MAX);
+    round_trip(usize::MAX);
+}
+
+#[test]
+fn integers_fitting_isize_are_not_big() {
+    for value in [
+        0u64.to_json(),
+        7u32.to_json(),
+        (-7i64).to_json(),
+        9usize.to_json(),
+    ]
+    .iter()
+    {
+        assert!(
+            matches!(value, JsonValue::Number(Number::Integer(_))),
+            "{:?}",
+            value
+        );
+    }
+    assert!(matches!(
+        u64::MAX.to_json(),
+        JsonValue::Number(Number::BigInt(_))
+    ));
+}
+
+#[test]
+fn floats_reject_what_they_cannot_hold() {
+    assert_eq!(f64::from_json(&json!(1.5)), Ok(1.5));
+    assert_eq!(f32::from_json(&json!(3)), Ok(3.0));
+    assert_eq!(
+        f32::from_json(&json!(1e300)).unwrap_err().reason(),
+        "number 1e+300 is out of range for f32"
+    );
+    assert!(f64::from_json(&exact("1e400")).is_err());
+    assert!(f32::from_json(&json!(null)).is_err());
+    // Json has no NaN nor infinities.
+    assert_eq!(f64::NAN.to_json(), JsonValue::Null);
+    assert_eq!(f32::INFINITY.to_json(), JsonValue::Null);
+    round_trip(0.1f32);
+    round_trip(-2.5e-308f64);
+}
+
+#[test]
+fn containers_convert_element_by_element() {
+    round_trip(vec![Some(1u8), None]);
+    round_trip([true, false, true]);
+    round_trip((1i32, "two".to_string(), 3.5f64));
+    round_trip(Box::new("boxed".to_string()));
+    round_trip(Rc::new(vec![1u16]));
+    let map: BTreeMap<String, Vec<bool>> =
+        vec![("a".to_string(), vec![true])].into_iter().collect();
+    round_trip(map);
+    let map: HashMap<String, u8> = vec![("k".to_string(), 1)].into_iter().collect();
+    round_trip(map);
+    assert_eq!("str".to_json(), json!("str"));
+    assert_eq!(Option::<u8>::from_json(&json!(null)), Ok(None));
+    assert_eq!(JsonValue::from_json(&json!([1])), Ok(json!([1])));
+}
+
+#[test]
+fn errors_point_at_the_failing_node() {
+    let value = json!({"outer": [{"a/b": [1, "x"]}]});
+    let error = BTreeMap::<String, Vec<BTreeMap<String, Vec<u8>>>>::from_json(&value).unwrap_err();
+    assert_eq!(error.pointer(), "/outer/0/a~1b/1");
+    assert_eq!(
+        error.to_string(),
+        "expected an integer, found a string at `/outer/0/a~1b/1`"
+    );
+    let error = <[u8; 3]>::from_json(&json!([1, 2])).unwrap_err();
+    assert_eq!(error.reason(), "expected an array of 3 elements, found 2");
+    let error = <(u8, u8)>::from_json(&json!([1, 2, 3])).unwrap_err();
+    assert_eq!(error.reason(), "expected an array of 2 elements, found 3");
+    let error = String::from_json(&json!({})).unwrap_err();
+    assert_eq!(error.pointer(), "");
+    assert_eq!(error.reason(), "expected a string, found an object");
+}
+
+// Reads `{"x": <u8>}`, the way a derived impl would.
+#[derive(Debug)]
+struct Point {
+    x: u8,
+}
+impl FromJson for Point {
+    fn from_json(value: &JsonValue) -> Result<Self, FromJsonError> {
+        let x = value
+            .get("x")
+            .ok_or_else(|| FromJsonError::missing_member("x"))?;
+        Ok(Self {
+            x: u8::from_json(x).map_err(|error| error.at_member("x"))?,
+        })
+    }
+}
+
+// Fails at a member no document has.
+#[derive(Debug)]
+struct Elsewhere;
+impl FromJson for Elsewhere {
+    fn from_json(_: &JsonValue) -> Result<Self, FromJsonError> {
+        Err(FromJsonError::new("not here").at_member("nowhere"))
+    }
+}
+
+#[test]
+fn decoding_spanned_values_locates_errors() {
+    let input = "{\n  \"outer\": [\n    {\"a\": [1, \"x\"]}\n  ]\n}";
+    let spanned = parse_spanned_value(input, &ParseOptions::default()).unwrap();
+    let error = spanned
+        .decode::<BTreeMap<String, Vec<BTreeMap<String, Vec<u8>>>>>()
+        .unwrap_err();
+    assert_eq!(error.pointer(), "/outer/0/a/1");
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column), (3, 15));
+    assert_eq!(&input[location.offset..location.offset + 3], "\"x\"");
+    assert_eq!(
+        error.to_string(),
+        "expected an integer, found a string at `/outer/0/a/1` (line 3 column 15)"
+    );
+
+    let input = "\n  {\"y\": 1}";
+    let error = parse_spanned_value(input, &ParseOptions::default())
+        .unwrap()
+        .decode::<Point>()
+        .unwrap_err();
+    assert_eq!(error.reason(), "missing member `x`");
+    assert_eq!(error.pointer(), "");
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column, location.offset), (2, 3, 3));
+
+    let spanned = parse_spanned_value("{\"x\": 7}", &ParseOptions::default()).unwrap();
+    assert_eq!(spanned.decode::<Point>().unwrap().x, 7);
+    let error = spanned.decode::<Elsewhere>().unwrap_err();
+    assert_eq!(error.pointer(), "/nowhere");
+    assert_eq!(error.location(), None);
+    assert_eq!(error.to_string(), "not here at `/nowhere`");
+}
@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use json_parser::{json, parse_spanned, FromJson, JsonValue, ParseOptions, ToJson};
+// With the `derive` feature the crate already exports the derives next to the traits.
+#[cfg(not(feature = "derive"))]
+use json_parser_derive::{FromJson, ToJson};
+
+fn round_trip<T: FromJson + ToJson + PartialEq + Debug>(value: T, json: JsonValue) {
+    assert_eq!(value.to_json(), json);
+    assert_eq!(T::from_json(&json), Ok(value));
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(rename_all = "camelCase")]
+struct Renamed {
+    first_name: String,
+    #[json(rename = "years")]
+    age_in_years: u8,
+    #[json(skip)]
+    cache: Vec<u8>,
+    #[json(default)]
+    tags: Vec<String>,
+    #[json(default = "answer")]
+    answer: u32,
+    nickname: Option<String>,
+}
+
+fn answer() -> u32 {
+    42
+}
+
+#[test]
+fn fields_are_renamed_skipped_and_defaulted() {
+    round_trip(
+        Renamed {
+            first_name: "Ada".to_string(),
+            age_in_years: 36,
+            cache: vec![],
+            tags: vec!["math".to_string()],
+            answer: 7,
+            nickname: None,
+        },
+        json!({"firstName": "Ada", "years": 36, "tags": ["math"], "answer": 7, "nickname": null}),
+    );
+    let decoded = Renamed::from_json(&json!({"firstName": "Ada", "years": 36, "cache": [1]}));
+    assert_eq!(
+        decoded,
+        Ok(Renamed {
+            first_name: "Ada".to_string(),
+            age_in_years: 36,
+            cache: vec![],
+            tags: vec![],
+            answer: 42,
+            nickname: None,
+        })
+    );
+    let error = Renamed::from_json(&json!({"years": 36})).unwrap_err();
+    assert_eq!(error.reason(), "missing member `firstName`");
+    let error = Renamed::from_json(&json!({"firstName": "Ada", "years": 256})).unwrap_err();
+    assert_eq!(error.pointer(), "/years");
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Tuple(u8, String);
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Newtype(Vec<bool>);
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Unit;
+
+#[test]
+fn tuple_and_unit_structs() {
+    round_trip(Tuple(1, "a".to_string()), json!([1, "a"]));
+    round_trip(Newtype(vec![true]), json!([true]));
+    round_trip(Unit, json!(null));
+    let error = Tuple::from_json(&json!([1])).unwrap_err();
+    assert_eq!(error.reason(), "expected an array of 2 elements, found 1");
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Page {
+    title: String,
+    #[json(flatten)]
+    meta: Meta,
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Meta {
+    author: String,
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Open {
+    title: String,
+    #[json(flatten)]
+    extra: BTreeMap<String, JsonValue>,
+}
+
+#[test]
+fn flattened_fields_share_the_object() {
+    round_trip(
+        Page {
+            title: "Notes".to_string(),
+            meta: Meta {
+                author: "Ada".to_string(),
+            },
+        },
+        json!({"title": "Notes", "author": "Ada"}),
+    );
+    let mut extra = BTreeMap::new();
+    extra.insert("draft".to_string(), json!(true));
+    round_trip(
+        Open {
+            title: "Notes".to_string(),
+            extra,
+        },
+        json!({"title": "Notes", "draft": true}),
+    );
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(rename_all = "snake_case")]
+enum External {
+    Plain,
+    Wrapped(u8),
+    Pair(u8, u8),
+    #[json(rename_all = "UPPERCASE")]
+    Point {
+        x: i32,
+        y: i32,
+    },
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(tag = "type")]
+enum Internal {
+    Empty,
+    Wrapped(Meta),
+    Point { x: i32, y: i32 },
+}
+
+// A string in json, which the derive can't tell from the type.
+#[derive(Debug, PartialEq)]
+struct Code(String);
+impl ToJson for Code {
+    fn to_json(&self) -> JsonValue {
+        self.0.to_json()
+    }
+}
+impl FromJson for Code {
+    fn from_json(value: &JsonValue) -> Result<Self, json_parser::FromJsonError> {
+        String::from_json(value).map(Code)
+    }
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(tag = "type")]
+enum Loose {
+    Maybe(Option<Meta>),
+    Custom(Code),
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(tag = "t", content = "c")]
+enum Adjacent {
+    Empty,
+    Wrapped(u8),
+    Point { x: i32 },
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(untagged)]
+enum Untagged {
+    Number(u8),
+    Text(String),
+    Point { x: i32, y: i32 },
+}
+
+#[test]
+fn externally_tagged_enums() {
+    round_trip(External::Plain, json!("plain"));
+    round_trip(External::Wrapped(1), json!({"wrapped": 1}));
+    round_trip(External::Pair(1, 2), json!({"pair": [1, 2]}));
+    round_trip(
+        External::Point { x: 1, y: 2 },
+        json!({"point": {"X": 1, "Y": 2}}),
+    );
+    let error = External::from_json(&json!("line")).unwrap_err();
+    assert_eq!(
+        error.reason(),
+        "unknown variant `line`, expected one of `plain`, `wrapped`, `pair`, `point`"
+    );
+}
+
+#[test]
+fn internally_tagged_enums() {
+    round_trip(Internal::Empty, json!({"type": "Empty"}));
+    round_trip(
+        Internal::Wrapped(Meta {
+            author: "Ada".to_string(),
+        }),
+        json!({"type": "Wrapped", "author": "Ada"}),
+    );
+    round_trip(
+        Internal::Point { x: 1, y: 2 },
+        json!({"type": "Point", "x": 1, "y": 2}),
+    );
+    let error = Internal::from_json(&json!({"x": 1})).unwrap_err();
+    assert_eq!(error.reason(), "missing member `type`");
+    round_trip(
+        Loose::Maybe(Some(Meta {
+            author: "Ada".to_string(),
+        })),
+        json!({"type": "Maybe", "author": "Ada"}),
+    );
+}
+
+// Content that isn't an object has nowhere to go next to the tag.
+#[test]
+#[should_panic(
+    expected = "the content of the internally tagged variant `Custom` must convert to an object, \
+                and converted to a string"
+)]
+fn internally_tagged_content_must_be_an_object() {
+    Loose::Custom(Code("a".to_string())).to_json();
+}
+
+#[test]
+#[should_panic(
+    expected = "the internally tagged variant `Maybe` must convert to an object, \
+                           and converted to null"
+)]
+fn internally_tagged_content_must_not_be_null() {
+    Loose::Maybe(None).to_json();
+}
+
+#[test]
+fn adjacently_tagged_enums() {
+    round_trip(Adjacent::Empty, json!({"t": "Empty"}));
+    round_trip(Adjacent::Wrapped(3), json!({"t": "Wrapped", "c": 3}));
+    round_trip(
+        Adjacent::Point { x: 1 },
+        json!({"t": "Point", "c": {"x": 1}}),
+    );
+}
+
+#[test]
+fn untagged_enums_take_the_first_variant_that_fits() {
+    round_trip(Untagged::Number(1), json!(1));
+    round_trip(Untagged::Text("a".to_string()), json!("a"));
+    round_trip(Untagged::Point { x: 1, y: 2 }, json!({"x": 1, "y": 2}));
+    assert!(Untagged::from_json(&json!([])).is_err());
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+#[json(deny_unknown_fields)]
+struct Strict {
+    a: u8,
+}
+
+#[test]
+fn unknown_fields_are_denied() {
+    round_trip(Strict { a: 1 }, json!({"a": 1}));
+    let error = Strict::from_json(&json!({"a": 1, "b": 2})).unwrap_err();
+    assert_eq!(error.reason(), "unknown member `b`");
+    assert_eq!(error.pointer(), "/b");
+    assert!(error.is_key_error());
+
+    // Decoded from the source, the error is at the key rather than at its value.
+    let input = "{\n  \"a\": 1,\n  \"b\": 2\n}";
+    let spanned = parse_spanned(input, &ParseOptions::default()).unwrap();
+    let error = spanned.decode::<Strict>().unwrap_err();
+    let location = error.location().unwrap();
+    assert_eq!((location.line, location.column), (3, 3));
+    assert_eq!(&input[location.offset..location.offset + 3], "\"b\"");
+}
+
+#[derive(Debug, PartialEq, FromJson, ToJson)]
+struct Generic<T> {
+    items: Vec<T>,
+}
+
+#[test]
+fn generic_structs() {
+    round_trip(
+        Generic {
+            items: vec![1u8, 2],
+        },
+        json!({"items": [1, 2]}),
+    );
+}
+
+// Derives that must not compile, in a crate of their own, with the error each should
+// report.
+#[test]
+fn non_object_content_is_rejected_at_compile_time() {
+    let cases = [
+        (
+            "struct A { #[json(flatten)] items: Vec<u8> }",
+            "a `#[json(flatten)]` field must convert to an object, and `Vec < u8 >` \
+             converts to an array",
+        ),
+        (
+            "struct B { #[json(flatten)] name: Option<String> }",
+            "a `#[json(flatten)]` field must convert to an object, and `Option < String >` \
+             converts to a string",
+        ),
+        (
+            "#[json(tag = \"type\")] enum C { Number(u8) }",
+            "the content of an internally tagged newtype variant must convert to an object, \
+             and `u8` converts to a number",
+        ),
+    ];
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("derive_errors");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"derive_errors\"\nversion = \"0.0.0\"\nedition = \"2018\"\n\n\
+             [dependencies]\njson-parser = {{ path = {:?}, features = [\"derive\"] }}\n\n\
+             [workspace]\n",
+            env!("CARGO_MANIFEST_DIR")
+        ),
+    )
+    .unwrap();
+    let source: String = cases
+        .iter()
+        .map(|(item, _)| {
+            format!(
+                "#[derive(json_parser::FromJson, json_parser::ToJson)]\n{}\n",
+                item
+            )
+        })
+        .collect();
+    fs::write(root.join("src/lib.rs"), source).unwrap();
+    let output = Command::new(env!("CARGO"))
+        .arg("check")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(root.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(root.join("target"))
+        .env("RUSTC_BOOTSTRAP", "1")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for (_, message) in &cases {
+        assert!(stderr.contains(message), "{} not in:\n{}", message, stderr);
+    }
+}
@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+
+use json_parser::document::ValueRef;
+use json_parser::{parse_value, Document, JsonValue, ParseOptions};
+
+// Reads everything a `ValueRef` offers and compares it with the `JsonValue` tree.
+fn assert_same(document: ValueRef<'_, '_>, value: &JsonValue) {
+    assert_eq!(document.to_value(), *value);
+    assert_eq!(document.as_str().as_deref(), value.as_str());
+    assert_eq!(document.as_number(), value.as_number());
+    assert_eq!(document.as_bool(), value.as_bool());
+    assert_eq!(document.is_null(), value.is_null());
+    assert_eq!(document.len(), value.len());
+    for (i, element) in value.iter().enumerate() {
+        assert_same(document.get_index(i).unwrap(), element);
+    }
+    for (member, (key, element)) in value.members().zip(document.members()) {
+        assert_eq!(member.identifier(), key);
+        assert_same(element, member.value());
+        let found = document.get(member.identifier()).unwrap();
+        assert_eq!(Some(found.to_value()), value.get(&key).cloned());
+    }
+}
+
+#[test]
+fn documents_read_like_values() {
+    let inputs = [
+        r#"{"a\"b": 1, "tab\t": "x\ny", "é": ["😀", "\\"], "plain": "text"}"#,
+        r#"{"k": {"\/": [true, false, null, -1.5e3]}, "k": "first wins"}"#,
+        r#"{}"#,
+    ];
+    for input in inputs.iter() {
+        let document = Document::parse(input).unwrap();
+        let value = parse_value(input, &ParseOptions::default()).unwrap();
+        assert_same(document.root(), &value);
+    }
+}
+
+#[test]
+fn escaped_keys_and_strings_are_decoded() {
+    let input = r#"{"a\"b": "cA\\d", "plain": "text"}"#;
+    let document = Document::parse(input).unwrap();
+    let value = document.get("a\"b").unwrap();
+    assert_eq!(value.as_str().unwrap(), "cA\\d");
+    assert_eq!(value.as_raw_str(), Some(r"cA\\d"));
+    assert!(document.get(r#"a\"b"#).is_none());
+    // Strings without escapes are still borrowed from the input.
+    match document.get("plain").and_then(|plain| plain.as_str()) {
+        Some(Cow::Borrowed(plain)) => {
+            assert!(input.as_bytes().as_ptr_range().contains(&plain.as_ptr()))
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        format!("{:?}", document),
+        r#"{"a\"b": "cA\\d", "plain": "text"}"#
+    );
+}
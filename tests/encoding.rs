@@ -0,0 +1,94 @@
+use json_parser::encoding::{decode, detect, Encoding};
+use json_parser::{parse_bytes, parse_value, JsonValue, ParseOptions};
+
+fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+    text.encode_utf16()
+        .flat_map(|unit| match big_endian {
+            true => unit.to_be_bytes(),
+            false => unit.to_le_bytes(),
+        })
+        .collect()
+}
+
+fn utf32(text: &str, big_endian: bool) -> Vec<u8> {
+    text.chars()
+        .flat_map(|c| match big_endian {
+            true => (c as u32).to_be_bytes(),
+            false => (c as u32).to_le_bytes(),
+        })
+        .collect()
+}
+
+#[test]
+fn every_encoding_reads_the_same() {
+    let texts = [
+        "{\"k\": [\"é\", \"😀\", 1]}",
+        "\u{feff}{}",
+        "\u{feff}1",
+        "1",
+        "[]",
+    ];
+    for text in texts.iter() {
+        let expected = parse_value(
+            text.trim_start_matches('\u{feff}'),
+            &ParseOptions::default(),
+        );
+        let inputs = vec![
+            (Encoding::Utf8, text.as_bytes().to_vec()),
+            (Encoding::Utf16Le, utf16(text, false)),
+            (Encoding::Utf16Be, utf16(text, true)),
+            (Encoding::Utf32Le, utf32(text, false)),
+            (Encoding::Utf32Be, utf32(text, true)),
+        ];
+        for (encoding, bytes) in inputs {
+            assert_eq!(detect(&bytes).0, encoding, "{:?}", bytes);
+            let value = parse_bytes(&bytes, &ParseOptions::default());
+            assert_eq!(value, expected, "{:?} in {}", text, encoding);
+        }
+    }
+}
+
+#[test]
+fn malformed_input_points_at_its_first_bad_byte() {
+    let cases: Vec<(Vec<u8>, &str)> = vec![
+        (b"{\"a\": \"\xff\"}".to_vec(), "Invalid UTF-8 at byte 7"),
+        (
+            b"\xef\xbb\xbf[\"\xc3\"]".to_vec(),
+            "Invalid UTF-8 at byte 5",
+        ),
+        (
+            [utf16("[\"", false), vec![0x00, 0xd8]].concat(),
+            "Invalid UTF-16LE at byte 4",
+        ),
+        (
+            [utf16("[1", true), vec![0x00]].concat(),
+            "Invalid UTF-16BE at byte 4",
+        ),
+        (
+            [utf32("[", false), vec![0, 0, 0x11, 0]].concat(),
+            "Invalid UTF-32LE at byte 4",
+        ),
+    ];
+    for (bytes, message) in cases {
+        let error = parse_bytes(&bytes, &ParseOptions::default()).unwrap_err();
+        assert_eq!(error.message, message);
+    }
+}
+
+#[test]
+fn lossy_decoding_repairs_malformed_input() {
+    let lossy = ParseOptions {
+        lossy_decoding: true,
+        ..ParseOptions::default()
+    };
+    let value = parse_bytes(b"[\"a\xffb\", \"\xe2\x82\"]", &lossy).unwrap();
+    let strings: Vec<_> = value.iter().filter_map(JsonValue::as_str).collect();
+    assert_eq!(strings, ["a\u{fffd}b", "\u{fffd}"]);
+    let surrogate = [utf16("[\"", true), vec![0xdc, 0x00], utf16("\"]", true)].concat();
+    let value = parse_bytes(&surrogate, &lossy).unwrap();
+    assert_eq!(
+        value.get_index(0).and_then(JsonValue::as_str),
+        Some("\u{fffd}")
+    );
+    assert_eq!(decode(b"plain", false).unwrap(), "plain");
+}
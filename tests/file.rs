@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::PathBuf;
+
+use json_parser::{json, parse_file, parse_value, FileError, JsonFile, ParseOptions};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+#[test]
+fn regular_files_are_mapped() {
+    let path = scratch("mapped.json");
+    fs::write(
+        &path,
+        "\u{feff}{\"name\": \"caf\\u00e9\", \"list\": [1, 2.5, null], \"plain\": \"text\"}",
+    )
+    .unwrap();
+    let options = ParseOptions::default();
+    assert!(!JsonFile::open(&path, &options).unwrap().is_mapped());
+    // Nothing writes to the scratch file while it is mapped.
+    let file = unsafe { JsonFile::map(&path, &options) }.unwrap();
+    assert_eq!(
+        file.is_mapped(),
+        cfg!(all(unix, target_pointer_width = "64"))
+    );
+    let document = file.document(&options).unwrap();
+    // Strings without escapes are borrowed from the mapping.
+    let bytes = file.bytes().as_ptr_range();
+    let plain = document
+        .get("plain")
+        .and_then(|plain| plain.as_str())
+        .unwrap();
+    assert!(bytes.contains(&plain.as_ptr()));
+    let name = document.get("name").unwrap();
+    assert_eq!(name.as_str().unwrap(), "café");
+    assert!(bytes.contains(&name.as_raw_str().unwrap().as_ptr()));
+    assert_eq!(
+        parse_file(&path, &options).unwrap(),
+        json!({"name": "café", "list": [1, 2.5, null], "plain": "text"})
+    );
+}
+
+#[test]
+fn other_encodings_are_decoded() {
+    let path = scratch("utf16.json");
+    let bytes: Vec<u8> = "\u{feff}[\"😀\"]"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    fs::write(&path, bytes).unwrap();
+    let file = JsonFile::open(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(file.text(), "[\"😀\"]");
+}
+
+#[test]
+fn errors_tell_io_from_parsing() {
+    let options = ParseOptions::default();
+    let missing = parse_file(scratch("missing.json"), &options);
+    assert!(matches!(missing, Err(FileError::Io(_))));
+    let path = scratch("invalid.json");
+    fs::write(&path, b"{\"a\": \xff}").unwrap();
+    match parse_file(&path, &options) {
+        Err(FileError::Invalid(diagnostic)) => {
+            assert_eq!(diagnostic.message, "Invalid UTF-8 at byte 6")
+        }
+        other => panic!("{:?}", other),
+    }
+    let empty = scratch("empty.json");
+    fs::write(&empty, "").unwrap();
+    assert!(matches!(
+        parse_file(&empty, &options),
+        Err(FileError::Invalid(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn pipes_are_read() {
+    use std::process::Command;
+    use std::thread;
+
+    let path = scratch("pipe.json");
+    let _ = fs::remove_file(&path);
+    match Command::new("mkfifo").arg(&path).status() {
+        Ok(status) if status.success() => {}
+        // No `mkfifo` to make one with.
+        _ => return,
+    }
+    let writer = {
+        let path = path.clone();
+        thread::spawn(move || fs::write(path, "[true, false]").unwrap())
+    };
+    let file = JsonFile::open(&path, &ParseOptions::default()).unwrap();
+    writer.join().unwrap();
+    assert!(!file.is_mapped());
+    assert_eq!(
+        file.value(&ParseOptions::default()).unwrap(),
+        json!([true, false])
+    );
+}
+
+// Files of `/sys` report 4096 bytes whatever they hold, and can't be mapped.
+#[cfg(target_os = "linux")]
+#[test]
+fn special_files_are_read() {
+    let path = "/sys/kernel/profiling";
+    let expected = match fs::read_to_string(path) {
+        Ok(text) => text,
+        // No sysfs to read from.
+        Err(_) => return,
+    };
+    let options = ParseOptions::default();
+    // sysfs files change only as the kernel settings they show do.
+    let file = unsafe { JsonFile::map(path, &options) }.unwrap();
+    assert!(!file.is_mapped());
+    assert_eq!(file.text(), expected);
+    assert_eq!(
+        file.value(&options).unwrap(),
+        parse_value(&expected, &options).unwrap()
+    );
+    assert_eq!(
+        parse_file(path, &options).unwrap(),
+        parse_value(&expected, &options).unwrap()
+    );
+}
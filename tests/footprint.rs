@@ -0,0 +1,138 @@
+// Allocation budgets for parsing and serializing, and the heap footprint of each representation of a
+// parsed document, checked against what the allocator actually handed out.
+
+use std::alloc::System;
+use std::fmt::Write;
+
+use json_parser::footprint::{count, Counting};
+use json_parser::parsec::character::string;
+use json_parser::parsec::Remaining;
+use json_parser::serialize::{to_string, to_string_pretty};
+use json_parser::{parse_value, Document, ParseOptions};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting(System);
+
+// About a megabyte of records with repeated keys, strings, numbers and nesting.
+fn fixture() -> String {
+    let mut out = String::from("{\"records\": [");
+    for i in 0..4000 {
+        if i > 0 {
+            out.push(',');
+        }
+        write!(
+            out,
+            r#"{{"id": {}, "name": "user {}", "email": "user{}@example.com", "active": {},
+"score": {}.5, "tags": ["alpha", "beta", "gamma"], "address": {{"street": "{} Main St",
+"city": "Springfield", "zip": "{:05}"}}, "note": "line\nbreak \"quoted\" café", "manager": null}}"#,
+            i,
+            i,
+            i,
+            i % 3 == 0,
+            i * 37 % 1000,
+            i,
+            i * 7 % 100_000
+        )
+        .unwrap();
+    }
+    out.push_str("]}");
+    out
+}
+
+fn interned() -> ParseOptions {
+    ParseOptions {
+        intern_keys: true,
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn footprint_is_what_parsing_retains() {
+    let input = fixture();
+    for options in [ParseOptions::default(), interned()].iter() {
+        let (value, allocations) = count(|| parse_value(&input, options).unwrap());
+        assert_eq!(allocations.retained, value.footprint().total() as isize);
+    }
+    let (document, allocations) = count(|| Document::parse(&input).unwrap());
+    assert_eq!(allocations.retained, document.footprint().total() as isize);
+}
+
+#[test]
+fn footprint_counts_allocations() {
+    let input = r#"{"a": "xy", "b": [1, "", {"c": null}], "d": 123456789012345678901234567890}"#;
+    let exact = ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    };
+    let value = parse_value(input, &exact).unwrap();
+    let footprint = value.footprint();
+    // Two objects and an array, four keys, one non-empty string and one big integer.
+    assert_eq!(footprint.allocations, 9);
+    assert_eq!(footprint.strings, 2);
+    assert!(footprint.numbers > 0);
+    assert!(footprint.overhead < footprint.total());
+}
+
+#[test]
+fn parsing_a_megabyte_stays_within_budget() {
+    let input = fixture();
+    assert!(input.len() > 1_000_000);
+    let (_, allocations) = count(|| parse_value(&input, &ParseOptions::default()).unwrap());
+    // Measured at about 1.7 allocations per byte; raise the budget knowingly.
+    assert!(
+        allocations.count < 2 * input.len(),
+        "{} allocations for {} bytes",
+        allocations.count,
+        input.len()
+    );
+    assert_eq!(count(|| ()).1.count, 0);
+}
+
+#[test]
+fn strings_parse_without_allocating() {
+    let input = r#""plain, \"escaped\" and \u00e9scaped text" after"#;
+    let parser = string();
+    let ((rest, value), allocations) = count(|| parser(Remaining::new(input, 0)).unwrap());
+    assert_eq!(value, r#"plain, \"escaped\" and \u00e9scaped text"#);
+    assert_eq!(rest.rem, " after");
+    assert_eq!(allocations.count, 0);
+}
+
+#[test]
+fn serializing_allocates_little_beyond_the_output() {
+    let element = r#"{"a/b": null, "c~d": [true, false, "x"], "e": {"f": []}}"#;
+    let input = format!("[{}]", vec![element; 1000].join(","));
+    let value = parse_value(&input, &ParseOptions::default()).unwrap();
+    for pretty in [false, true] {
+        let (text, allocations) = count(|| match pretty {
+            false => to_string(&value),
+            true => to_string_pretty(&value),
+        });
+        assert_eq!(parse_value(&text, &ParseOptions::default()).unwrap(), value);
+        // One vector of members per object, and the output growing.
+        assert!(
+            allocations.count < 2 * 1000 + 100,
+            "{} allocations",
+            allocations.count
+        );
+    }
+}
+
+#[test]
+fn representations_compare() {
+    let input = fixture();
+    let owned = parse_value(&input, &ParseOptions::default())
+        .unwrap()
+        .footprint();
+    let shared = parse_value(&input, &interned()).unwrap().footprint();
+    let document = Document::parse(&input).unwrap().footprint();
+    // Interning keeps one copy of each of the 13 distinct keys.
+    assert!(shared.keys < owned.keys / 100);
+    assert_eq!(shared.strings, owned.strings);
+    assert!(shared.total() < owned.total());
+    // The document borrows every string and packs its values in two vectors, one of
+    // nodes and one of numbers.
+    assert_eq!(document.strings + document.keys, 0);
+    assert!(document.allocations < 3);
+    assert!(document.total() < shared.total());
+}
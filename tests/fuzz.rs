@@ -0,0 +1,357 @@
+// A std-only fuzzer: mutates seed documents at random and checks, for every input,
+// that parsing never panics, that whatever parses prints back to an equal value, and
+// that the ways of parsing a document agree with each other:
+//
+// - strict mode accepts less than the default and JSON5 modes, with equal values,
+// - the `JsonValue` tree, the arena `Document`, the spanned tree and interned keys
+//   build equal values from the same input.
+//
+// There is no streaming parser to compare against yet; the builders above are the
+// grammar's only consumers.
+//
+// `fuzz` runs for long and is ignored by default:
+//
+//     FUZZ_ITERATIONS=1000000 FUZZ_SEED=42 cargo test --test fuzz -- --ignored
+//
+// Failing inputs are written to `target/fuzz/` before the test fails.
+
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use json_parser::document::Document;
+use json_parser::serialize::{to_string, to_string_pretty};
+use json_parser::{
+    parse, parse_spanned, parse_spanned_value, parse_value, JsonValue, ParseOptions,
+    DEFAULT_MAX_DEPTH,
+};
+
+// xorshift64*, plenty for picking mutations.
+struct Rng(u64);
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+// Fragments the grammar cares about, spliced into inputs.
+const TOKENS: &[&str] = &[
+    "{",
+    "}",
+    "[",
+    "]",
+    "\"",
+    "'",
+    ",",
+    ":",
+    "\\",
+    "\\u",
+    "\\uD800",
+    "\\uDC00",
+    "\\n",
+    "-",
+    "+",
+    ".",
+    "e",
+    "E",
+    "0",
+    "1",
+    "9",
+    "0x",
+    "1e999",
+    "-0.0",
+    "true",
+    "false",
+    "null",
+    "Infinity",
+    "NaN",
+    " ",
+    "\n",
+    "\t",
+    "\r",
+    "\u{a0}",
+    "\u{feff}",
+    "/*",
+    "*/",
+    "//",
+    "é",
+    "\u{1f600}",
+    "\0",
+    "\u{1f}",
+    "a",
+    "_",
+    "$",
+];
+
+fn seeds() -> Vec<Vec<u8>> {
+    let corpus = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/JSONTestSuite/test_parsing"
+    );
+    let mut seeds: Vec<Vec<u8>> = fs::read_dir(corpus)
+        .expect("the corpus is vendored")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("y_")
+        })
+        .map(|path| fs::read(path).unwrap())
+        .collect();
+    seeds.sort();
+    seeds.extend(
+        [
+            r#"{"a": [1, 2.5, -3e10, "x\ty", {"b": null}], "c": true}"#,
+            "{unquoted: 'single', trailing: [1, 2,], hex: 0x1F, /* c */ plus: +.5, // l\n}",
+            r#"{"deep": [[[[{"k": [[[]]]}]]]], "big": 123456789012345678901234567890}"#,
+            r#"{"escapes": "\"\\\/\b\f\n\r\té😀", "": ""}"#,
+        ]
+        .iter()
+        .map(|seed| seed.as_bytes().to_vec()),
+    );
+    // Around the default depth limit, and far beyond it.
+    for depth in [DEFAULT_MAX_DEPTH, DEFAULT_MAX_DEPTH + 1, 100_000] {
+        seeds.push(nested(depth).into_bytes());
+    }
+    seeds
+}
+
+// `depth` arrays and objects, alternating, each in the one before.
+fn nested(depth: usize) -> String {
+    let open: String = (0..depth)
+        .map(|i| if i % 2 == 0 { "[" } else { "{\"a\":" })
+        .collect();
+    let close: String = (0..depth)
+        .rev()
+        .map(|i| if i % 2 == 0 { "]" } else { "}" })
+        .collect();
+    format!("{}1{}", open, close)
+}
+
+fn mutate(rng: &mut Rng, seeds: &[Vec<u8>]) -> String {
+    let mut input = rng.pick(seeds).clone();
+    for _ in 0..1 + rng.below(8) {
+        let at = rng.below(input.len() + 1);
+        match rng.below(6) {
+            0 if at < input.len() => input[at] = rng.next() as u8,
+            1 => {
+                let token = rng.pick(TOKENS).as_bytes();
+                input.splice(at..at, token.iter().copied());
+            }
+            2 => {
+                let end = (at + rng.below(8)).min(input.len());
+                input.drain(at..end);
+            }
+            3 => {
+                let end = (at + rng.below(16)).min(input.len());
+                let copy = input[at..end].to_vec();
+                input.splice(at..at, copy);
+            }
+            4 => {
+                let other = rng.pick(seeds);
+                let start = rng.below(other.len());
+                let end = (start + rng.below(32)).min(other.len());
+                input.splice(at..at, other[start..end].iter().copied());
+            }
+            _ => input.truncate(at),
+        }
+    }
+    String::from_utf8_lossy(&input).into_owned()
+}
+
+fn options(strict: bool, json5: bool, exact_numbers: bool) -> ParseOptions {
+    ParseOptions {
+        strict,
+        json5,
+        exact_numbers,
+        ..ParseOptions::default()
+    }
+}
+
+// Every check on one input; `Err` describes the first one that failed.
+fn check(input: &str) -> Result<(), String> {
+    let strict = parse_value(input, &options(true, false, false)).ok();
+    let default = parse_value(input, &options(false, false, false)).ok();
+    let json5 = parse_value(input, &options(false, true, false)).ok();
+    let exact = parse_value(input, &options(false, false, true)).ok();
+    if let Some(strict) = &strict {
+        if default.as_ref() != Some(strict) {
+            return Err(format!("strict: {:?}, default: {:?}", strict, default));
+        }
+        if json5.as_ref() != Some(strict) {
+            return Err(format!("strict: {:?}, json5: {:?}", strict, json5));
+        }
+    }
+    // Exact numbers only fail on exponents beyond an `i64`, which round to zero or
+    // infinity otherwise.
+    if default.is_some() && exact.is_none() && !input.contains(['e', 'E']) {
+        return Err(format!("default: {:?}, exact: {:?}", default, exact));
+    }
+    for value in [&strict, &default, &json5].iter().flat_map(|v| v.as_ref()) {
+        round_trip(value, false)?;
+    }
+    if let Some(exact) = &exact {
+        round_trip(exact, true)?;
+    }
+    // Everything else only parses top-level objects, in the default mode.
+    let default_options = options(false, false, false);
+    let tree = parse(input, &default_options).ok().map(JsonValue::Object);
+    let spanned = parse_spanned(input, &default_options).ok().map(|s| s.value);
+    let interned = parse(
+        input,
+        &ParseOptions {
+            intern_keys: true,
+            ..default_options.clone()
+        },
+    )
+    .ok()
+    .map(JsonValue::Object);
+    let document = Document::parse(input).map(|document| document.to_value());
+    for (name, other) in [
+        ("spanned", &spanned),
+        ("interned", &interned),
+        ("document", &document),
+    ] {
+        if *other != tree {
+            return Err(format!("tree: {:?}, {}: {:?}", tree, name, other));
+        }
+    }
+    if let Some(tree) = &tree {
+        if default.as_ref() != Some(tree) {
+            return Err(format!("tree: {:?}, value: {:?}", tree, default));
+        }
+    }
+    Ok(())
+}
+
+// Exact numbers read back as exact numbers only.
+fn round_trip(value: &JsonValue, exact_numbers: bool) -> Result<(), String> {
+    for printed in [to_string(value), to_string_pretty(value)].iter() {
+        match parse_value(printed, &options(true, false, exact_numbers)) {
+            Ok(ref reparsed) if reparsed == value => {}
+            other => {
+                return Err(format!(
+                    "{:?} printed as {:?} read back as {:?}",
+                    value, printed, other
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+// On the test thread's own stack, which the default depth limit keeps deep seeds
+// from overflowing.
+fn run(iterations: u64, seed: u64) {
+    let seeds = seeds();
+    let mut rng = Rng::new(seed);
+    let mut failures = vec![];
+    for _ in 0..iterations {
+        let input = mutate(&mut rng, &seeds);
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| check(&input)));
+        let failure = match outcome {
+            Ok(Ok(())) => continue,
+            Ok(Err(failure)) => failure,
+            Err(payload) => format!(
+                "panicked: {}",
+                payload
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_default()
+            ),
+        };
+        failures.push((input, failure));
+    }
+    if failures.is_empty() {
+        return;
+    }
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target/fuzz");
+    fs::create_dir_all(&dir).unwrap();
+    for (i, (input, _)) in failures.iter().enumerate() {
+        fs::write(dir.join(format!("{}-{}.json", seed, i)), input).unwrap();
+    }
+    let shown: Vec<_> = failures
+        .iter()
+        .take(10)
+        .map(|(input, failure)| format!("{:?}\n    {}", input, failure))
+        .collect();
+    panic!(
+        "{} failures with seed {}, written to {}:\n{}",
+        failures.len(),
+        seed,
+        dir.display(),
+        shown.join("\n")
+    );
+}
+
+fn env(name: &str) -> Option<u64> {
+    std::env::var(name).ok().map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| panic!("`{}` is not a number", name))
+    })
+}
+
+#[test]
+fn deep_nesting_is_rejected_without_overflowing_the_stack() {
+    let options = ParseOptions::default();
+    let text = nested(DEFAULT_MAX_DEPTH);
+    let value = parse_value(&text, &options).unwrap();
+    assert_eq!(to_string(&value), text);
+    assert!(parse_spanned_value(&text, &options).is_ok());
+    assert!(Document::parse(&format!("{{\"a\":{}}}", nested(DEFAULT_MAX_DEPTH - 1))).is_some());
+    for depth in [DEFAULT_MAX_DEPTH + 1, 100_000] {
+        let text = nested(depth);
+        for options in [
+            options.clone(),
+            ParseOptions {
+                json5: true,
+                ..ParseOptions::default()
+            },
+        ] {
+            let error = parse_value(&text, &options).unwrap_err();
+            assert_eq!(
+                error.message,
+                format!(
+                    "Exceeded the maximum nesting depth of {}",
+                    DEFAULT_MAX_DEPTH
+                )
+            );
+        }
+        assert!(parse_spanned_value(&text, &options).is_err());
+        assert!(Document::try_parse(&text, &options).is_err());
+    }
+}
+
+#[test]
+fn smoke() {
+    run(2_000, 0x5eed);
+}
+
+#[test]
+#[ignore]
+fn fuzz() {
+    let seed = env("FUZZ_SEED").unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64
+    });
+    println!("seed {}", seed);
+    run(env("FUZZ_ITERATIONS").unwrap_or(100_000), seed);
+}
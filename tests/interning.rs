@@ -0,0 +1,48 @@
+use std::sync::Arc;
+use std::thread;
+
+use json_parser::{parse_value, JsonValue, ParseOptions};
+
+const INPUT: &str = r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}, {"id": 3}]"#;
+
+fn keys(value: &JsonValue) -> Vec<Option<&Arc<str>>> {
+    value
+        .iter()
+        .flat_map(JsonValue::members)
+        .filter(|member| member.identifier() == "id")
+        .map(|member| member.shared_identifier())
+        .collect()
+}
+
+#[test]
+fn interned_keys_share_one_allocation() {
+    let options = ParseOptions {
+        intern_keys: true,
+        ..ParseOptions::default()
+    };
+    let interned = parse_value(INPUT, &options).unwrap();
+    let ids = keys(&interned);
+    assert_eq!(ids.len(), 3);
+    assert!(ids
+        .iter()
+        .all(|id| Arc::ptr_eq(id.unwrap(), ids[0].unwrap())));
+
+    let owned = parse_value(INPUT, &ParseOptions::default()).unwrap();
+    assert!(keys(&owned).iter().all(Option::is_none));
+    assert_eq!(owned, interned);
+    // Five keys, two of them distinct, so three allocations fewer.
+    let allocations = |value: &JsonValue| value.footprint().allocations;
+    assert_eq!(allocations(&owned) - allocations(&interned), 3);
+}
+
+#[test]
+fn values_cross_threads() {
+    let options = ParseOptions {
+        intern_keys: true,
+        ..ParseOptions::default()
+    };
+    let value = Arc::new(parse_value(INPUT, &options).unwrap());
+    let shared = Arc::clone(&value);
+    let names = thread::spawn(move || shared.iter().filter_map(|v| v.get("name")).count());
+    assert_eq!(names.join().unwrap(), 2);
+}
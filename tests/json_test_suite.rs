@@ -0,0 +1,161 @@
+// Runs the parsing corpus of JSONTestSuite (https://github.com/nst/JSONTestSuite),
+// vendored in `tests/JSONTestSuite/test_parsing`: files starting with `y_` must be
+// accepted, those starting with `n_` rejected, and those starting with `i_` may go
+// either way. Nothing may panic.
+//
+// The outcome of every file in each parse mode is recorded in
+// `tests/JSONTestSuite/REPORT.md`; run with `UPDATE_REPORT=1` to regenerate it.
+
+use std::fs;
+use std::panic;
+use std::path::Path;
+
+use json_parser::{parse_bytes, ParseOptions};
+
+const SUITE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/JSONTestSuite");
+
+#[derive(Clone, PartialEq)]
+enum Outcome {
+    Accepted,
+    Rejected(String),
+    Panicked(String),
+}
+
+// With the default depth limit, which rejects `i_structure_500_nested_arrays`, so
+// that the suite runs on the test thread's stack as callers' code would.
+fn modes() -> Vec<(&'static str, ParseOptions)> {
+    vec![
+        (
+            "strict",
+            ParseOptions {
+                strict: true,
+                ..ParseOptions::default()
+            },
+        ),
+        ("default", ParseOptions::default()),
+        (
+            "json5",
+            ParseOptions {
+                json5: true,
+                ..ParseOptions::default()
+            },
+        ),
+    ]
+}
+
+fn run(bytes: &[u8], options: &ParseOptions) -> Outcome {
+    match panic::catch_unwind(|| parse_bytes(bytes, options).map_err(|e| e.message)) {
+        Ok(Ok(_)) => Outcome::Accepted,
+        Ok(Err(message)) => Outcome::Rejected(message),
+        Err(payload) => Outcome::Panicked(
+            payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+// Every file with its outcome in each mode, sorted by name.
+fn outcomes() -> Vec<(String, Vec<Outcome>)> {
+    let mut files: Vec<_> = fs::read_dir(Path::new(SUITE).join("test_parsing"))
+        .expect("the corpus is vendored")
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    let modes = modes();
+    files
+        .iter()
+        .map(|path| {
+            let bytes = fs::read(path).unwrap();
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let outcomes = modes.iter().map(|(_, o)| run(&bytes, o)).collect();
+            (name, outcomes)
+        })
+        .collect()
+}
+
+fn report(outcomes: &[(String, Vec<Outcome>)]) -> String {
+    let names: Vec<_> = modes().into_iter().map(|(name, _)| name).collect();
+    let mut out = String::from(
+        "# JSONTestSuite results\n\n\
+         Generated by `tests/json_test_suite.rs`. `y_` files must be accepted and `n_`\n\
+         files rejected; `i_` files are up to the parser. `parse_bytes` is run in each\n\
+         mode, decoding UTF-16 and UTF-32 and rejecting malformed encodings, with the\n\
+         default nesting limit of 128. Only strict mode has to reject every `n_` file:\n\
+         the others accept more on purpose.\n\n",
+    );
+    for (i, name) in names.iter().enumerate() {
+        let (mut expected, mut total) = (0, 0);
+        for (file, outcomes) in outcomes.iter().filter(|(file, _)| !file.starts_with("i_")) {
+            total += 1;
+            expected += (file.starts_with("y_") == (outcomes[i] == Outcome::Accepted)) as usize;
+        }
+        out.push_str(&format!(
+            "- {}: {} of {} `y_` and `n_` files as expected\n",
+            name, expected, total
+        ));
+    }
+    out.push_str(&format!("\n| file | {} |\n", names.join(" | ")));
+    out.push_str(&format!("|---{}|\n", "|---".repeat(names.len())));
+    for (file, outcomes) in outcomes {
+        let cells: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| {
+                let expected = match (file.as_bytes()[0], outcome) {
+                    (_, Outcome::Panicked(_)) => false,
+                    (b'y', outcome) => *outcome == Outcome::Accepted,
+                    (b'n', outcome) => *outcome != Outcome::Accepted,
+                    _ => true,
+                };
+                let cell = match outcome {
+                    Outcome::Accepted => "accepted",
+                    Outcome::Rejected(_) => "rejected",
+                    Outcome::Panicked(_) => "**panicked**",
+                };
+                match expected {
+                    true => cell.to_string(),
+                    false => format!("{} ✗", cell),
+                }
+            })
+            .collect();
+        out.push_str(&format!("| `{}` | {} |\n", file, cells.join(" | ")));
+    }
+    out
+}
+
+#[test]
+fn conformance() {
+    let outcomes = outcomes();
+    let mut failures = vec![];
+    for (file, outcomes) in &outcomes {
+        for ((mode, _), outcome) in modes().iter().zip(outcomes) {
+            let failure = match (file.as_bytes()[0], outcome) {
+                (_, Outcome::Panicked(message)) => Some(format!("panicked: {}", message)),
+                (b'y', Outcome::Rejected(message)) => Some(format!("rejected: {}", message)),
+                (b'n', Outcome::Accepted) if *mode == "strict" => Some("accepted".to_string()),
+                _ => None,
+            };
+            if let Some(failure) = failure {
+                failures.push(format!("{} ({}): {}", file, mode, failure));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n"));
+}
+
+#[test]
+fn report_is_up_to_date() {
+    let report = report(&outcomes());
+    let path = Path::new(SUITE).join("REPORT.md");
+    if std::env::var_os("UPDATE_REPORT").is_some() {
+        fs::write(&path, &report).unwrap();
+    }
+    let committed = fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        committed == report,
+        "{} is out of date, rerun with `UPDATE_REPORT=1`",
+        path.display()
+    );
+}
@@ -0,0 +1,60 @@
+use json_parser::{json, parse_value, JsonObject, JsonValue, Member, Number, ParseOptions};
+
+fn parse(input: &str) -> JsonValue {
+    parse_value(input, &ParseOptions::default()).unwrap()
+}
+
+#[test]
+fn literals_match_the_parsed_text() {
+    assert_eq!(json!(null), JsonValue::Null);
+    assert_eq!(json!(true), JsonValue::True);
+    assert_eq!(json!(false), JsonValue::False);
+    assert_eq!(json!([]), JsonValue::Array(vec![]));
+    assert_eq!(json!({}), JsonValue::Object(JsonObject::new(vec![])));
+    assert_eq!(
+        json!({"a": [1, null, {"b": false}], "c": {}, "d": "e", "f": -1.5}),
+        parse(r#"{"a": [1, null, {"b": false}], "c": {}, "d": "e", "f": -1.5}"#)
+    );
+    assert_eq!(
+        json!([[true], [[]], {"a": null}]),
+        parse(r#"[[true], [[]], {"a": null}]"#)
+    );
+}
+
+#[test]
+fn trailing_commas_are_accepted() {
+    assert_eq!(json!([1, 2,]), parse("[1, 2]"));
+    assert_eq!(json!({"a": 1,}), parse(r#"{"a": 1}"#));
+    assert_eq!(json!({"a": null,}), parse(r#"{"a": null}"#));
+}
+
+#[test]
+fn values_are_expressions() {
+    let name = "Ada";
+    let tags = vec!["math", "poetry"];
+    let age: Option<u8> = None;
+    assert_eq!(
+        json!({"name": name, "tags": tags, "age": age, "next": 1 + 1, "neg": -3}),
+        parse(r#"{"name": "Ada", "tags": ["math", "poetry"], "age": null, "next": 2, "neg": -3}"#)
+    );
+    assert_eq!(json!([name.len(), tags[1]]), parse(r#"[3, "poetry"]"#));
+    assert_eq!(json!(2.5), JsonValue::Number(Number::Float(2.5)));
+}
+
+#[test]
+fn keys_are_expressions() {
+    let prefix = "user".to_string();
+    let key = "k";
+    assert_eq!(
+        json!({(prefix.clone() + "_id"): 1, key: 2, 3: 4}),
+        parse(r#"{"user_id": 1, "k": 2, "3": 4}"#)
+    );
+    // Members keep the order they're written in, duplicates included.
+    match json!({"b": 1, "a": 2, "b": 3}) {
+        JsonValue::Object(object) => {
+            let keys: Vec<&str> = object.iter().map(Member::identifier).collect();
+            assert_eq!(keys, ["b", "a", "b"]);
+        }
+        other => panic!("expected an object, found {:?}", other),
+    }
+}
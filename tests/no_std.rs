@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+// Builds the library without its default `std` feature, as a `no_std` user would,
+// so that a stray `std` path or a `println!` fails here rather than downstream.
+#[test]
+fn builds_without_std() {
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("no_std");
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "build",
+            "--lib",
+            "--no-default-features",
+            "--features",
+            "derive",
+        ])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("cargo runs");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,330 @@
+use json_parser::serialize::to_string;
+use json_parser::{parse_value, Decimal, Number, ParseOptions};
+
+// What `Number::parse` reads `text` as: the bits of the double, or `None` when it
+// is too large for one.
+fn parsed(text: &str) -> Option<u64> {
+    Number::parse(text).map(|n| n.as_f64().to_bits())
+}
+
+// What std reads `text` as, with infinities as `None` as `Number::parse` has them.
+fn expected(text: &str) -> Option<u64> {
+    let f: f64 = text.parse().unwrap();
+    f.is_finite().then_some(f.to_bits())
+}
+
+fn assert_parses_as_std(text: &str) {
+    assert_eq!(parsed(text), expected(text), "{}", text);
+}
+
+// The significant digits of a number written either positionally or with an
+// exponent, without leading or trailing zeros.
+fn digits(text: &str) -> String {
+    let mantissa = text.split(['e', 'E']).next().unwrap();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    digits.trim_matches('0').to_string()
+}
+
+// `x` printed as a json number reads back as `x`, with as few digits as std prints
+// and the same ones, but for ties: like ECMAScript, and unlike std, the printer
+// takes the even digits when `x` is exactly halfway between the two nearest.
+fn assert_prints_shortest(x: f64) {
+    let text = Number::Float(x).to_string();
+    assert_eq!(
+        parsed(&text),
+        Some(x.to_bits()),
+        "{:e} printed as {}",
+        x,
+        text
+    );
+    let std = digits(&format!("{:e}", x));
+    let ours = format!("{:0<1$}", digits(&text), std.len());
+    if ours != std {
+        let exact = digits(&Decimal::from_f64(x).unwrap().to_string());
+        let even = ours.bytes().last().is_some_and(|digit| digit % 2 == 0);
+        assert!(
+            ours.len() == std.len() && exact == format!("{}5", ours) && even,
+            "{:e} printed as {}",
+            x,
+            text
+        );
+    }
+}
+
+// A small xorshift generator, so that failures reproduce.
+struct Random(u64);
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+// `(2k + 1) * 2^-1075`, the point halfway between the subnormals `k * 2^-1074` and
+// `(k + 1) * 2^-1074`, written out exactly: 1075 digits after the point.
+fn halfway(k: u64) -> String {
+    // Little-endian decimal digits of `(2k + 1) * 5^1075`.
+    let mut n: Vec<u64> = (2 * k + 1)
+        .to_string()
+        .bytes()
+        .rev()
+        .map(|b| (b - b'0') as u64)
+        .collect();
+    for _ in 0..1075 {
+        let mut carry = 0;
+        for digit in n.iter_mut() {
+            let product = *digit * 5 + carry;
+            *digit = product % 10;
+            carry = product / 10;
+        }
+        while carry > 0 {
+            n.push(carry % 10);
+            carry /= 10;
+        }
+    }
+    let digits: String = n.iter().rev().map(|d| (b'0' + *d as u8) as char).collect();
+    format!("0.{:0>1075}", digits)
+}
+
+#[test]
+fn halfway_cases_round_to_even() {
+    let cases = [
+        // 2^53 + 1, halfway between 2^53 and 2^53 + 2.
+        "9007199254740993.0",
+        "9007199254740993.00000000000000000000000000000000000001",
+        "9007199254740992.99999999999999999999999999999999999999",
+        // 2^53 + 3, halfway between 2^53 + 2 and 2^53 + 4.
+        "9007199254740995e0",
+        // 1 + 2^-53, halfway between 1 and the double after it.
+        "1.00000000000000011102230246251565404236316680908203125",
+        "1.00000000000000011102230246251565404236316680908203124",
+        "1.00000000000000011102230246251565404236316680908203126",
+        "1.000000000000000111022302462515654042363166809082031250000000000000001",
+        // The largest power of ten Clinger's fast path takes, and the first it doesn't.
+        "9007199254740993e22",
+        "9007199254740993e23",
+        "0.1",
+        "0.3",
+        "2.2250738585072011e-308",
+        "7.3177701707893310e+15",
+        "1448997445238699e0",
+    ];
+    for text in &cases {
+        assert_parses_as_std(text);
+    }
+    assert_eq!(
+        parsed("9007199254740993.0"),
+        Some(9_007_199_254_740_992f64.to_bits())
+    );
+    assert_eq!(
+        parsed("9007199254740995e0"),
+        Some(9_007_199_254_740_996f64.to_bits())
+    );
+}
+
+#[test]
+fn subnormals_and_the_smallest_double() {
+    let smallest = f64::from_bits(1);
+    let cases = [
+        "4.9406564584124654e-324",
+        "5e-324",
+        "4e-324",
+        "3e-324",
+        "2.4703282292062328e-324",
+        "2.4703282292062327e-324",
+        "2e-324",
+        "1e-400",
+        "-5e-324",
+        "-2e-324",
+        "2.2250738585072009e-308",
+        "2.2250738585072014e-308",
+        "2.225073858507201136057409796709131975934819546351645648e-308",
+        "1.23456789e-310",
+        "0.0000000000000000000000000000000000000000000000000001e-270",
+    ];
+    for text in &cases {
+        assert_parses_as_std(text);
+    }
+    assert_eq!(parsed("4.9406564584124654e-324"), Some(smallest.to_bits()));
+    assert_eq!(parsed("2e-324"), Some(0));
+    assert_eq!(parsed("-2e-324"), Some((-0.0f64).to_bits()));
+    assert_prints_shortest(smallest);
+    assert_prints_shortest(f64::MIN_POSITIVE);
+    assert_prints_shortest(f64::from_bits(f64::MIN_POSITIVE.to_bits() - 1));
+}
+
+#[test]
+fn the_largest_double_and_beyond() {
+    let cases = [
+        "1.7976931348623157e308",
+        "1.7976931348623158e308",
+        "1.7976931348623158079372897140530341507993413271003782693617377898044496829276475094664e308",
+        "1.7976931348623159e308",
+        "179769313486231580793728971405303415079934132710037826936173778980444968292764750946649017977587207096330286416692887910946555547851940402630657488671505820681908902000708383676273854845817711531764475730270069855571366959622842914819860834936475292719074168444365510704342711559699508093042880177904174497791.9",
+        "1.8e308",
+        "1e309",
+        "-1.7976931348623157e308",
+        "-1e309",
+        "1e99999999999999999999",
+    ];
+    for text in &cases {
+        assert_parses_as_std(text);
+    }
+    assert_eq!(parsed("1.7976931348623158e308"), Some(f64::MAX.to_bits()));
+    assert_eq!(parsed("1e309"), None);
+    assert_prints_shortest(f64::MAX);
+    assert_prints_shortest(f64::MIN);
+}
+
+// Halfway points between subnormals need all of their 700-odd digits, and a last
+// non-zero digit past the 767th decides which way they round.
+#[test]
+fn long_mantissas() {
+    for &k in &[0, 1, 2, 3, 1000, (1 << 52) - 2, (1 << 52) - 1] {
+        let exact = halfway(k);
+        let even = match k % 2 {
+            0 => k,
+            _ => k + 1,
+        };
+        assert_eq!(parsed(&exact), Some(even), "{}", k);
+        assert_parses_as_std(&exact);
+        let above = format!("{}{}1", exact, "0".repeat(800));
+        assert_eq!(parsed(&above), Some(k + 1), "{}", k);
+        assert_parses_as_std(&above);
+        // The digits of `(2k + 1) * 5^1075` end in a 5.
+        let below = format!("{}4{}", &exact[..exact.len() - 1], "9".repeat(800));
+        assert_eq!(parsed(&below), Some(k), "{}", k);
+        assert_parses_as_std(&below);
+    }
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for _ in 0..200 {
+        let len = 700 + random.below(200) as usize;
+        let mut text: String = (0..len)
+            .map(|_| (b'0' + random.below(10) as u8) as char)
+            .collect();
+        text.insert(0, (b'1' + random.below(9) as u8) as char);
+        let exponent = random.below(1100) as i64 - 1100;
+        let text = format!("{}e{}", text, exponent);
+        assert_parses_as_std(&text);
+    }
+    let eight_hundred = format!("0.{}", "3".repeat(800));
+    assert_parses_as_std(&eight_hundred);
+    assert_eq!(parsed(&eight_hundred), Some((1.0f64 / 3.0).to_bits()));
+}
+
+#[test]
+fn random_literals_parse_as_std_does() {
+    let mut random = Random(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..20_000 {
+        let len = 1 + random.below(25) as usize;
+        let digits: String = (0..len)
+            .map(|_| (b'0' + random.below(10) as u8) as char)
+            .collect();
+        let exponent = random.below(700) as i64 - 350;
+        let text = format!("{}e{}", digits.trim_start_matches('0'), exponent);
+        if text.starts_with('e') {
+            continue;
+        }
+        assert_parses_as_std(&text);
+    }
+}
+
+#[test]
+fn random_doubles_print_shortest_and_read_back() {
+    let mut random = Random(0xd1b5_4a32_d192_ed03);
+    for _ in 0..20_000 {
+        let x = f64::from_bits(random.next());
+        if !x.is_finite() {
+            continue;
+        }
+        assert_prints_shortest(x);
+        // Written with more digits than needed, they still read back as `x`.
+        for precision in &[16, 17, 25, 40] {
+            let text = format!("{:.*e}", precision, x);
+            assert_parses_as_std(&text);
+        }
+    }
+    for x in &[
+        0.1,
+        0.3,
+        1.0 / 3.0,
+        5e-324,
+        1e21,
+        1e-7,
+        123456789012345680.0,
+        9007199254740993.0,
+        2f64.powi(-1022),
+        2f64.powi(63),
+        1.7976931348623157e308,
+    ] {
+        assert_prints_shortest(*x);
+        assert_prints_shortest(-x);
+    }
+    // 134310626912275.625 is as close to ...62 as to ...63, which std prints.
+    let tie = 1_074_485_015_298_205.0 / 8.0;
+    assert_eq!(Number::Float(tie).to_string(), "134310626912275.62");
+    assert_eq!(format!("{}", tie), "134310626912275.63");
+}
+
+fn exact() -> ParseOptions {
+    ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn exact_numbers_keep_the_sign_of_zero() {
+    let cases = [
+        ("-0.0", "-0.0"),
+        ("-0.000", "-0.000"),
+        ("-0e5", "-0e+5"),
+        ("-0.00E-3", "-0.00000"),
+    ];
+    for (text, printed) in &cases {
+        let value = parse_value(text, &exact()).unwrap();
+        let number = value.as_number().unwrap();
+        assert!(matches!(number, Number::Decimal(_)), "{}", text);
+        assert_eq!(to_string(&value), *printed);
+        assert_eq!(number.as_f64().to_bits(), (-0.0f64).to_bits(), "{}", text);
+        assert_eq!(*number, Number::Integer(0));
+    }
+    let zero = Decimal::parse("-0.000").unwrap();
+    assert!(zero.is_sign_negative() && !zero.is_negative());
+    assert_eq!(zero, Decimal::parse("0").unwrap());
+    assert_eq!((-&zero).to_string(), "0.000");
+    assert_eq!((-&-&zero).to_string(), "-0.000");
+    assert!(!zero.abs().is_sign_negative());
+    assert_eq!(Decimal::from_f64(-0.0).unwrap().to_string(), "-0e+0");
+    assert_eq!(Decimal::from_f64(0.0).unwrap().to_string(), "0e+0");
+    // `-0` is an integer, and integers have a single zero.
+    assert_eq!(to_string(&parse_value("-0", &exact()).unwrap()), "0");
+    assert_eq!(
+        to_string(&parse_value("-0.0", &ParseOptions::default()).unwrap()),
+        "-0.0"
+    );
+}
+
+#[test]
+fn numbers_beyond_an_f64_need_exact_numbers() {
+    for text in &["1e400", "-1e400", "1.8e308"] {
+        let error = parse_value(text, &ParseOptions::default()).unwrap_err();
+        assert!(error.to_string().contains("is too large"), "{}", error);
+        assert_eq!(Number::parse(text), None);
+        let value = parse_value(text, &exact()).unwrap();
+        assert_eq!(
+            to_string(&value),
+            text.replace("e4", "e+4").replace("e3", "e+3")
+        );
+    }
+    // Underflow is not an error: tiny numbers round to zero.
+    assert_eq!(
+        to_string(&parse_value("1e-400", &ParseOptions::default()).unwrap()),
+        "0.0"
+    );
+}
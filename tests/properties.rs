@@ -0,0 +1,218 @@
+// Properties of generated values: every printer style reads back to the value it
+// printed, and every parse mode reads valid JSON to the same value.
+//
+// Failures are shrunk to a minimal counterexample; run with `JSON_PROPERTY_SEED=<n>` to
+// explore other values.
+
+use std::panic;
+
+use json_parser::arbitrary::{self, Alphabet, Generator, Numbers};
+use json_parser::diff::{diff, DiffOptions};
+use json_parser::document::Document;
+use json_parser::serialize::{to_string_with, Format};
+use json_parser::{
+    parse, parse_spanned, parse_value, JsonObject, JsonValue, Member, Number, ParseOptions,
+};
+
+const CASES: usize = 500;
+
+fn formats() -> Vec<(&'static str, Format)> {
+    vec![
+        ("minified", Format::minified()),
+        ("pretty", Format::pretty()),
+        (
+            "tabs",
+            Format {
+                indent: Some("\t".to_string()),
+                ..Format::default()
+            },
+        ),
+        (
+            "sorted",
+            Format {
+                sort_keys: true,
+                ..Format::pretty()
+            },
+        ),
+    ]
+}
+
+fn modes() -> Vec<(&'static str, ParseOptions)> {
+    vec![
+        (
+            "strict",
+            ParseOptions {
+                strict: true,
+                ..ParseOptions::default()
+            },
+        ),
+        ("default", ParseOptions::default()),
+        (
+            "json5",
+            ParseOptions {
+                json5: true,
+                ..ParseOptions::default()
+            },
+        ),
+        (
+            "interned",
+            ParseOptions {
+                intern_keys: true,
+                ..ParseOptions::default()
+            },
+        ),
+    ]
+}
+
+// Equal up to the order of members, which `sort_keys` changes.
+fn same(a: &JsonValue, b: &JsonValue) -> bool {
+    diff(a, b, &DiffOptions::default()).is_empty()
+}
+
+// Exact numbers compared as the floats the default mode reads.
+fn rounded(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Number(Number::Integer(i)) => JsonValue::Number(Number::Integer(*i)),
+        JsonValue::Number(n) => JsonValue::Number(Number::Float(n.as_f64())),
+        JsonValue::Array(values) => JsonValue::Array(values.iter().map(rounded).collect()),
+        JsonValue::Object(object) => JsonValue::Object(JsonObject::new(
+            object
+                .iter()
+                .map(|member| Member::new(member.identifier(), rounded(member.value())))
+                .collect(),
+        )),
+        other => other.clone(),
+    }
+}
+
+fn round_trips(value: &JsonValue) -> Result<(), String> {
+    for (name, format) in formats() {
+        let printed = to_string_with(value, &format);
+        let options = ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        };
+        match parse_value(&printed, &options) {
+            Ok(ref reparsed) if same(reparsed, value) => {}
+            other => {
+                return Err(format!(
+                    "{} printed {:?}, read back as {:?}",
+                    name, printed, other
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn modes_agree(value: &JsonValue) -> Result<(), String> {
+    let input = to_string_with(value, &Format::pretty());
+    for (name, options) in modes() {
+        match parse_value(&input, &options) {
+            Ok(ref parsed) if parsed == value => {}
+            other => return Err(format!("{} read {:?} as {:?}", name, input, other)),
+        }
+    }
+    let exact = ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    };
+    match parse_value(&input, &exact).map(|parsed| rounded(&parsed)) {
+        Ok(ref parsed) if parsed == value => {}
+        other => return Err(format!("exact read {:?} as {:?}", input, other)),
+    }
+    // The document-level parsers only read objects.
+    if let JsonValue::Object(object) = value {
+        let tree = parse(&input, &ParseOptions::default()).ok();
+        let spanned = parse_spanned(&input, &ParseOptions::default())
+            .ok()
+            .map(|spanned| spanned.value);
+        let document = Document::parse(&input).map(|document| document.to_value());
+        if tree.as_ref() != Some(object) {
+            return Err(format!("tree read {:?} as {:?}", input, tree));
+        }
+        if spanned.as_ref() != Some(value) {
+            return Err(format!("spanned read {:?} as {:?}", input, spanned));
+        }
+        if document.as_ref() != Some(value) {
+            return Err(format!("document read {:?} as {:?}", input, document));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn round_trip() {
+    arbitrary::check(&Generator::default(), CASES, round_trips);
+}
+
+#[test]
+fn round_trip_escapes() {
+    let generator = Generator {
+        alphabet: Alphabet::escapes(),
+        max_string_len: 32,
+        ..Generator::default()
+    };
+    arbitrary::check(&generator, CASES, round_trips);
+}
+
+#[test]
+fn modes_agree_on_valid_input() {
+    arbitrary::check(&Generator::default(), CASES, modes_agree);
+}
+
+#[test]
+fn modes_agree_on_objects() {
+    let generator = Generator::default();
+    arbitrary::check_with(
+        CASES,
+        0x0b7ec7,
+        |rng| JsonValue::Object(generator.object(rng)),
+        modes_agree,
+    );
+}
+
+#[test]
+fn modes_agree_on_small_integers() {
+    let generator = Generator {
+        numbers: Numbers {
+            integers: -1000..=1000,
+            floats: false,
+        },
+        ..Generator::default()
+    };
+    arbitrary::check(&generator, CASES, modes_agree);
+}
+
+// Whether a string among `value` and its descendants, keys aside, holds a quote.
+fn quoted(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::String(s) => s.contains('"'),
+        JsonValue::Array(values) => values.iter().any(quoted),
+        JsonValue::Object(object) => object.iter().any(|member| quoted(member.value())),
+        _ => false,
+    }
+}
+
+#[test]
+fn failures_shrink_to_a_minimal_counterexample() {
+    let generator = Generator::default();
+    let failure = panic::catch_unwind(|| {
+        arbitrary::check_with(
+            CASES,
+            0x5eed,
+            |rng| generator.value(rng),
+            |value| match quoted(value) {
+                true => Err("holds a quote".to_string()),
+                false => Ok(()),
+            },
+        )
+    })
+    .expect_err("some generated string holds a quote");
+    let message = failure.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains(r#"minimal counterexample: String("\"")"#),
+        "{}",
+        message
+    );
+}
@@ -0,0 +1,205 @@
+use std::alloc::System;
+
+use json_parser::footprint::{count, Counting};
+use json_parser::query::Filter;
+use json_parser::{json, parse_value, JsonValue, ParseOptions};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting(System);
+
+fn outputs(filter: &str, input: &JsonValue) -> Vec<JsonValue> {
+    Filter::parse(filter).unwrap().outputs(input).unwrap()
+}
+
+fn error(filter: &str, input: &JsonValue) -> String {
+    let error = Filter::parse(filter).unwrap().outputs(input).unwrap_err();
+    error.message().to_string()
+}
+
+fn document() -> JsonValue {
+    json!({
+        "name": "ops",
+        "items": [{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}, {"id": 3}],
+        "cursor": 1,
+        "nothing": null
+    })
+}
+
+#[test]
+fn paths() {
+    let input = document();
+    assert_eq!(outputs(".", &input), std::slice::from_ref(&input));
+    assert_eq!(outputs(".name", &input), [json!("ops")]);
+    assert_eq!(outputs(".items[0].id", &input), [json!(1)]);
+    assert_eq!(outputs(".items[-1].id", &input), [json!(3)]);
+    assert_eq!(outputs(".items[.cursor].id", &input), [json!(2)]);
+    assert_eq!(outputs(".missing.deeper", &input), [json!(null)]);
+    assert_eq!(outputs(".items[9]", &input), [json!(null)]);
+    assert_eq!(
+        outputs(".items[].id", &input),
+        [json!(1), json!(2), json!(3)]
+    );
+    assert_eq!(outputs(".items[1:][].id", &input), [json!(2), json!(3)]);
+    assert_eq!(outputs(".name[1:]", &input), [json!("ps")]);
+    assert_eq!(outputs(".items[:-2] | length", &input), [json!(1)]);
+}
+
+#[test]
+fn recursion_visits_everything_in_order() {
+    let input = json!({"a": [1, {"b": 2}]});
+    assert_eq!(
+        outputs("..", &input),
+        [
+            input.clone(),
+            json!([1, {"b": 2}]),
+            json!(1),
+            json!({"b": 2}),
+            json!(2)
+        ]
+    );
+}
+
+#[test]
+fn pipes_commas_and_construction() {
+    let input = document();
+    assert_eq!(outputs(".name, .cursor", &input), [json!("ops"), json!(1)]);
+    assert_eq!(
+        outputs("[.items[] | .id * 10]", &input),
+        [json!([10, 20, 30])]
+    );
+    assert_eq!(
+        outputs("{name, first: .items[0].id}", &input),
+        [json!({"name": "ops", "first": 1})]
+    );
+    assert_eq!(
+        outputs("{(.name): (1, 2)}", &input),
+        [json!({"ops": 1}), json!({"ops": 2})]
+    );
+    // Computed values go through the rest of a pipe like parts of the input do.
+    assert_eq!(outputs("{a: [1, 2]} | .a[]", &input), [json!(1), json!(2)]);
+    assert_eq!(outputs("[3, 4] | .[1:] | .[0]", &input), [json!(4)]);
+}
+
+#[test]
+fn errors_and_alternatives() {
+    let input = document();
+    assert_eq!(error(".name[0]", &input), "Cannot index string with number");
+    assert_eq!(error(".name.x", &input), "Cannot index string with \"x\"");
+    assert_eq!(error(".cursor[]", &input), "Cannot iterate over number");
+    assert_eq!(outputs(".name.x?", &input), Vec::<JsonValue>::new());
+    assert_eq!(outputs("(.name.x)?, 1", &input), [json!(1)]);
+    assert_eq!(outputs(".nothing // .cursor", &input), [json!(1)]);
+    assert_eq!(outputs(".name // .cursor", &input), [json!("ops")]);
+    assert_eq!(outputs(".name.x // 2", &input), [json!(2)]);
+    assert_eq!(
+        outputs("(.items[].tags[0]) // \"none\"", &input),
+        [json!("a")]
+    );
+}
+
+#[test]
+fn arithmetic_and_comparisons() {
+    let input = json!(null);
+    assert_eq!(
+        outputs("(1, 2) + (10, 20)", &input),
+        [11, 12, 21, 22].map(|n| json!(n))
+    );
+    assert_eq!(
+        outputs("7 / 2, 7 % 2, -7 % 2", &input),
+        [json!(3.5), json!(1), json!(-1)]
+    );
+    assert_eq!(outputs("\"a,b\" / \",\"", &input), [json!(["a", "b"])]);
+    assert_eq!(outputs("[1, 2, 1] - [1]", &input), [json!([2])]);
+    assert_eq!(
+        outputs("{a: 1} + {a: 2, b: 3}", &input),
+        [json!({"a": 2, "b": 3})]
+    );
+    assert_eq!(error("1 / 0", &input), "1 cannot be divided by zero");
+    assert_eq!(
+        error("\"a\" - 1", &input),
+        "string (\"a\") and number (1) cannot be subtracted"
+    );
+    assert_eq!(
+        outputs(
+            "[null < false, false < 0, 0 < \"\", \"\" < [], [] < {}]",
+            &input
+        ),
+        [json!([true, true, true, true, true])]
+    );
+    assert_eq!(
+        outputs("{b: 1, a: 2} == {a: 2, b: 1}", &input),
+        [json!(true)]
+    );
+    assert_eq!(
+        outputs("1 == 1.0, [1, 2] < [1, 3]", &input),
+        [json!(true), json!(true)]
+    );
+    assert_eq!(
+        outputs("true and (true, false), false or null", &input),
+        [json!(true), json!(false), json!(false)]
+    );
+    assert_eq!(outputs("-(1, 2.5)", &input), [json!(-1), json!(-2.5)]);
+}
+
+#[test]
+fn exact_numbers_stay_exact() {
+    let options = ParseOptions {
+        exact_numbers: true,
+        ..ParseOptions::default()
+    };
+    let input = parse_value("[18446744073709551616, 0.1]", &options).unwrap();
+    let sum = outputs(".[0] + 1, .[1] + .[1]", &input);
+    assert_eq!(sum[0].to_string(), "18446744073709551617");
+    assert_eq!(sum[1].to_string(), "0.2");
+}
+
+#[test]
+fn builtins() {
+    let input = document();
+    assert_eq!(
+        outputs(".items[] | select(.id > 1) | .id", &input),
+        [json!(2), json!(3)]
+    );
+    assert_eq!(outputs(".items | map(.id)", &input), [json!([1, 2, 3])]);
+    assert_eq!(
+        outputs("keys", &input),
+        [json!(["cursor", "items", "name", "nothing"])]
+    );
+    assert_eq!(outputs(".items | keys", &input), [json!([0, 1, 2])]);
+    assert_eq!(
+        outputs("[.name, .items, .nothing, -5] | map(length)", &input),
+        [json!([3, 3, 0, 5])]
+    );
+    assert_eq!(outputs("[.items[].id] | add", &input), [json!(6)]);
+    assert_eq!(outputs("[] | add", &input), [json!(null)]);
+    assert_eq!(
+        outputs("has(\"name\"), has(\"x\")", &input),
+        [json!(true), json!(false)]
+    );
+    assert_eq!(
+        outputs(".items | has(2), has(3)", &input),
+        [json!(true), json!(false)]
+    );
+    assert_eq!(
+        outputs("[.[] | type]", &input),
+        [json!(["string", "array", "number", "null"])]
+    );
+    assert_eq!(
+        outputs("[.nothing | not], [empty]", &input),
+        [json!([true]), json!([])]
+    );
+    assert_eq!(error("true | length", &input), "boolean has no length");
+}
+
+#[test]
+fn paths_only_copy_what_they_output() {
+    let items: Vec<JsonValue> = (0..1000)
+        .map(|i| json!({"id": i, "tags": ["a", "b"]}))
+        .collect();
+    let input = json!({"items": items, "name": "big"});
+    let filter = Filter::parse(".items[500].tags, (.items[] | select(.id == 999) | .id)").unwrap();
+    let (result, allocations) = count(|| filter.outputs(&input));
+    assert_eq!(result.unwrap(), [json!(["a", "b"]), json!(999)]);
+    // The two outputs and the vector holding them, rather than copies of the input.
+    assert!(allocations.count < 10, "{:?}", allocations);
+}